@@ -0,0 +1,60 @@
+//! 构建时为 static/ 中的文本文件生成 gzip 和 brotli 预压缩版本，放在 $OUT_DIR/static/ 下，
+//! 由 assets.rs 嵌入程序，响应时按 Accept-Encoding 直接返回，不在请求时压缩。
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+// 图片等已压缩的格式再压缩没有收益
+const COMPRESSIBLE: [&str; 7] = ["html", "css", "js", "json", "svg", "txt", "map"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=static");
+    let out_dir = std::env::var("OUT_DIR").expect("cargo 设置了 OUT_DIR");
+    let out = Path::new(&out_dir).join("static");
+    // 源文件删除后不能留下旧的压缩版本
+    let _ = fs::remove_dir_all(&out);
+    fs::create_dir_all(&out).expect("创建预压缩目录");
+    compress_dir(Path::new("static"), Path::new("static"), &out);
+}
+
+fn compress_dir(root: &Path, dir: &Path, out: &Path) {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .expect("读取 static 目录")
+        .map(|entry| entry.expect("读取目录项").path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            compress_dir(root, &path, out);
+            continue;
+        }
+        let compressible = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| COMPRESSIBLE.contains(&ext));
+        if !compressible {
+            continue;
+        }
+        let data = fs::read(&path).expect("读取静态文件");
+        let target = out.join(path.strip_prefix(root).expect("文件在 static 目录下"));
+        fs::create_dir_all(target.parent().expect("文件有上级目录")).expect("创建预压缩目录");
+        let name = target.file_name().expect("文件有名字").to_string_lossy();
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        gzip.write_all(&data).expect("gzip 压缩");
+        fs::write(
+            target.with_file_name(format!("{}.gz", name)),
+            gzip.finish().expect("gzip 压缩"),
+        )
+        .expect("写入 gzip 文件");
+
+        let mut brotli = Vec::new();
+        let params = brotli::enc::BrotliEncoderParams {
+            quality: 11,
+            ..Default::default()
+        };
+        brotli::BrotliCompress(&mut data.as_slice(), &mut brotli, &params).expect("brotli 压缩");
+        fs::write(target.with_file_name(format!("{}.br", name)), brotli).expect("写入 brotli 文件");
+    }
+}
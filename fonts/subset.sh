@@ -0,0 +1,28 @@
+#!/bin/sh
+# 从完整字体中截取排盘图片用到的字，生成体积很小的子集放在 fonts/ 下，编译时嵌入程序。
+# 用法：fonts/subset.sh NotoSerifSC-Regular.otf [NotoSansSymbols2-Regular.ttf ...]
+# 依赖 fonttools（pip install fonttools）。
+set -e
+if [ $# -eq 0 ]; then
+    echo "用法: $0 字体文件 [字体文件 ...]" >&2
+    exit 1
+fi
+cd "$(dirname "$0")/.."
+
+# 源码中出现的所有非 ASCII 字符（卦名、六亲、干支、关系等）加上可打印 ASCII
+chars=$(mktemp)
+trap 'rm -f "$chars"' EXIT
+python3 - "$chars" <<'PY'
+import pathlib, sys
+text = "".join(p.read_text(encoding="utf-8") for p in pathlib.Path("src").glob("*.rs"))
+used = {c for c in text if ord(c) > 0x7E} | {chr(c) for c in range(0x20, 0x7F)}
+pathlib.Path(sys.argv[1]).write_text("".join(sorted(used)), encoding="utf-8")
+PY
+
+for font in "$@"; do
+    name=$(basename "$font")
+    ext="${name##*.}"
+    out="fonts/${name%.*}-subset.$ext"
+    pyftsubset "$font" --text-file="$chars" --output-file="$out" --layout-features='*'
+    echo "已生成 $out"
+done
@@ -0,0 +1,125 @@
+use crate::{batch, calendar, interchange, readings, yingqi};
+use actix_web::{
+    body::{self, BoxBody},
+    dev::{ServiceRequest, ServiceResponse},
+    error::ErrorInternalServerError,
+    http::header,
+    middleware::{from_fn, Next},
+    web, HttpResponse, Responder,
+};
+use serde::Serialize;
+use utoipa::OpenApi;
+
+/// /api/v1 下所有错误响应的格式。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ApiError {
+    /// HTTP 状态码
+    pub status: u16,
+    /// 错误说明，例如 "请输入6位数字（0-3）"
+    pub message: String,
+    /// 逐项的错误，目前只有导入交换文件时给出
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// /api/v1 的 OpenAPI 描述，由各处理函数上的注解生成。
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "六爻排盘 API", description = "起卦排盘、排盘图片、历法和占卜记录。"),
+    paths(
+        crate::generate_gua_xian,
+        batch::charts_batch,
+        crate::render_svg,
+        crate::render_png,
+        calendar::calendar,
+        readings::list_readings,
+        readings::create_reading,
+        readings::outcome_report,
+        readings::get_reading,
+        readings::update_reading,
+        readings::delete_reading,
+        readings::render_reading,
+        readings::set_outcome,
+        readings::clear_outcome,
+        readings::set_yingqi,
+        readings::clear_yingqi,
+        interchange::export,
+        interchange::import,
+        yingqi::yingqi,
+    ),
+    tags(
+        (name = "cast", description = "起卦排盘"),
+        (name = "chart", description = "排盘图片"),
+        (name = "calendar", description = "干支历与节气"),
+        (name = "readings", description = "占卜记录与应验"),
+    )
+)]
+pub struct ApiDoc;
+
+/// 注册 /api/v1 下的接口。旧的无版本路由仍在 main::routes 中保留。
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/v1")
+            .wrap(from_fn(json_errors))
+            .route("/openapi.json", web::get().to(openapi))
+            .route("/cast", web::post().to(crate::generate_gua_xian))
+            .route("/charts:batch", web::post().to(batch::charts_batch))
+            .route("/chart.svg", web::get().to(crate::render_svg))
+            .route("/chart.png", web::get().to(crate::render_png))
+            .route("/calendar", web::get().to(calendar::calendar))
+            .route("/yingqi", web::post().to(yingqi::yingqi))
+            .configure(readings::configure)
+            .configure(interchange::configure),
+    );
+}
+
+// 生成的 OpenAPI 文档
+async fn openapi() -> impl Responder {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+// 把错误响应统一改写为 ApiError：处理函数返回的 JSON 字符串取其内容，
+// 提取器返回的纯文本原样保留，没有正文时（如 404）用状态码的原因短语；
+// 处理函数已返回 JSON 对象（ApiError 或导入报告）时不改写
+async fn json_errors(
+    req: ServiceRequest,
+    next: Next<BoxBody>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let res = next.call(req).await?;
+    let status = res.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(res);
+    }
+    let (req, res) = res.into_parts();
+    let (head, body) = res.into_parts();
+    let bytes = body::to_bytes(body)
+        .await
+        .map_err(ErrorInternalServerError)?;
+    if serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(&bytes).is_ok() {
+        return Ok(ServiceResponse::new(
+            req,
+            head.set_body(BoxBody::new(bytes)),
+        ));
+    }
+    let message = serde_json::from_slice::<String>(&bytes)
+        .ok()
+        .or_else(|| {
+            std::str::from_utf8(&bytes)
+                .ok()
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or_default().to_string());
+
+    let mut response = HttpResponse::build(status).json(ApiError {
+        status: status.as_u16(),
+        message,
+        errors: Vec::new(),
+    });
+    for (name, value) in head.headers() {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            response.headers_mut().append(name.clone(), value.clone());
+        }
+    }
+    Ok(ServiceResponse::new(req, response))
+}
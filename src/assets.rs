@@ -0,0 +1,190 @@
+use actix_web::{
+    http::header::{self, HeaderValue},
+    web, HttpRequest, HttpResponse,
+};
+use rust_embed::{EmbeddedFile, RustEmbed};
+use std::time::{Duration, UNIX_EPOCH};
+
+// 嵌入整个 static 目录（递归所有文件）
+#[derive(RustEmbed)]
+#[folder = "static/"]
+struct Asset;
+
+// build.rs 生成的预压缩文件，例如 "js/app.js.br"、"js/app.js.gz"
+#[derive(RustEmbed)]
+#[folder = "$OUT_DIR/static/"]
+struct Precompressed;
+
+/// 预压缩文件的编码方式，按优先级排列（brotli 优先于 gzip）。
+#[derive(Debug, Clone, Copy)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    /// 预压缩文件相对于原文件追加的后缀，例如 "app.js.br"。
+    fn suffix(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => ".br",
+            Encoding::Gzip => ".gz",
+        }
+    }
+
+    /// Accept-Encoding / Content-Encoding 中使用的名称。
+    fn token(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+}
+
+/// 注册网页界面的静态文件路由，按 Accept-Encoding 返回构建时生成的 brotli 或 gzip 版本。
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // 捕获static所有文件路径请求，注意这里的正则表达式
+    cfg.route("/{filename:.*}", web::get().to(embedded_file));
+}
+
+// 通用处理函数，根据请求路径返回嵌入的文件
+async fn embedded_file(req: HttpRequest, path: web::Path<String>) -> HttpResponse {
+    // 如果请求为空或是目录，则默认返回该目录下的 index.html
+    let mut file_path = path.into_inner();
+    if file_path.is_empty() || file_path.ends_with('/') {
+        file_path.push_str("index.html");
+    }
+
+    // 原文件必须存在，预压缩文件只作为它的替代表示
+    let Some(original) = Asset::get(&file_path) else {
+        return HttpResponse::NotFound()
+            .content_type("text/plain; charset=utf-8")
+            .body("File not found");
+    };
+    // 修改时间取自原文件，预压缩文件是构建时才生成的
+    let last_modified = original
+        .metadata
+        .last_modified()
+        .map(|secs| header::HttpDate::from(UNIX_EPOCH + Duration::from_secs(secs)));
+    let has_variants =
+        Precompressed::get(&format!("{}{}", file_path, Encoding::Gzip.suffix())).is_some();
+
+    // 客户端支持且存在预压缩版本时，优先返回压缩后的内容
+    let (content, encoding) = accepted_encodings(&req)
+        .into_iter()
+        .find_map(|enc| {
+            Precompressed::get(&format!("{}{}", file_path, enc.suffix())).map(|c| (c, enc))
+        })
+        .map_or((original, None), |(c, enc)| (c, Some(enc)));
+    // 每种编码的内容不同，ETag 取各自内容的哈希，缓存不会把一种编码当作另一种
+    let etag = etag_of(&content);
+
+    let not_modified = is_not_modified(&req, &etag, last_modified);
+    let mut builder = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    builder
+        .insert_header((header::ETAG, etag))
+        // 文件名不带内容指纹，每次都用 ETag 重新验证，部署后浏览器不会继续使用旧的前端
+        .insert_header((header::CACHE_CONTROL, "no-cache"));
+    if let Some(date) = last_modified {
+        builder.insert_header((header::LAST_MODIFIED, date));
+    }
+    if has_variants {
+        builder.insert_header((header::VARY, "Accept-Encoding"));
+    }
+    if let Some(enc) = encoding {
+        builder.insert_header((header::CONTENT_ENCODING, enc.token()));
+    }
+
+    // 304 响应不带 body，也不需要 Content-Type
+    if not_modified {
+        return builder.finish();
+    }
+    builder
+        .content_type(content_type_of(&file_path))
+        .body(content.data.into_owned())
+}
+
+/// 根据文件扩展名推断 Content-Type，文本类型追加 utf-8 字符集。
+fn content_type_of(file_path: &str) -> String {
+    let mime = mime_guess::from_path(file_path).first_or_octet_stream();
+    let is_text = mime.type_() == mime_guess::mime::TEXT
+        || mime.subtype() == mime_guess::mime::JAVASCRIPT
+        || mime.subtype() == mime_guess::mime::JSON;
+    if is_text {
+        format!("{}; charset=utf-8", mime.essence_str())
+    } else {
+        mime.essence_str().to_string()
+    }
+}
+
+/// 使用 rust-embed 编译期计算好的 sha256 作为强 ETag。
+fn etag_of(content: &EmbeddedFile) -> String {
+    let hex: String = content
+        .metadata
+        .sha256_hash()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("\"{}\"", hex)
+}
+
+/// 解析 Accept-Encoding，返回客户端可接受且我们支持的编码（按优先级）。
+fn accepted_encodings(req: &HttpRequest) -> Vec<Encoding> {
+    let accept = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let accepts = |token: &str| {
+        accept.split(',').any(|part| {
+            let mut params = part.split(';').map(str::trim);
+            let name_matches = params
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case(token) || name == "*");
+            // q=0 表示明确拒绝该编码
+            let rejected = params.any(|p| {
+                p.strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            name_matches && !rejected
+        })
+    };
+    [Encoding::Brotli, Encoding::Gzip]
+        .into_iter()
+        .filter(|enc| accepts(enc.token()))
+        .collect()
+}
+
+/// 判断条件请求是否命中缓存：If-None-Match 优先，其次 If-Modified-Since。
+fn is_not_modified(req: &HttpRequest, etag: &str, last_modified: Option<header::HttpDate>) -> bool {
+    let headers = req.headers();
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        return etag_matches(if_none_match, etag);
+    }
+    match (
+        headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<header::HttpDate>().ok()),
+        last_modified,
+    ) {
+        (Some(since), Some(modified)) => {
+            std::time::SystemTime::from(modified) <= std::time::SystemTime::from(since)
+        }
+        _ => false,
+    }
+}
+
+// If-None-Match 允许以逗号分隔多个 ETag，且使用弱比较（忽略 W/ 前缀）
+fn etag_matches(if_none_match: &HeaderValue, etag: &str) -> bool {
+    if_none_match.to_str().is_ok_and(|value| {
+        value
+            .split(',')
+            .map(str::trim)
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+    })
+}
@@ -0,0 +1,347 @@
+use crate::{
+    cli::parse_datetime_with_offset, config::Config, locale::Locale, paipan_solar,
+    solar_time::SolarTimeOptions, GuaResponse, ZiShi,
+};
+use actix_web::{
+    error::{ErrorBadRequest, ErrorInternalServerError},
+    web::{self, Bytes},
+    HttpMessage, HttpRequest, HttpResponse,
+};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// 一行输入最多多少字节，超过时中止，避免没有换行的请求占满内存。
+const MAX_LINE_BYTES: usize = 64 * 1024;
+/// HTTP 接口每次交给线程池排盘的最多行数。
+const CHUNK_LINES: usize = 64;
+
+/// 批量排盘的输入格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BatchFormat {
+    /// 每行一个 JSON 对象
+    #[value(name = "jsonl")]
+    JsonLines,
+    /// 首行为表头，需有 numbers、datetime 列，question、longitude 列可选
+    Csv,
+}
+
+/// 批量排盘的一条记录。
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct BatchRecord {
+    /// 六次阳面个数，例如 "311113"
+    pub numbers: String,
+    /// 起卦时间，格式同 paipan --at；历史记录必须给出，不取当前时间
+    pub datetime: String,
+    #[serde(default)]
+    pub question: String,
+    /// 给出经度时按真太阳时排盘，datetime 带时区时按该时区定标准经线
+    #[serde(flatten)]
+    pub solar_time: Option<SolarTimeOptions>,
+}
+
+/// 一条记录的排盘结果，与输入逐行对应。出错的记录 chart 为 null 并给出 error，不影响其余记录。
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct BatchResult {
+    /// 输入中的行号，从1开始，CSV 的表头为第1行
+    pub line: usize,
+    pub question: Option<String>,
+    pub chart: Option<GuaResponse>,
+    pub error: Option<String>,
+}
+
+impl BatchResult {
+    /// 序列化为 JSON Lines 的一行。
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("排盘结果总能序列化为 JSON");
+        line.push('\n');
+        line
+    }
+}
+
+/// CSV 表头中各字段所在的列。
+#[derive(Debug, Clone, Copy)]
+struct Columns {
+    numbers: usize,
+    datetime: usize,
+    question: Option<usize>,
+    longitude: Option<usize>,
+}
+
+/// 逐行读入的批量排盘，HTTP 接口和命令行共用。
+pub struct Batch {
+    format: BatchFormat,
+    config: Config,
+    zi_shi: ZiShi,
+    locale: Locale,
+    /// CSV 读到表头后才有；表头缺列时为错误，之后每行都报同样的错
+    columns: Option<Result<Columns, String>>,
+    line: usize,
+}
+
+impl Batch {
+    pub fn new(format: BatchFormat, config: &Config, zi_shi: ZiShi, locale: Locale) -> Self {
+        Batch {
+            format,
+            config: config.clone(),
+            zi_shi,
+            locale,
+            columns: None,
+            line: 0,
+        }
+    }
+
+    /// 处理一行输入。空行和 CSV 表头没有输出，返回 None。
+    pub fn next_line(&mut self, text: &str) -> Option<BatchResult> {
+        self.line += 1;
+        let text = text.trim_end_matches(['\r', '\n']);
+        if text.trim().is_empty() {
+            return None;
+        }
+        let record = match self.format {
+            BatchFormat::JsonLines => serde_json::from_str(text).map_err(|e| e.to_string()),
+            BatchFormat::Csv => match &self.columns {
+                None => {
+                    self.columns = Some(csv_header(&split_csv(text)));
+                    return None;
+                }
+                Some(columns) => columns
+                    .clone()
+                    .and_then(|columns| csv_record(&split_csv(text), columns)),
+            },
+        };
+        let mut result = BatchResult {
+            line: self.line,
+            question: None,
+            chart: None,
+            error: None,
+        };
+        match record.and_then(|record| self.chart(&record).map(|chart| (record, chart))) {
+            Ok((record, chart)) => {
+                result.question = Some(record.question);
+                result.chart = Some(chart);
+            }
+            Err(e) => result.error = Some(e),
+        }
+        Some(result)
+    }
+
+    fn chart(&self, record: &BatchRecord) -> Result<GuaResponse, String> {
+        let (at, utc_offset) = parse_datetime_with_offset(&record.datetime)?;
+        let mut chart = paipan_solar(
+            &self.config,
+            &record.numbers,
+            at,
+            utc_offset,
+            self.zi_shi,
+            record.solar_time,
+        )
+        .map_err(|e| e.to_string())?;
+        chart.localize(self.locale);
+        Ok(chart)
+    }
+}
+
+// 按 RFC 4180 拆分一行 CSV：字段可用双引号包住，引号内的 "" 表示一个引号。
+// 不支持引号内换行，每条记录必须在一行内
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    fields
+}
+
+fn csv_header(header: &[String]) -> Result<Columns, String> {
+    let find = |name: &str| header.iter().position(|h| h.trim() == name);
+    let required = |name: &str| find(name).ok_or_else(|| format!("CSV 表头缺少 {} 列", name));
+    Ok(Columns {
+        numbers: required("numbers")?,
+        datetime: required("datetime")?,
+        question: find("question"),
+        longitude: find("longitude"),
+    })
+}
+
+fn csv_record(fields: &[String], columns: Columns) -> Result<BatchRecord, String> {
+    let field = |i: usize| {
+        fields
+            .get(i)
+            .map(|f| f.trim().to_string())
+            .unwrap_or_default()
+    };
+    // longitude 列为空表示这一条不做真太阳时校正
+    let solar_time = match columns.longitude.map(field).filter(|l| !l.is_empty()) {
+        Some(longitude) => Some(SolarTimeOptions {
+            longitude: longitude
+                .parse()
+                .map_err(|_| format!("longitude 列的值 \"{}\" 不是数字", longitude))?,
+            equation_of_time: true,
+        }),
+        None => None,
+    };
+    Ok(BatchRecord {
+        numbers: field(columns.numbers),
+        datetime: field(columns.datetime),
+        question: columns.question.map(field).unwrap_or_default(),
+        solar_time,
+    })
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BatchQuery {
+    /// 23点子时的日柱归属，缺省时使用配置
+    zi_shi: Option<ZiShi>,
+    /// 文本字段的语言，缺省时使用配置
+    locale: Option<Locale>,
+}
+
+// 流式读取请求体时的状态
+struct Upload {
+    payload: web::Payload,
+    /// 排盘时移进线程池，排完再放回
+    batch: Option<Batch>,
+    buffer: Vec<u8>,
+    /// 已排好、还没发出的结果
+    pending: VecDeque<Bytes>,
+    done: bool,
+}
+
+impl Upload {
+    // 从缓冲区取出至多 CHUNK_LINES 个完整的行；读完请求体后最后一行可以没有换行
+    fn take_lines(&mut self) -> Vec<Vec<u8>> {
+        let mut lines = Vec::new();
+        while lines.len() < CHUNK_LINES {
+            match self.buffer.iter().position(|&b| b == b'\n') {
+                Some(end) => lines.push(self.buffer.drain(..=end).collect()),
+                None if self.done && !self.buffer.is_empty() => {
+                    lines.push(std::mem::take(&mut self.buffer))
+                }
+                None => break,
+            }
+        }
+        lines
+    }
+
+    // 取出下一行有输出的结果；读完或出错后返回 None。
+    // 排盘要查历法，按块放到线程池里做，不占用处理请求的线程
+    async fn next(&mut self) -> Option<Result<Bytes, actix_web::Error>> {
+        loop {
+            if let Some(line) = self.pending.pop_front() {
+                return Some(Ok(line));
+            }
+            let lines = self.take_lines();
+            if !lines.is_empty() {
+                let mut batch = self.batch.take()?;
+                let charted = web::block(move || {
+                    let results: Vec<Bytes> = lines
+                        .iter()
+                        .filter_map(|line| batch.next_line(&String::from_utf8_lossy(line)))
+                        .map(|result| result.to_line().into())
+                        .collect();
+                    (batch, results)
+                })
+                .await;
+                match charted {
+                    Ok((batch, results)) => {
+                        self.batch = Some(batch);
+                        self.pending.extend(results);
+                    }
+                    Err(e) => {
+                        self.done = true;
+                        self.buffer.clear();
+                        return Some(Err(ErrorInternalServerError(e)));
+                    }
+                }
+                continue;
+            }
+            if self.done {
+                return None;
+            }
+            match self.payload.next().await {
+                Some(Ok(chunk)) => {
+                    self.buffer.extend_from_slice(&chunk);
+                    if self.buffer.len() > MAX_LINE_BYTES && !self.buffer.contains(&b'\n') {
+                        self.done = true;
+                        self.buffer.clear();
+                        let line = self.batch.as_ref().map_or(0, |batch| batch.line);
+                        return Some(Err(ErrorBadRequest(format!(
+                            "第 {} 行超过 {} 字节",
+                            line + 1,
+                            MAX_LINE_BYTES
+                        ))));
+                    }
+                }
+                Some(Err(e)) => {
+                    self.done = true;
+                    self.buffer.clear();
+                    return Some(Err(e.into()));
+                }
+                None => self.done = true,
+            }
+        }
+    }
+}
+
+/// 批量排盘：请求体为 JSON Lines（Content-Type 为 text/csv 时按 CSV 读），
+/// 每条记录排盘后立即以 JSON Lines 返回，大批量数据不必整体读入内存。
+#[utoipa::path(
+    post,
+    path = "/api/v1/charts:batch",
+    tag = "cast",
+    params(BatchQuery),
+    request_body(
+        description = "每行一条记录",
+        content(
+            (BatchRecord = "application/x-ndjson"),
+            (String = "text/csv"),
+        ),
+    ),
+    responses(
+        (status = 200, description = "每行一条结果，与输入逐行对应", content_type = "application/x-ndjson", body = BatchResult),
+    )
+)]
+pub async fn charts_batch(
+    config: web::Data<Config>,
+    req: HttpRequest,
+    query: web::Query<BatchQuery>,
+    payload: web::Payload,
+) -> HttpResponse {
+    let csv = matches!(req.mime_type(), Ok(Some(mime)) if mime.essence_str() == "text/csv");
+    let format = if csv {
+        BatchFormat::Csv
+    } else {
+        BatchFormat::JsonLines
+    };
+    let upload = Upload {
+        payload,
+        batch: Some(Batch::new(
+            format,
+            &config,
+            query.zi_shi.unwrap_or(config.zi_shi),
+            query.locale.unwrap_or(config.locale),
+        )),
+        buffer: Vec::new(),
+        pending: VecDeque::new(),
+        done: false,
+    };
+    let results = stream::unfold(upload, |mut upload| async move {
+        upload.next().await.map(|item| (item, upload))
+    });
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(results)
+}
@@ -0,0 +1,323 @@
+use crate::{
+    build_guas, get_ganzhi_info, get_sheng_ke_relation, locale::Locale, DiZhi, PaipanError,
+    ShengKe, WuXing, ZiShi, SUPPORTED_YEARS, TIAN_GAN,
+};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use lunar_rust::{
+    lunar::LunarRefHelper,
+    solar::{self, SolarRefHelper},
+};
+use serde::Serialize;
+
+/// 十天干的五行，与 TIAN_GAN 顺序一致；序号为偶数的是阳干。
+const GAN_WUXING: [WuXing; 10] = [
+    WuXing::Mu,
+    WuXing::Mu,
+    WuXing::Huo,
+    WuXing::Huo,
+    WuXing::Tu,
+    WuXing::Tu,
+    WuXing::Jin,
+    WuXing::Jin,
+    WuXing::Shui,
+    WuXing::Shui,
+];
+
+/// 按五行相生顺序列出，用于输出五行统计。
+const WUXING_ORDER: [WuXing; 5] = [
+    WuXing::Mu,
+    WuXing::Huo,
+    WuXing::Tu,
+    WuXing::Jin,
+    WuXing::Shui,
+];
+
+const PILLAR_LABELS: [&str; 4] = ["年柱", "月柱", "日柱", "时柱"];
+
+// 十二地支的藏干（本气、中气、余气），与 DiZhi 顺序一致
+fn cang_gan(zhi: DiZhi) -> &'static [char] {
+    const CANG_GAN: [&[char]; 12] = [
+        &['癸'],
+        &['己', '癸', '辛'],
+        &['甲', '丙', '戊'],
+        &['乙'],
+        &['戊', '乙', '癸'],
+        &['丙', '庚', '戊'],
+        &['丁', '己'],
+        &['己', '丁', '乙'],
+        &['庚', '壬', '戊'],
+        &['辛'],
+        &['戊', '辛', '丁'],
+        &['壬', '甲'],
+    ];
+    CANG_GAN[zhi as usize]
+}
+
+/// 一个天干相对日主的十神，例如 "正财"。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Gan {
+    pub gan: String,
+    pub wuxing: String,
+    pub shishen: &'static str,
+}
+
+/// 八字中的一柱。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Pillar {
+    pub label: &'static str,
+    pub ganzhi: String,
+    /// 天干及其十神，日柱天干为日主
+    pub gan: Gan,
+    pub zhi: String,
+    pub zhi_wuxing: String,
+    /// 地支藏干及其十神
+    pub cang_gan: Vec<Gan>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct WuXingCount {
+    pub wuxing: String,
+    pub count: u32,
+}
+
+/// 六爻用神与命局喜忌的对照。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct YongShenComparison {
+    pub line: usize,
+    /// 用神的地支五行，例如 "午火"
+    pub yao: String,
+    /// 用神五行对日主而言属于哪一类，例如 "财星"
+    pub category: &'static str,
+    pub favourable: bool,
+    pub text: String,
+}
+
+/// 求测人的八字命局。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct BaZi {
+    pub birth: NaiveDateTime,
+    pub pillars: Vec<Pillar>,
+    /// 日主，例如 "壬水"
+    pub rizhu: String,
+    /// 八个字（四干四支）的五行个数
+    pub wuxing_count: Vec<WuXingCount>,
+    /// "身强" 或 "身弱"
+    pub strength: &'static str,
+    /// 喜用五行
+    pub favourable: Vec<String>,
+    /// 忌神五行
+    pub unfavourable: Vec<String>,
+    pub yong_shen: Option<YongShenComparison>,
+}
+
+impl Gan {
+    fn localize(&mut self, locale: Locale) {
+        self.gan = locale.term(&self.gan).to_string();
+        self.wuxing = locale.term(&self.wuxing).to_string();
+        self.shishen = locale.term(self.shishen);
+    }
+}
+
+impl BaZi {
+    /// 把命局中的文本字段译成指定语言，与排盘一样只在输出前翻译。
+    pub fn localize(&mut self, locale: Locale) {
+        for pillar in &mut self.pillars {
+            pillar.label = locale.term(pillar.label);
+            pillar.ganzhi = locale.text(&pillar.ganzhi);
+            pillar.gan.localize(locale);
+            pillar.zhi = locale.term(&pillar.zhi).to_string();
+            pillar.zhi_wuxing = locale.term(&pillar.zhi_wuxing).to_string();
+            for gan in &mut pillar.cang_gan {
+                gan.localize(locale);
+            }
+        }
+        self.rizhu = locale.text(&self.rizhu);
+        for count in &mut self.wuxing_count {
+            count.wuxing = locale.term(&count.wuxing).to_string();
+        }
+        self.strength = locale.term(self.strength);
+        for wuxing in self.favourable.iter_mut().chain(&mut self.unfavourable) {
+            *wuxing = locale.term(wuxing).to_string();
+        }
+        if let Some(yong_shen) = &mut self.yong_shen {
+            yong_shen.yao = locale.text(&yong_shen.yao);
+            yong_shen.category = locale.term(yong_shen.category);
+            yong_shen.text = locale.text(&yong_shen.text);
+        }
+    }
+}
+
+// 五行对日主的类别：比劫、食伤、财星、官杀、印星
+fn category(rizhu: WuXing, other: WuXing) -> &'static str {
+    if rizhu == other {
+        "比劫"
+    } else {
+        match (
+            get_sheng_ke_relation(rizhu, other),
+            get_sheng_ke_relation(other, rizhu),
+        ) {
+            (Some(ShengKe::Sheng), _) => "食伤",
+            (Some(ShengKe::Ke), _) => "财星",
+            (_, Some(ShengKe::Ke)) => "官杀",
+            _ => "印星",
+        }
+    }
+}
+
+// 十神：按五行类别和阴阳是否相同细分，例如同为财星，阴阳相同为偏财，不同为正财
+fn shishen(rizhu: usize, other: usize) -> &'static str {
+    let same_polarity = rizhu % 2 == other % 2;
+    match (
+        category(GAN_WUXING[rizhu], GAN_WUXING[other]),
+        same_polarity,
+    ) {
+        ("比劫", true) => "比肩",
+        ("比劫", false) => "劫财",
+        ("食伤", true) => "食神",
+        ("食伤", false) => "伤官",
+        ("财星", true) => "偏财",
+        ("财星", false) => "正财",
+        ("官杀", true) => "七杀",
+        ("官杀", false) => "正官",
+        (_, true) => "偏印",
+        (_, false) => "正印",
+    }
+}
+
+fn gan_index(gan: char) -> usize {
+    TIAN_GAN.iter().position(|&g| g == gan).unwrap_or_default()
+}
+
+/// 根据出生时间排八字。若给出六爻用神的地支，一并对照其五行是否为命主喜用。
+pub fn bazi(birth: NaiveDateTime, zi_shi: ZiShi, yong_shen: Option<(usize, DiZhi)>) -> BaZi {
+    // 八字的年柱在立春、月柱在每个节交接的时刻更换，不同于六爻排盘按春节换年
+    let lunar = solar::from_ymdhms(
+        birth.year() as i64,
+        birth.month() as i64,
+        birth.day() as i64,
+        birth.hour() as i64,
+        birth.minute() as i64,
+        birth.second() as i64,
+    )
+    .get_lunar();
+    let year = lunar.get_year_in_gan_zhi_exact();
+    let month = lunar.get_month_in_gan_zhi_exact();
+    let (_, _, day, hour) = get_ganzhi_info(birth, zi_shi);
+    let ganzhi = [year, month, day, hour];
+    let rizhu = gan_index(ganzhi[2].chars().next().unwrap_or('甲'));
+    let rizhu_wuxing = GAN_WUXING[rizhu];
+    let gan = |g: char, shishen_name: &'static str| Gan {
+        gan: g.to_string(),
+        wuxing: GAN_WUXING[gan_index(g)].to_string(),
+        shishen: shishen_name,
+    };
+
+    let mut pillars = Vec::new();
+    let mut counts = [0u32; 5];
+    // 身强身弱：除日主外的七个字里生扶日主的个数，月令按两个字计
+    let mut support = 0;
+    for (i, (label, gz)) in PILLAR_LABELS.iter().zip(&ganzhi).enumerate() {
+        let mut chars = gz.chars();
+        let g = chars.next().unwrap_or('甲');
+        let zhi = chars
+            .next()
+            .and_then(|z| DiZhi::try_from(z.to_string().as_str()).ok())
+            .unwrap_or(DiZhi::Zi);
+        let g_index = gan_index(g);
+        for wuxing in [GAN_WUXING[g_index], zhi.wuxing()] {
+            counts[WUXING_ORDER.iter().position(|&w| w == wuxing).unwrap_or(0)] += 1;
+        }
+        let supports = |w: WuXing| matches!(category(rizhu_wuxing, w), "比劫" | "印星");
+        if i != 2 && supports(GAN_WUXING[g_index]) {
+            support += 1;
+        }
+        if supports(zhi.wuxing()) {
+            support += if i == 1 { 2 } else { 1 };
+        }
+        let gan_shishen = if i == 2 {
+            "日主"
+        } else {
+            shishen(rizhu, g_index)
+        };
+        pillars.push(Pillar {
+            label,
+            ganzhi: gz.clone(),
+            gan: gan(g, gan_shishen),
+            zhi: zhi.to_string(),
+            zhi_wuxing: zhi.wuxing().to_string(),
+            cang_gan: cang_gan(zhi)
+                .iter()
+                .map(|&c| gan(c, shishen(rizhu, gan_index(c))))
+                .collect(),
+        });
+    }
+
+    // 身强喜泄耗（食伤、财、官杀），身弱喜生扶（印、比劫）
+    let strong = support >= 4;
+    let (favourable, unfavourable): (Vec<WuXing>, Vec<WuXing>) = WUXING_ORDER
+        .iter()
+        .partition(|&&w| matches!(category(rizhu_wuxing, w), "比劫" | "印星") != strong);
+
+    let yong_shen = yong_shen.map(|(line, zhi)| {
+        let wuxing = zhi.wuxing();
+        let yao = format!("{}{}", zhi, wuxing);
+        let is_favourable = favourable.contains(&wuxing);
+        let category = category(rizhu_wuxing, wuxing);
+        YongShenComparison {
+            line,
+            text: if is_favourable {
+                format!(
+                    "用神{}为日主之{}，是命主喜用，所测之事得命局相助",
+                    yao, category
+                )
+            } else {
+                format!(
+                    "用神{}为日主之{}，是命主忌神，所测之事命局不助",
+                    yao, category
+                )
+            },
+            yao,
+            category,
+            favourable: is_favourable,
+        }
+    });
+
+    BaZi {
+        birth,
+        pillars,
+        rizhu: format!("{}{}", TIAN_GAN[rizhu], rizhu_wuxing),
+        wuxing_count: WUXING_ORDER
+            .iter()
+            .zip(counts)
+            .map(|(w, count)| WuXingCount {
+                wuxing: w.to_string(),
+                count,
+            })
+            .collect(),
+        strength: if strong { "身强" } else { "身弱" },
+        favourable: favourable.iter().map(ToString::to_string).collect(),
+        unfavourable: unfavourable.iter().map(ToString::to_string).collect(),
+        yong_shen,
+    }
+}
+
+/// 为一次排盘附上求测人八字，line 为六爻用神所在爻位（初爻为1）。
+pub fn for_reading(
+    numbers: &str,
+    birth: NaiveDateTime,
+    zi_shi: ZiShi,
+    line: Option<usize>,
+) -> Result<BaZi, PaipanError> {
+    let yong_shen = match line {
+        Some(line) if !(1..=6).contains(&line) => return Err(PaipanError::InvalidLine(line)),
+        Some(line) => {
+            let (zheng_gua, _) = build_guas(numbers)?;
+            Some((line, zheng_gua.dizhi[line - 1]))
+        }
+        None => None,
+    };
+    if !SUPPORTED_YEARS.contains(&(birth.year() as i64)) {
+        return Err(PaipanError::DateOutOfRange(birth));
+    }
+    Ok(bazi(birth, zi_shi, yong_shen))
+}
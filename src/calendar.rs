@@ -0,0 +1,279 @@
+use crate::{
+    calculate_xun_kong, config::Config, hour_ganzhi, locale::Locale, DiZhi, SUPPORTED_YEARS,
+    TIAN_GAN,
+};
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use lunar_rust::{
+    jie_qi::JieQiRefHelper,
+    lunar::LunarRefHelper,
+    solar::{self, SolarRef, SolarRefHelper},
+};
+use serde::{Deserialize, Serialize};
+
+/// 单次查询最多返回的天数。
+const MAX_DAYS: i64 = 366;
+
+/// 十二节：月柱在交节当天更换，十二中气不换月。
+const JIE: [&str; 12] = [
+    "立春", "惊蛰", "清明", "立夏", "芒种", "小暑", "立秋", "白露", "寒露", "立冬", "大雪", "小寒",
+];
+
+/// 一天内的时辰划分，子时跨日：0点为早子时，23点为晚子时。
+const SHICHEN: [(&str, &str, &str); 13] = [
+    ("子", "00:00", "00:59"),
+    ("丑", "01:00", "02:59"),
+    ("寅", "03:00", "04:59"),
+    ("卯", "05:00", "06:59"),
+    ("辰", "07:00", "08:59"),
+    ("巳", "09:00", "10:59"),
+    ("午", "11:00", "12:59"),
+    ("未", "13:00", "14:59"),
+    ("申", "15:00", "16:59"),
+    ("酉", "17:00", "18:59"),
+    ("戌", "19:00", "20:59"),
+    ("亥", "21:00", "22:59"),
+    ("子", "23:00", "23:59"),
+];
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CalendarQuery {
+    /// 起始日期，YYYY-MM-DD
+    from: String,
+    /// 结束日期（含），缺省时只查起始日期当天
+    to: Option<String>,
+    /// 文本字段的语言，缺省时使用配置
+    locale: Option<Locale>,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct LunarDate {
+    pub year: i64,
+    pub month: i64,
+    pub day: i64,
+    pub leap: bool,
+    /// 例如 "闰六月初三"；英文为 "Leap Month 6, Day 3"
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct JieQi {
+    pub name: String,
+    /// 是否为"节"（交节换月），否则为"中气"
+    pub is_jie: bool,
+    /// 交节气的时刻（北京时间）
+    pub time: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ShiChen {
+    pub zhi: &'static str,
+    /// 时干支，按五鼠遁由日干推出；晚子时用次日日干
+    pub ganzhi: String,
+    pub start: &'static str,
+    pub end: &'static str,
+}
+
+/// 某一天的历法信息。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub lunar: LunarDate,
+    /// 年柱从立春当天起换，与排盘一致；农历年份仍按春节
+    pub year_ganzhi: String,
+    /// 月柱从交节当天起换，与排盘一致；交节前后的确切时刻见 jieqi.time
+    pub month_ganzhi: String,
+    pub day_ganzhi: String,
+    pub xun_kong: String,
+    /// 当天交的节气
+    pub jieqi: Option<JieQi>,
+    /// 月柱是否从当天起与前一天不同
+    pub month_changes: bool,
+    pub shichen: Vec<ShiChen>,
+}
+
+impl CalendarDay {
+    /// 把干支、节气、时辰和农历日期译成指定语言；英文的农历日期按数字书写。
+    pub fn localize(&mut self, locale: Locale) {
+        let lunar = &mut self.lunar;
+        lunar.text = match locale {
+            Locale::En => format!(
+                "{}Month {}, Day {}",
+                if lunar.leap { "Leap " } else { "" },
+                lunar.month,
+                lunar.day
+            ),
+            _ => locale.text(&lunar.text),
+        };
+        for ganzhi in [
+            &mut self.year_ganzhi,
+            &mut self.month_ganzhi,
+            &mut self.day_ganzhi,
+            &mut self.xun_kong,
+        ] {
+            *ganzhi = locale.text(ganzhi);
+        }
+        if let Some(jieqi) = &mut self.jieqi {
+            jieqi.name = locale.term(&jieqi.name).to_string();
+        }
+        for shichen in &mut self.shichen {
+            shichen.zhi = locale.term(shichen.zhi);
+            shichen.ganzhi = locale.text(&shichen.ganzhi);
+        }
+    }
+}
+
+/// 计算一天的历法信息，previous_month 为前一天的月干支，用于判断是否换月。
+fn calendar_day(date: NaiveDate, previous_month: Option<&str>) -> CalendarDay {
+    // 取正午计算，避免子时换日的歧义；月柱按交节的日期而不是时刻换，正午与否不影响
+    let lunar = solar::from_ymdhms(
+        date.year() as i64,
+        date.month() as i64,
+        date.day() as i64,
+        12,
+        0,
+        0,
+    )
+    .get_lunar();
+    let month_ganzhi = lunar.get_month_in_gan_zhi();
+    let day_ganzhi = lunar.get_day_in_gan_zhi();
+    let jieqi = lunar.get_current_jie_qi().and_then(|jieqi| {
+        let name = jieqi.get_name();
+        Some(JieQi {
+            is_jie: JIE.contains(&name.as_str()),
+            name,
+            time: solar_datetime(&jieqi.get_solar())?,
+        })
+    });
+    CalendarDay {
+        date,
+        lunar: LunarDate {
+            year: lunar.get_year(),
+            month: lunar.get_month().abs(),
+            day: lunar.get_day(),
+            // lunar_rust 用负数月份表示闰月
+            leap: lunar.get_month() < 0,
+            text: format!(
+                "{}月{}",
+                lunar.get_month_in_chinese(),
+                lunar.get_day_in_chinese()
+            ),
+        },
+        year_ganzhi: lunar.get_year_in_gan_zhi_by_li_chun(),
+        month_changes: previous_month.is_some_and(|m| m != month_ganzhi),
+        xun_kong: calculate_xun_kong(&day_ganzhi).unwrap_or("").to_string(),
+        month_ganzhi,
+        shichen: shichen(&day_ganzhi),
+        day_ganzhi,
+        jieqi,
+    }
+}
+
+fn solar_datetime(solar: &SolarRef) -> Option<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(
+        solar.get_year() as i32,
+        solar.get_month() as u32,
+        solar.get_day() as u32,
+    )?
+    .and_hms_opt(
+        solar.get_hour() as u32,
+        solar.get_minute() as u32,
+        solar.get_second() as u32,
+    )
+}
+
+// 列出当天各时辰及时干支
+fn shichen(day_ganzhi: &str) -> Vec<ShiChen> {
+    let day_gan = day_ganzhi.chars().next();
+    let next_day_gan = day_gan
+        .and_then(|gan| TIAN_GAN.iter().position(|&g| g == gan))
+        .map(|i| TIAN_GAN[(i + 1) % 10]);
+    SHICHEN
+        .iter()
+        .enumerate()
+        .map(|(i, &(zhi, start, end))| {
+            let gan = if i == SHICHEN.len() - 1 {
+                next_day_gan
+            } else {
+                day_gan
+            };
+            let ganzhi = gan
+                .zip(DiZhi::try_from(zhi).ok())
+                .and_then(|(gan, zhi)| hour_ganzhi(gan, zhi))
+                .unwrap_or_default();
+            ShiChen {
+                zhi,
+                ganzhi,
+                start,
+                end,
+            }
+        })
+        .collect()
+}
+
+/// 返回 [from, to] 范围内每一天的历法信息。
+pub fn calendar_range(from: NaiveDate, to: NaiveDate) -> Vec<CalendarDay> {
+    // 多算前一天，用来判断第一天是否换月
+    let mut previous = calendar_day(from - Duration::days(1), None).month_ganzhi;
+    from.iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let day = calendar_day(date, Some(&previous));
+            previous = day.month_ganzhi.clone();
+            day
+        })
+        .collect()
+}
+
+/// 查询一段日期的农历、干支、旬空、节气和时辰，例如 /calendar?from=2025-02-01&to=2025-02-07。
+#[utoipa::path(
+    get,
+    path = "/api/v1/calendar",
+    tag = "calendar",
+    params(CalendarQuery),
+    responses(
+        (status = 200, description = "逐日的历法信息", body = Vec<CalendarDay>),
+        (status = 400, description = "日期无效或范围过大", body = crate::api::ApiError),
+    )
+)]
+pub async fn calendar(
+    config: web::Data<Config>,
+    query: web::Query<CalendarQuery>,
+) -> impl Responder {
+    let parse = |s: &str| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map_err(|_| format!("无法解析日期 \"{}\"，格式为 YYYY-MM-DD", s))
+    };
+    let from = match parse(&query.from) {
+        Ok(date) => date,
+        Err(e) => return HttpResponse::BadRequest().json(e),
+    };
+    let to = match query.to.as_deref().map(parse).transpose() {
+        Ok(date) => date.unwrap_or(from),
+        Err(e) => return HttpResponse::BadRequest().json(e),
+    };
+    if let Some(date) = [from, to]
+        .into_iter()
+        .find(|date| !SUPPORTED_YEARS.contains(&(date.year() as i64)))
+    {
+        return HttpResponse::BadRequest().json(format!(
+            "日期 {} 超出支持的范围（公元{}年至{}年）",
+            date,
+            SUPPORTED_YEARS.start(),
+            SUPPORTED_YEARS.end()
+        ));
+    }
+    if to < from {
+        return HttpResponse::BadRequest().json("结束日期不能早于开始日期");
+    }
+    if (to - from).num_days() >= MAX_DAYS {
+        return HttpResponse::BadRequest().json(format!("一次最多查询 {} 天", MAX_DAYS));
+    }
+    let locale = query.locale.unwrap_or(config.locale);
+    let mut days = calendar_range(from, to);
+    for day in &mut days {
+        day.localize(locale);
+    }
+    HttpResponse::Ok().json(days)
+}
@@ -0,0 +1,204 @@
+use crate::{
+    batch::{Batch, BatchFormat},
+    bazi,
+    config::Config,
+    enumeration,
+    lunar_date::LunarDateInput,
+    paipan_solar, raster, render,
+    solar_time::SolarTimeOptions,
+    SUPPORTED_YEARS,
+};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime};
+use clap::{Args, ValueEnum};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
+/// `sixsixsix paipan` 子命令的参数。
+#[derive(Debug, Args)]
+pub struct PaipanArgs {
+    /// 六次阳面个数（0-3），从初爻到上爻，例如 013212
+    pub numbers: String,
+    /// 起卦时间，例如 2025-06-01T10:00+08:00；不带时区时视为当地时间，默认取当前时间
+    #[arg(long)]
+    pub at: Option<String>,
+    /// 以农历给出起卦时间，例如 "2025-06-03 午"，闰月写作 "2025-闰06-03 午"
+    #[arg(long, conflicts_with = "at")]
+    pub lunar: Option<LunarDateInput>,
+    /// 起卦地经度（东经为正），给出时按真太阳时排盘
+    #[arg(long, allow_negative_numbers = true)]
+    pub longitude: Option<f64>,
+    /// 真太阳时校正时不计均时差，只按经度差校正
+    #[arg(long, requires = "longitude")]
+    pub no_equation_of_time: bool,
+    /// 求测人出生时间，给出时附上八字命局，格式同 --at
+    #[arg(long)]
+    pub birth: Option<String>,
+    /// 六爻用神所在爻位（1-6），与命局喜忌对照
+    #[arg(long, requires = "birth")]
+    pub yong_shen_line: Option<usize>,
+    /// 输出格式；png 输出二进制图片，请重定向到文件
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// PNG 的缩放倍数（0.5-4）
+    #[arg(long, default_value_t = raster::DEFAULT_SCALE)]
+    pub scale: f32,
+}
+
+/// 命令行排盘的输出格式。
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Markdown,
+    Svg,
+    Png,
+}
+
+/// 执行一次命令行排盘，返回要输出的内容。
+pub fn run_paipan(args: &PaipanArgs, config: &Config) -> Result<Vec<u8>, String> {
+    let (at, utc_offset) = match (&args.at, &args.lunar) {
+        (Some(at), _) => parse_datetime_with_offset(at)?,
+        (None, Some(lunar)) => (lunar.to_solar().map_err(|e| e.to_string())?, None),
+        (None, None) => (config.now(), None),
+    };
+    let solar_time = args.longitude.map(|longitude| SolarTimeOptions {
+        longitude,
+        equation_of_time: !args.no_equation_of_time,
+    });
+    let mut gua = paipan_solar(
+        config,
+        &args.numbers,
+        at,
+        utc_offset,
+        config.zi_shi,
+        solar_time,
+    )
+    .map_err(|e| e.to_string())?;
+    if let Some(birth) = &args.birth {
+        let birth = parse_datetime(birth)?;
+        gua.bazi = Some(
+            bazi::for_reading(&args.numbers, birth, config.zi_shi, args.yong_shen_line)
+                .map_err(|e| e.to_string())?,
+        );
+    }
+    // SVG 和 PNG 按汉字排版，只有文本输出按配置的语言翻译
+    if !matches!(args.format, OutputFormat::Svg | OutputFormat::Png) {
+        gua.localize(config.locale);
+    }
+    Ok(match args.format {
+        OutputFormat::Text => render::text(&gua).into_bytes(),
+        OutputFormat::Json => {
+            (serde_json::to_string_pretty(&gua).map_err(|e| e.to_string())? + "\n").into_bytes()
+        }
+        OutputFormat::Markdown => render::markdown(&gua).into_bytes(),
+        OutputFormat::Svg => render::svg(&gua).into_bytes(),
+        OutputFormat::Png => {
+            raster::png(&render::svg(&gua), args.scale).map_err(|e| e.to_string())?
+        }
+    })
+}
+
+/// `sixsixsix batch` 子命令的参数。
+#[derive(Debug, Args)]
+pub struct BatchArgs {
+    /// 输入文件，每行一条 {numbers, datetime, question, longitude} 记录；缺省或为 "-" 时读标准输入
+    pub input: Option<PathBuf>,
+    /// 输入格式，缺省时扩展名为 .csv 的按 CSV 读，其余按 JSON Lines 读
+    #[arg(long, value_enum)]
+    pub input_format: Option<BatchFormat>,
+}
+
+/// 逐行读入记录并排盘，结果以 JSON Lines 逐行写到标准输出。
+/// 单条记录出错只写在该行的 error 中，读写失败时才中止。
+pub fn run_batch(args: &BatchArgs, config: &Config) -> Result<(), String> {
+    let path = args.input.as_ref().filter(|path| path.as_os_str() != "-");
+    let format =
+        args.input_format
+            .unwrap_or_else(|| match path.and_then(|path| path.extension()) {
+                Some(ext) if ext.eq_ignore_ascii_case("csv") => BatchFormat::Csv,
+                _ => BatchFormat::JsonLines,
+            });
+    let input: Box<dyn BufRead> = match path {
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).map_err(|e| format!("无法打开 {}: {}", path.display(), e))?,
+        )),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut batch = Batch::new(format, config, config.zi_shi, config.locale);
+    let mut output = BufWriter::new(io::stdout().lock());
+    for line in input.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if let Some(result) = batch.next_line(&line) {
+            output
+                .write_all(result.to_line().as_bytes())
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    output.flush().map_err(|e| e.to_string())
+}
+
+/// `sixsixsix enumerate` 子命令的参数。
+#[derive(Debug, Args)]
+pub struct EnumerateArgs {
+    /// 起始日期，例如 2025-01-01；给出时另按每天午时统计各断卦规则的出现频率
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+    /// 结束日期（含），缺省时只统计起始日期当天
+    #[arg(long, requires = "from")]
+    pub to: Option<NaiveDate>,
+}
+
+/// 枚举全部 4096 种输入，输出统计结果（JSON）。
+pub fn run_enumerate(args: &EnumerateArgs) -> Result<Vec<u8>, String> {
+    let dates = args.from.map(|from| (from, args.to.unwrap_or(from)));
+    if let Some((from, to)) = dates.filter(|(from, to)| to < from) {
+        return Err(format!("结束日期 {} 早于起始日期 {}", to, from));
+    }
+    if let Some(date) = dates
+        .into_iter()
+        .flat_map(|(from, to)| [from, to])
+        .find(|date| !SUPPORTED_YEARS.contains(&(date.year() as i64)))
+    {
+        return Err(format!(
+            "日期 {} 超出支持的范围（公元{}年至{}年）",
+            date,
+            SUPPORTED_YEARS.start(),
+            SUPPORTED_YEARS.end()
+        ));
+    }
+    let report = enumeration::enumerate(dates);
+    Ok((serde_json::to_string_pretty(&report).map_err(|e| e.to_string())? + "\n").into_bytes())
+}
+
+/// 解析起卦时间。带时区偏移时取该时区的当地时间，因为干支按当地时间排。
+pub fn parse_datetime(s: &str) -> Result<NaiveDateTime, String> {
+    parse_datetime_with_offset(s).map(|(at, _)| at)
+}
+
+/// 同 parse_datetime，另返回输入中给出的 UTC 偏移（秒），真太阳时据此确定标准经线；
+/// 不带时区的输入没有偏移，由调用方按配置的时区确定。
+pub fn parse_datetime_with_offset(s: &str) -> Result<(NaiveDateTime, Option<i32>), String> {
+    const WITH_OFFSET: [&str; 2] = ["%Y-%m-%dT%H:%M%:z", "%Y-%m-%d %H:%M%:z"];
+    const NAIVE: [&str; 4] = [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ];
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .or_else(|| {
+            WITH_OFFSET
+                .iter()
+                .find_map(|fmt| DateTime::parse_from_str(s, fmt).ok())
+        })
+        .map(|dt| (dt.naive_local(), Some(dt.offset().local_minus_utc())))
+        .or_else(|| {
+            NAIVE
+                .iter()
+                .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+                .map(|at| (at, None))
+        })
+        .ok_or_else(|| format!("无法解析时间 \"{}\"，示例：2025-06-01T10:00+08:00", s))
+}
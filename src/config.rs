@@ -0,0 +1,368 @@
+use chrono::{DateTime, Local, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use clap::Args;
+use serde::Deserialize;
+use std::fmt::{self, Display};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::{locale::Locale, ZiShi};
+
+/// 未指定 --config 时尝试读取的配置文件（不存在则忽略）。
+const DEFAULT_CONFIG_FILE: &str = "sixsixsix.toml";
+
+/// 运行配置。优先级从低到高：默认值 < TOML 配置文件 < 环境变量 < 命令行参数。
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind: IpAddr,
+    pub port: u16,
+    pub workers: Option<usize>,
+    /// 排盘使用的时区，为 None 时使用系统本地时区。
+    pub timezone: Option<Tz>,
+    /// 23点子时的日柱归属
+    pub zi_shi: ZiShi,
+    /// 排盘输出的默认语言，请求中可以另行指定
+    pub locale: Locale,
+    pub log_level: String,
+    pub serve_ui: bool,
+    /// 占卜记录数据库（SQLite）文件路径
+    pub database: PathBuf,
+    pub llm: LlmConfig,
+    /// 起卦时取当前时刻的时钟
+    pub clock: Arc<dyn Clock>,
+}
+
+/// 当前时刻的来源。服务默认读系统时间，测试中可换成固定时刻，使排盘结果可以断言。
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// 读取系统时间的时钟。
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// 只给出固定的描述，不读时钟，使 Config 的 Debug 输出稳定
+impl fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
+
+/// 调用大语言模型解卦所需的接口配置。密钥不写入配置，只给出存放密钥的环境变量名。
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmConfig {
+    /// 接口地址，例如 https://api.example.com/v1/chat/completions
+    pub endpoint: Option<String>,
+    pub model: Option<String>,
+    /// 存放接口密钥的环境变量名
+    pub api_key_env: String,
+    /// 请求超时（秒）
+    pub timeout_secs: u64,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        LlmConfig {
+            endpoint: None,
+            model: None,
+            api_key_env: "SIXSIXSIX_LLM_API_KEY".to_string(),
+            timeout_secs: 60,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind: IpAddr::from([0u16; 8]), // 等价于 "[::]"
+            port: 8080,
+            workers: None,
+            timezone: None,
+            zi_shi: ZiShi::default(),
+            locale: Locale::default(),
+            log_level: "info".to_string(),
+            serve_ui: true,
+            database: PathBuf::from("sixsixsix.db"),
+            llm: LlmConfig::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+/// TOML 配置文件的结构，所有字段可选，缺省时沿用默认值。
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    bind: Option<String>,
+    port: Option<u16>,
+    workers: Option<usize>,
+    timezone: Option<String>,
+    zi_shi: Option<ZiShi>,
+    locale: Option<Locale>,
+    log_level: Option<String>,
+    serve_ui: Option<bool>,
+    database: Option<PathBuf>,
+    llm: FileLlmConfig,
+}
+
+/// 配置文件中的 [llm] 表，缺省的项沿用默认值。
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileLlmConfig {
+    endpoint: Option<String>,
+    model: Option<String>,
+    api_key_env: Option<String>,
+    timeout_secs: Option<u64>,
+}
+
+/// 命令行参数，每个参数都可以用对应的 SIXSIXSIX_* 环境变量代替。
+#[derive(Debug, Default, Args)]
+pub struct ConfigArgs {
+    /// 配置文件路径（TOML），默认读取当前目录下的 sixsixsix.toml
+    #[arg(long, global = true, env = "SIXSIXSIX_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// 监听地址，例如 127.0.0.1 或 ::
+    #[arg(long, global = true, env = "SIXSIXSIX_BIND")]
+    pub bind: Option<String>,
+    /// 监听端口
+    #[arg(long, global = true, env = "SIXSIXSIX_PORT")]
+    pub port: Option<u16>,
+    /// 工作线程数，默认等于 CPU 核数
+    #[arg(long, global = true, env = "SIXSIXSIX_WORKERS")]
+    pub workers: Option<usize>,
+    /// 排盘时区（IANA 名称，如 Asia/Shanghai），默认使用系统时区
+    #[arg(long, global = true, env = "SIXSIXSIX_TIMEZONE")]
+    pub timezone: Option<String>,
+    /// 23点子时的日柱归属：split 分早晚子时（日柱不变），next_day 子初换日
+    #[arg(long, global = true, env = "SIXSIXSIX_ZI_SHI", value_enum)]
+    pub zi_shi: Option<ZiShi>,
+    /// 排盘输出的默认语言：zh-Hans、zh-Hant、en、pinyin
+    #[arg(long, global = true, env = "SIXSIXSIX_LOCALE", value_enum)]
+    pub locale: Option<Locale>,
+    /// 日志过滤规则，与 RUST_LOG 写法相同，例如 info 或 sixsixsix=debug,actix_web=warn
+    #[arg(long, global = true, env = "SIXSIXSIX_LOG_LEVEL")]
+    pub log_level: Option<String>,
+    /// 是否提供内嵌的网页界面
+    #[arg(long, global = true, env = "SIXSIXSIX_SERVE_UI")]
+    pub serve_ui: Option<bool>,
+    /// 占卜记录数据库文件路径，默认为当前目录下的 sixsixsix.db
+    #[arg(long, global = true, env = "SIXSIXSIX_DATABASE")]
+    pub database: Option<PathBuf>,
+    /// 大语言模型接口地址
+    #[arg(long, global = true, env = "SIXSIXSIX_LLM_ENDPOINT")]
+    pub llm_endpoint: Option<String>,
+    /// 大语言模型名称
+    #[arg(long, global = true, env = "SIXSIXSIX_LLM_MODEL")]
+    pub llm_model: Option<String>,
+    /// 存放大语言模型接口密钥的环境变量名，默认为 SIXSIXSIX_LLM_API_KEY
+    #[arg(long, global = true, env = "SIXSIXSIX_LLM_API_KEY_ENV")]
+    pub llm_api_key_env: Option<String>,
+    /// 大语言模型请求超时（秒），默认 60
+    #[arg(long, global = true, env = "SIXSIXSIX_LLM_TIMEOUT_SECS")]
+    pub llm_timeout_secs: Option<u64>,
+}
+
+/// 加载或校验配置时出现的错误。
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid {
+        field: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "无法读取配置文件 {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "配置文件 {} 格式错误: {}", path.display(), e),
+            ConfigError::Invalid {
+                field,
+                value,
+                reason,
+            } => {
+                write!(f, "配置项 {} 的值 \"{}\" 无效: {}", field, value, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// 按优先级合并配置文件与命令行/环境变量，并校验所有取值。
+    pub fn load(args: ConfigArgs) -> Result<Self, ConfigError> {
+        let file = match &args.config {
+            Some(path) => read_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                read_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => FileConfig::default(),
+        };
+
+        let mut config = Config::default();
+        if let Some(bind) = args.bind.or(file.bind) {
+            config.bind = parse_bind(&bind)?;
+        }
+        if let Some(port) = args.port.or(file.port) {
+            config.port = port;
+        }
+        config.workers = args.workers.or(file.workers);
+        if let Some(tz) = args.timezone.or(file.timezone) {
+            config.timezone = Some(tz.parse().map_err(|_| ConfigError::Invalid {
+                field: "timezone",
+                value: tz,
+                reason: "不是有效的 IANA 时区名称，例如 Asia/Shanghai",
+            })?);
+        }
+        if let Some(zi_shi) = args.zi_shi.or(file.zi_shi) {
+            config.zi_shi = zi_shi;
+        }
+        if let Some(locale) = args.locale.or(file.locale) {
+            config.locale = locale;
+        }
+        if let Some(level) = args.log_level.or(file.log_level) {
+            config.log_level = level;
+        }
+        if let Some(serve_ui) = args.serve_ui.or(file.serve_ui) {
+            config.serve_ui = serve_ui;
+        }
+        if let Some(database) = args.database.or(file.database) {
+            config.database = database;
+        }
+        config.llm.endpoint = args.llm_endpoint.or(file.llm.endpoint);
+        config.llm.model = args.llm_model.or(file.llm.model);
+        if let Some(name) = args.llm_api_key_env.or(file.llm.api_key_env) {
+            config.llm.api_key_env = name;
+        }
+        if let Some(timeout) = args.llm_timeout_secs.or(file.llm.timeout_secs) {
+            config.llm.timeout_secs = timeout;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 当前时刻在配置时区下的本地时间，用于起卦。
+    pub fn now(&self) -> NaiveDateTime {
+        let now = self.clock.now();
+        match self.timezone {
+            Some(tz) => now.with_timezone(&tz).naive_local(),
+            None => now.with_timezone(&Local).naive_local(),
+        }
+    }
+
+    /// 某个当地时间在配置时区下的 UTC 偏移（秒），用于确定真太阳时的标准经线。
+    pub fn utc_offset_seconds(&self, at: NaiveDateTime) -> i32 {
+        // 夏令时跳过的时刻没有对应的当地时间，把它当作 UTC 时刻取同一时区在那一刻的偏移
+        match self.timezone {
+            Some(tz) => tz
+                .offset_from_local_datetime(&at)
+                .earliest()
+                .unwrap_or_else(|| tz.offset_from_utc_datetime(&at))
+                .fix()
+                .local_minus_utc(),
+            None => Local
+                .offset_from_local_datetime(&at)
+                .earliest()
+                .unwrap_or_else(|| Local.offset_from_utc_datetime(&at))
+                .local_minus_utc(),
+        }
+    }
+
+    // 校验无法由类型系统保证的取值
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.port == 0 {
+            return Err(ConfigError::Invalid {
+                field: "port",
+                value: self.port.to_string(),
+                reason: "端口必须在 1-65535 之间",
+            });
+        }
+        if self.workers == Some(0) {
+            return Err(ConfigError::Invalid {
+                field: "workers",
+                value: "0".to_string(),
+                reason: "工作线程数至少为 1",
+            });
+        }
+        // 与 serve 中 env_logger 的 parse_filters 是同一个解析器，支持按模块设置级别
+        if env_filter::Builder::new()
+            .try_parse(&self.log_level)
+            .is_err()
+        {
+            return Err(ConfigError::Invalid {
+                field: "log_level",
+                value: self.log_level.clone(),
+                reason: "不是有效的日志过滤规则，例如 info 或 sixsixsix=debug,actix_web=warn",
+            });
+        }
+        if let Some(endpoint) = &self.llm.endpoint {
+            if !(endpoint.starts_with("http://") || endpoint.starts_with("https://")) {
+                return Err(ConfigError::Invalid {
+                    field: "llm.endpoint",
+                    value: endpoint.clone(),
+                    reason: "必须是以 http:// 或 https:// 开头的地址",
+                });
+            }
+        }
+        if self
+            .llm
+            .model
+            .as_deref()
+            .is_some_and(|m| m.trim().is_empty())
+        {
+            return Err(ConfigError::Invalid {
+                field: "llm.model",
+                value: String::new(),
+                reason: "模型名称不能为空",
+            });
+        }
+        let name = &self.llm.api_key_env;
+        if name.is_empty()
+            || name.starts_with(|c: char| c.is_ascii_digit())
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(ConfigError::Invalid {
+                field: "llm.api_key_env",
+                value: name.clone(),
+                reason: "必须是环境变量名，只含字母、数字和下划线且不以数字开头",
+            });
+        }
+        if self.llm.timeout_secs == 0 {
+            return Err(ConfigError::Invalid {
+                field: "llm.timeout_secs",
+                value: "0".to_string(),
+                reason: "超时时间至少为 1 秒",
+            });
+        }
+        Ok(())
+    }
+}
+
+fn read_file(path: &Path) -> Result<FileConfig, ConfigError> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+    toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+}
+
+// 兼容 "[::]" 这种带方括号的 IPv6 写法
+fn parse_bind(bind: &str) -> Result<IpAddr, ConfigError> {
+    bind.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .map_err(|_| ConfigError::Invalid {
+            field: "bind",
+            value: bind.to_string(),
+            reason: "不是有效的 IP 地址",
+        })
+}
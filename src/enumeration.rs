@@ -0,0 +1,334 @@
+use crate::{
+    build_guas, get_ganzhi_info,
+    gua_table::{self, GuaCode},
+    hexagram::{Hexagram, Trigram},
+    paipan, stats, ChongHe, Gua, LiuQin, Yao, ZiShi, SIXTYFOURGUA_DATA,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 三枚铜钱起卦时，阳面个数 0-3（老阴、少阳、少阴、老阳）各自的概率。
+const COIN: [f64; 4] = [1.0 / 8.0, 3.0 / 8.0, 3.0 / 8.0, 1.0 / 8.0];
+/// 大衍筮法（揲蓍）得老阴、少阳、少阴、老阳的概率。
+const YARROW: [f64; 4] = [1.0 / 16.0, 5.0 / 16.0, 7.0 / 16.0, 3.0 / 16.0];
+
+const LINE_NAMES: [&str; 4] = ["老阴", "少阳", "少阴", "老阳"];
+
+/// 六冲卦、六合卦以外的卦在冲合转换中的名称。
+const NEITHER: &str = "非冲合";
+
+/// 某种情况在全部输入中出现的次数，以及两种起卦法下出现的概率。
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct Frequency {
+    /// 出现的输入个数；按日期统计时为输入个数与天数的乘积
+    pub count: u64,
+    /// 三枚铜钱起卦的概率
+    pub coin: f64,
+    /// 大衍筮法的概率
+    pub yarrow: f64,
+}
+
+impl Frequency {
+    fn add(&mut self, other: Frequency) {
+        self.count += other.count;
+        self.coin += other.coin;
+        self.yarrow += other.yarrow;
+    }
+}
+
+/// 按某个维度（宫、动爻个数、断卦规则）分组后的频数。
+#[derive(Debug, Serialize)]
+pub struct Group {
+    pub key: String,
+    #[serde(flatten)]
+    pub frequency: Frequency,
+}
+
+/// 单爻的概率。
+#[derive(Debug, Serialize)]
+pub struct LineProbability {
+    /// 阳面个数，即输入中的数字
+    pub number: u32,
+    pub name: &'static str,
+    pub coin: f64,
+    pub yarrow: f64,
+}
+
+/// 本卦到之卦的冲合转换，例如六冲变六合。
+#[derive(Debug, Serialize)]
+pub struct Transition {
+    pub from: &'static str,
+    pub to: &'static str,
+    #[serde(flatten)]
+    pub frequency: Frequency,
+}
+
+/// 本卦缺某一六亲、需要取伏神的情况。
+#[derive(Debug, Serialize)]
+pub struct MissingLiuQin {
+    pub liuqin: String,
+    /// 缺此六亲的卦，按卦编码排列
+    pub hexagrams: Vec<&'static str>,
+    #[serde(flatten)]
+    pub frequency: Frequency,
+}
+
+/// 反吟的两种情形。
+#[derive(Debug, Default, Serialize)]
+pub struct FanYin {
+    /// 卦反吟：内卦或外卦变为后天八卦中相对之卦，如乾变巽、坎变离
+    pub gua: Frequency,
+    /// 爻反吟：有动爻化出与本爻相冲的地支，如子化午
+    pub yao: Frequency,
+}
+
+/// 一段日期内各断卦规则的出现频率，规则与 /readings/report 中的 by_rule 一致。
+#[derive(Debug, Serialize)]
+pub struct RuleFrequencies {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub days: u64,
+    pub rules: Vec<Group>,
+}
+
+/// 4^6 种起卦输入的统计，用于教学和核对排盘用表。
+#[derive(Debug, Serialize)]
+pub struct Enumeration {
+    /// 枚举的输入个数，即 4096
+    pub inputs: u64,
+    pub lines: Vec<LineProbability>,
+    /// 动爻个数的分布
+    pub changing_lines: Vec<Group>,
+    /// 本卦所属宫的分布，按八宫顺序
+    pub palaces: Vec<Group>,
+    /// 之卦所属宫的分布，只计有动爻的输入
+    pub bian_palaces: Vec<Group>,
+    /// 本卦到之卦的六冲、六合转换，只计有动爻的输入
+    pub chong_he: Vec<Transition>,
+    /// 本卦缺各六亲（需取伏神）的频率
+    pub missing_liuqin: Vec<MissingLiuQin>,
+    pub fan_yin: FanYin,
+    /// 伏吟：内卦或外卦变了而地支不变，即乾震互变
+    pub fu_yin: Frequency,
+    /// 给出日期范围时才有
+    pub rules: Option<RuleFrequencies>,
+}
+
+// 第 n 个输入（0..4096）的卦数，每爻占 2 位，初爻在低位
+fn numbers(n: usize) -> String {
+    (0..6)
+        .map(|i| char::from(b'0' + (n >> (2 * i) & 3) as u8))
+        .collect()
+}
+
+// 一个输入在两种起卦法下的概率，各爻独立
+fn weight(numbers: &str) -> Frequency {
+    numbers.bytes().map(|b| (b - b'0') as usize).fold(
+        Frequency {
+            count: 1,
+            coin: 1.0,
+            yarrow: 1.0,
+        },
+        |w, line| Frequency {
+            count: 1,
+            coin: w.coin * COIN[line],
+            yarrow: w.yarrow * YARROW[line],
+        },
+    )
+}
+
+fn is_changing(gua: &Gua, i: usize) -> bool {
+    matches!(gua.yao_xiang[i], Yao::YinChanging | Yao::YangChanging)
+}
+
+fn chong_he(code: GuaCode) -> &'static str {
+    match gua_table::lookup(code).chong_he {
+        Some(ChongHe::Chong) => "六冲",
+        Some(ChongHe::He) => "六合",
+        None => NEITHER,
+    }
+}
+
+fn palace_groups(frequencies: [Frequency; 8]) -> Vec<Group> {
+    frequencies
+        .into_iter()
+        .enumerate()
+        .map(|(palace, frequency)| Group {
+            key: Trigram::from_code(gua_table::parse_code(SIXTYFOURGUA_DATA[palace].index))
+                .name()
+                .to_string(),
+            frequency,
+        })
+        .collect()
+}
+
+/// 枚举全部 4096 种输入并统计；给出日期范围时，另按每天午时排盘统计各断卦规则。
+pub fn enumerate(dates: Option<(NaiveDate, NaiveDate)>) -> Enumeration {
+    let inputs: Vec<(String, Frequency)> = (0..4096)
+        .map(|n| {
+            let numbers = numbers(n);
+            let weight = weight(&numbers);
+            (numbers, weight)
+        })
+        .collect();
+
+    let mut changing_lines = [Frequency::default(); 7];
+    let mut palaces = [Frequency::default(); 8];
+    let mut bian_palaces = [Frequency::default(); 8];
+    let mut chong_he_counts: BTreeMap<(&str, &str), Frequency> = BTreeMap::new();
+    let mut missing = [Frequency::default(); 5];
+    let mut missing_hexagrams: [Vec<GuaCode>; 5] = Default::default();
+    let mut fan_yin = FanYin::default();
+    let mut fu_yin = Frequency::default();
+
+    for (numbers, weight) in &inputs {
+        let (zheng, bian) = build_guas(numbers).expect("枚举的输入总是有效");
+        let weight = *weight;
+        let changing: Vec<usize> = (0..6).filter(|&i| is_changing(&zheng, i)).collect();
+        changing_lines[changing.len()].add(weight);
+        palaces[gua_table::lookup(zheng.code).palace].add(weight);
+
+        for (i, liuqin) in LiuQin::ALL.into_iter().enumerate() {
+            if !zheng.liuqin.contains(&liuqin) {
+                missing[i].add(weight);
+                if !missing_hexagrams[i].contains(&zheng.code) {
+                    missing_hexagrams[i].push(zheng.code);
+                }
+            }
+        }
+
+        if changing.is_empty() {
+            continue;
+        }
+        bian_palaces[gua_table::lookup(bian.code).palace].add(weight);
+        chong_he_counts
+            .entry((chong_he(zheng.code), chong_he(bian.code)))
+            .or_default()
+            .add(weight);
+
+        // 内卦、外卦分别判断，只看有动爻的一半
+        let (mut gua_fan_yin, mut is_fu_yin) = (false, false);
+        for half in [0, 3] {
+            if !(half..half + 3).any(|i| changing.contains(&i)) {
+                continue;
+            }
+            let ben = Trigram::from_code(zheng.code >> half);
+            let zhi = Trigram::from_code(bian.code >> half);
+            gua_fan_yin |= zhi == ben.opposite();
+            is_fu_yin |= (half..half + 3).all(|i| bian.dizhi[i] == zheng.dizhi[i]);
+        }
+        if gua_fan_yin {
+            fan_yin.gua.add(weight);
+        }
+        if changing
+            .iter()
+            .any(|&i| bian.dizhi[i] == zheng.dizhi[i].chong())
+        {
+            fan_yin.yao.add(weight);
+        }
+        if is_fu_yin {
+            fu_yin.add(weight);
+        }
+    }
+
+    let classes = ["六冲", "六合", NEITHER];
+    let chong_he = classes
+        .iter()
+        .flat_map(|&from| classes.iter().map(move |&to| (from, to)))
+        .map(|(from, to)| Transition {
+            from,
+            to,
+            frequency: chong_he_counts
+                .get(&(from, to))
+                .copied()
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    let missing_liuqin = LiuQin::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, liuqin)| {
+            let mut codes = missing_hexagrams[i].clone();
+            codes.sort();
+            MissingLiuQin {
+                liuqin: liuqin.to_string(),
+                hexagrams: codes
+                    .into_iter()
+                    .map(|code| Hexagram::from_code(code).name())
+                    .collect(),
+                frequency: missing[i],
+            }
+        })
+        .collect();
+
+    Enumeration {
+        inputs: inputs.len() as u64,
+        lines: (0..4)
+            .map(|i| LineProbability {
+                number: i as u32,
+                name: LINE_NAMES[i],
+                coin: COIN[i],
+                yarrow: YARROW[i],
+            })
+            .collect(),
+        changing_lines: changing_lines
+            .into_iter()
+            .enumerate()
+            .map(|(n, frequency)| Group {
+                key: n.to_string(),
+                frequency,
+            })
+            .collect(),
+        palaces: palace_groups(palaces),
+        bian_palaces: palace_groups(bian_palaces),
+        chong_he,
+        missing_liuqin,
+        fan_yin,
+        fu_yin,
+        rules: dates.map(|(from, to)| rule_frequencies(&inputs, from, to)),
+    }
+}
+
+// 断卦规则只与月支和日干支（含旬空）有关，这两者相同的日子只需排一次盘，
+// 按天数加权，跨多年时最多排 12×60 次
+fn rule_frequencies(
+    inputs: &[(String, Frequency)],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> RuleFrequencies {
+    let mut days: BTreeMap<(char, String), (NaiveDateTime, u64)> = BTreeMap::new();
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        let at = date.and_hms_opt(12, 0, 0).unwrap_or_default();
+        let (_, month_ganzhi, day_ganzhi, _) = get_ganzhi_info(at, ZiShi::default());
+        let month_zhi = month_ganzhi.chars().last().unwrap_or_default();
+        days.entry((month_zhi, day_ganzhi)).or_insert((at, 0)).1 += 1;
+    }
+    let total = days.values().map(|(_, n)| n).sum::<u64>();
+
+    let mut rules: BTreeMap<String, Frequency> = BTreeMap::new();
+    for (at, n) in days.values() {
+        let share = *n as f64 / total as f64;
+        for (numbers, weight) in inputs {
+            let chart = paipan(numbers, *at, ZiShi::default()).expect("枚举的输入总是有效");
+            for rule in stats::fired_rules(&chart) {
+                rules.entry(rule).or_default().add(Frequency {
+                    count: *n,
+                    coin: weight.coin * share,
+                    yarrow: weight.yarrow * share,
+                });
+            }
+        }
+    }
+
+    RuleFrequencies {
+        from,
+        to,
+        days: total,
+        rules: rules
+            .into_iter()
+            .map(|(key, frequency)| Group { key, frequency })
+            .collect(),
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{get_chong_he_relation, ChongHe, DiZhi, WuXing, SIXTYFOURGUA_DATA};
+use std::fmt::{self, Display};
+
+/// 卦的 6 位编码：第 i 位对应第 i+1 爻（初爻为最低位），1 为阳爻。
+/// 例如天风姤（初爻阴，其余阳）为 0b111110。
+pub type GuaCode = u8;
+
+/// 卦在本宫八卦中的位置，决定世爻所在。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Generation {
+    BenGong,
+    YiShi,
+    ErShi,
+    SanShi,
+    SiShi,
+    WuShi,
+    YouHun,
+    GuiHun,
+}
+
+impl Generation {
+    const ALL: [Generation; 8] = [
+        Generation::BenGong,
+        Generation::YiShi,
+        Generation::ErShi,
+        Generation::SanShi,
+        Generation::SiShi,
+        Generation::WuShi,
+        Generation::YouHun,
+        Generation::GuiHun,
+    ];
+
+    /// 世爻位置（初爻为0）：本宫六世，一至五世依次，游魂四世，归魂三世。
+    pub const fn shi(self) -> usize {
+        match self {
+            Generation::BenGong => 5,
+            Generation::YiShi => 0,
+            Generation::ErShi => 1,
+            Generation::SanShi => 2,
+            Generation::SiShi => 3,
+            Generation::WuShi => 4,
+            Generation::YouHun => 3,
+            Generation::GuiHun => 2,
+        }
+    }
+}
+
+impl Display for Generation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Generation::BenGong => "本宫",
+                Generation::YiShi => "一世",
+                Generation::ErShi => "二世",
+                Generation::SanShi => "三世",
+                Generation::SiShi => "四世",
+                Generation::WuShi => "五世",
+                Generation::YouHun => "游魂",
+                Generation::GuiHun => "归魂",
+            }
+        )
+    }
+}
+
+/// 一卦排盘所需的全部静态信息。
+#[derive(Debug, Clone, Copy)]
+pub struct GuaInfo {
+    /// 卦名，带卦符及六冲、六合标记，例如 "天地否䷋(六合)"；标记在编译时与 chong_he 核对
+    pub name: &'static str,
+    /// 所属宫在 SIXTYFOURGUA_DATA 中的序号
+    pub palace: usize,
+    pub generation: Generation,
+    /// 初爻到上爻的纳甲地支
+    pub najia: [DiZhi; 6],
+    /// 六冲卦或六合卦，由内外卦对应爻的纳甲推出
+    pub chong_he: Option<ChongHe>,
+}
+
+impl GuaInfo {
+    /// 宫五行，正卦与变卦的六亲都按它来定。
+    pub fn palace_element(&self) -> WuXing {
+        SIXTYFOURGUA_DATA[self.palace].palace_element
+    }
+
+    /// 世爻位置（初爻为0）。
+    pub fn shi(&self) -> usize {
+        self.generation.shi()
+    }
+
+    /// 应爻位置，与世爻相隔三爻。
+    pub fn ying(&self) -> usize {
+        (self.shi() + 3) % 6
+    }
+}
+
+/// 按 GuaCode 索引的六十四卦表，编译时由 SIXTYFOURGUA_DATA 生成。
+pub const GUA_TABLE: [GuaInfo; 64] = build_table();
+
+/// 查表取卦的信息，只用低 6 位。
+pub fn lookup(code: GuaCode) -> &'static GuaInfo {
+    &GUA_TABLE[(code & 0x3f) as usize]
+}
+
+/// 把 "122111" 这样的字符串索引（'1' 阳 '2' 阴，初爻在前）转成编码。
+pub const fn parse_code(index: &str) -> GuaCode {
+    let bytes = index.as_bytes();
+    let mut code = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'1' => code |= 1 << i,
+            b'2' => {}
+            _ => panic!("卦索引只能由 1 和 2 组成"),
+        }
+        i += 1;
+    }
+    code
+}
+
+// 八宫表中三爻卦为 trigram 的宫，其纳甲即该卦作内卦、外卦时的地支
+const fn palace_of_trigram(trigram: GuaCode) -> usize {
+    let mut p = 0;
+    while p < SIXTYFOURGUA_DATA.len() {
+        if parse_code(SIXTYFOURGUA_DATA[p].index) == trigram {
+            return p;
+        }
+        p += 1;
+    }
+    panic!("八宫表缺少三爻卦")
+}
+
+// 内外卦对应爻两两相冲为六冲卦，两两相合为六合卦
+const fn chong_he_of(najia: &[DiZhi; 6]) -> Option<ChongHe> {
+    let first = get_chong_he_relation(najia[0], najia[3]);
+    let mut i = 1;
+    while i < 3 {
+        let same = matches!(
+            (get_chong_he_relation(najia[i], najia[i + 3]), first),
+            (Some(ChongHe::Chong), Some(ChongHe::Chong)) | (Some(ChongHe::He), Some(ChongHe::He))
+        );
+        if !same {
+            return None;
+        }
+        i += 1;
+    }
+    first
+}
+
+const fn ends_with(name: &str, suffix: &str) -> bool {
+    let (name, suffix) = (name.as_bytes(), suffix.as_bytes());
+    if name.len() < suffix.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < suffix.len() {
+        if name[name.len() - suffix.len() + i] != suffix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn build_table() -> [GuaInfo; 64] {
+    const EMPTY: GuaInfo = GuaInfo {
+        name: "",
+        palace: 0,
+        generation: Generation::BenGong,
+        najia: [DiZhi::Zi; 6],
+        chong_he: None,
+    };
+    let mut table = [EMPTY; 64];
+    let mut filled = [false; 64];
+
+    let mut p = 0;
+    while p < SIXTYFOURGUA_DATA.len() {
+        let palace = &SIXTYFOURGUA_DATA[p];
+        let mut g = 0;
+        while g < 8 {
+            let code = parse_code(palace.gua_index[g]);
+            assert!(!filled[code as usize], "八宫表中有重复的卦");
+            filled[code as usize] = true;
+
+            let nei = &SIXTYFOURGUA_DATA[palace_of_trigram(code & 0b111)];
+            let wai = &SIXTYFOURGUA_DATA[palace_of_trigram(code >> 3)];
+            let najia = [
+                nei.nei_dizhi[0],
+                nei.nei_dizhi[1],
+                nei.nei_dizhi[2],
+                wai.wai_dizhi[0],
+                wai.wai_dizhi[1],
+                wai.wai_dizhi[2],
+            ];
+            let name = palace.gua_name[g];
+            let chong_he = chong_he_of(&najia);
+            assert!(
+                matches!(chong_he, Some(ChongHe::Chong)) == ends_with(name, "(六冲)"),
+                "卦名的六冲标记与纳甲不符"
+            );
+            assert!(
+                matches!(chong_he, Some(ChongHe::He)) == ends_with(name, "(六合)"),
+                "卦名的六合标记与纳甲不符"
+            );
+            table[code as usize] = GuaInfo {
+                name,
+                palace: p,
+                generation: Generation::ALL[g],
+                najia,
+                chong_he,
+            };
+            g += 1;
+        }
+        p += 1;
+    }
+    table
+}
@@ -0,0 +1,432 @@
+use crate::{
+    config::Config,
+    gua_table::{self, GuaCode},
+    locale::Locale,
+    TianGan, WuXing, SIXTYFOURGUA_DATA,
+};
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// 八卦（三爻卦），按先天八卦顺序排列。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigram {
+    Qian,
+    Dui,
+    Li,
+    Zhen,
+    Xun,
+    Kan,
+    Gen,
+    Kun,
+}
+
+impl Trigram {
+    pub const ALL: [Trigram; 8] = [
+        Trigram::Qian,
+        Trigram::Dui,
+        Trigram::Li,
+        Trigram::Zhen,
+        Trigram::Xun,
+        Trigram::Kan,
+        Trigram::Gen,
+        Trigram::Kun,
+    ];
+
+    /// 3 位编码：第 i 位对应第 i+1 爻，1 为阳爻。
+    pub const fn code(self) -> GuaCode {
+        match self {
+            Trigram::Qian => 0b111,
+            Trigram::Dui => 0b011,
+            Trigram::Li => 0b101,
+            Trigram::Zhen => 0b001,
+            Trigram::Xun => 0b110,
+            Trigram::Kan => 0b010,
+            Trigram::Gen => 0b100,
+            Trigram::Kun => 0b000,
+        }
+    }
+
+    /// 由 3 位编码取八卦，只用低 3 位。
+    pub fn from_code(code: GuaCode) -> Trigram {
+        Trigram::ALL
+            .into_iter()
+            .find(|t| t.code() == code & 0b111)
+            .unwrap_or(Trigram::Kun)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Trigram::Qian => "乾",
+            Trigram::Dui => "兑",
+            Trigram::Li => "离",
+            Trigram::Zhen => "震",
+            Trigram::Xun => "巽",
+            Trigram::Kan => "坎",
+            Trigram::Gen => "艮",
+            Trigram::Kun => "坤",
+        }
+    }
+
+    /// Unicode 卦符 ☰…☷，码位顺序与先天八卦顺序一致。
+    pub fn symbol(self) -> char {
+        char::from_u32(0x2630 + self as u32).unwrap_or('☷')
+    }
+
+    /// 取象，例如乾为天、坤为地。
+    pub fn image(self) -> &'static str {
+        match self {
+            Trigram::Qian => "天",
+            Trigram::Dui => "泽",
+            Trigram::Li => "火",
+            Trigram::Zhen => "雷",
+            Trigram::Xun => "风",
+            Trigram::Kan => "水",
+            Trigram::Gen => "山",
+            Trigram::Kun => "地",
+        }
+    }
+
+    pub fn element(self) -> WuXing {
+        match self {
+            Trigram::Qian | Trigram::Dui => WuXing::Jin,
+            Trigram::Li => WuXing::Huo,
+            Trigram::Zhen | Trigram::Xun => WuXing::Mu,
+            Trigram::Kan => WuXing::Shui,
+            Trigram::Gen | Trigram::Kun => WuXing::Tu,
+        }
+    }
+
+    /// 纳甲的天干：乾内甲外壬，坤内乙外癸，其余六卦内外相同，震庚、巽辛、坎戊、离己、艮丙、兑丁。
+    pub fn na_gan(self, outer: bool) -> TianGan {
+        match (self, outer) {
+            (Trigram::Qian, false) => TianGan::Jia,
+            (Trigram::Qian, true) => TianGan::Ren,
+            (Trigram::Kun, false) => TianGan::Yi,
+            (Trigram::Kun, true) => TianGan::Gui,
+            (Trigram::Zhen, _) => TianGan::Geng,
+            (Trigram::Xun, _) => TianGan::Xin,
+            (Trigram::Kan, _) => TianGan::Wu,
+            (Trigram::Li, _) => TianGan::Ji,
+            (Trigram::Gen, _) => TianGan::Bing,
+            (Trigram::Dui, _) => TianGan::Ding,
+        }
+    }
+
+    /// 后天八卦中方位相对的卦：乾巽、坎离、艮坤、震兑。内外卦变为相对之卦即卦反吟。
+    pub fn opposite(self) -> Trigram {
+        match self {
+            Trigram::Qian => Trigram::Xun,
+            Trigram::Xun => Trigram::Qian,
+            Trigram::Kan => Trigram::Li,
+            Trigram::Li => Trigram::Kan,
+            Trigram::Gen => Trigram::Kun,
+            Trigram::Kun => Trigram::Gen,
+            Trigram::Zhen => Trigram::Dui,
+            Trigram::Dui => Trigram::Zhen,
+        }
+    }
+
+    /// 《说卦传》中的家人之象：乾父坤母，震坎艮为长中少男，巽离兑为长中少女。
+    pub fn family(self) -> &'static str {
+        match self {
+            Trigram::Qian => "父",
+            Trigram::Kun => "母",
+            Trigram::Zhen => "长男",
+            Trigram::Kan => "中男",
+            Trigram::Gen => "少男",
+            Trigram::Xun => "长女",
+            Trigram::Li => "中女",
+            Trigram::Dui => "少女",
+        }
+    }
+}
+
+impl Display for Trigram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// 按文王卦序（《周易》通行本次序）排列的卦编码，KING_WEN[0] 为乾。
+const KING_WEN: [GuaCode; 64] = [
+    0b111111, 0b000000, 0b010001, 0b100010, 0b010111, 0b111010, 0b000010, 0b010000, //
+    0b110111, 0b111011, 0b000111, 0b111000, 0b111101, 0b101111, 0b000100, 0b001000, //
+    0b011001, 0b100110, 0b000011, 0b110000, 0b101001, 0b100101, 0b100000, 0b000001, //
+    0b111001, 0b100111, 0b100001, 0b011110, 0b010010, 0b101101, 0b011100, 0b001110, //
+    0b111100, 0b001111, 0b101000, 0b000101, 0b110101, 0b101011, 0b010100, 0b001010, //
+    0b100011, 0b110001, 0b011111, 0b111110, 0b011000, 0b000110, 0b011010, 0b010110, //
+    0b011101, 0b101110, 0b001001, 0b100100, 0b110100, 0b001011, 0b001101, 0b101100, //
+    0b110110, 0b011011, 0b110010, 0b010011, 0b110011, 0b001100, 0b010101, 0b101010, //
+];
+
+// KING_WEN 的反查表：编码 -> 文王卦序（1-64）
+const KING_WEN_NUMBER: [u8; 64] = {
+    let mut numbers = [0; 64];
+    let mut i = 0;
+    while i < 64 {
+        assert!(numbers[KING_WEN[i] as usize] == 0, "文王卦序中有重复的卦");
+        numbers[KING_WEN[i] as usize] = i as u8 + 1;
+        i += 1;
+    }
+    numbers
+};
+
+/// 六爻卦（重卦）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hexagram(GuaCode);
+
+/// 解析卦的各种表示时出现的错误。
+#[derive(Debug)]
+pub enum HexagramError {
+    Index(String),
+    KingWen(u32),
+    FuXi(u32),
+    Symbol(char),
+}
+
+impl Display for HexagramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexagramError::Index(s) => write!(
+                f,
+                "卦索引应为6位，1为阳2为阴，初爻在前，例如 \"122111\"，收到 \"{}\"",
+                s
+            ),
+            HexagramError::KingWen(n) => {
+                write!(f, "文王卦序必须在1-64之间，收到 {}", n)
+            }
+            HexagramError::FuXi(n) => write!(f, "伏羲卦序必须在1-64之间，收到 {}", n),
+            HexagramError::Symbol(c) => {
+                write!(f, "\"{}\" 不是六十四卦卦符（䷀-䷿）", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HexagramError {}
+
+impl Hexagram {
+    /// 由 6 位编码取卦，只用低 6 位。
+    pub fn from_code(code: GuaCode) -> Self {
+        Hexagram(code & 0x3f)
+    }
+
+    pub fn code(self) -> GuaCode {
+        self.0
+    }
+
+    pub fn lower(self) -> Trigram {
+        Trigram::from_code(self.0)
+    }
+
+    pub fn upper(self) -> Trigram {
+        Trigram::from_code(self.0 >> 3)
+    }
+
+    /// 文王卦序，乾为1，未济为64。
+    pub fn king_wen(self) -> u32 {
+        KING_WEN_NUMBER[self.0 as usize] as u32
+    }
+
+    pub fn from_king_wen(number: u32) -> Result<Self, HexagramError> {
+        match number {
+            1..=64 => Ok(Hexagram(KING_WEN[number as usize - 1])),
+            _ => Err(HexagramError::KingWen(number)),
+        }
+    }
+
+    /// 伏羲（邵雍先天）卦序，乾为1，坤为64。
+    /// 以阳爻为1、初爻为最高位读作二进制数，卦序即 64 减去该数。
+    pub fn fu_xi(self) -> u32 {
+        64 - (self.0.reverse_bits() >> 2) as u32
+    }
+
+    pub fn from_fu_xi(number: u32) -> Result<Self, HexagramError> {
+        match number {
+            1..=64 => Ok(Hexagram(((64 - number) as u8).reverse_bits() >> 2)),
+            _ => Err(HexagramError::FuXi(number)),
+        }
+    }
+
+    /// Unicode 卦符，码位 U+4DC0 起按文王卦序排列。
+    pub fn symbol(self) -> char {
+        char::from_u32(0x4DC0 + self.king_wen() - 1).unwrap_or('䷀')
+    }
+
+    pub fn from_symbol(symbol: char) -> Result<Self, HexagramError> {
+        match symbol as u32 {
+            number @ 0x4DC0..=0x4DFF => Hexagram::from_king_wen(number - 0x4DC0 + 1),
+            _ => Err(HexagramError::Symbol(symbol)),
+        }
+    }
+
+    /// 原有的字符串索引，'1' 阳 '2' 阴，初爻在前，例如 "122111"。
+    pub fn index(self) -> String {
+        (0..6)
+            .map(|i| if self.0 >> i & 1 == 1 { '1' } else { '2' })
+            .collect()
+    }
+
+    /// 卦名，不带卦符和六冲、六合标记，例如 "天风姤"。
+    pub fn name(self) -> &'static str {
+        let name = gua_table::lookup(self.0).name;
+        name.split(self.symbol()).next().unwrap_or(name)
+    }
+
+    /// 所属宫，即本宫卦的上下卦。
+    pub fn palace(self) -> Trigram {
+        let palace = &SIXTYFOURGUA_DATA[gua_table::lookup(self.0).palace];
+        Trigram::from_code(gua_table::parse_code(palace.index))
+    }
+}
+
+impl FromStr for Hexagram {
+    type Err = HexagramError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 6 || !s.bytes().all(|b| b == b'1' || b == b'2') {
+            return Err(HexagramError::Index(s.to_string()));
+        }
+        Ok(Hexagram(gua_table::parse_code(s)))
+    }
+}
+
+impl Display for Hexagram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.name(), self.symbol())
+    }
+}
+
+/// 八卦在接口中的表示。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TrigramResponse {
+    pub name: String,
+    pub symbol: char,
+    pub image: String,
+    pub wuxing: String,
+    pub family: String,
+}
+
+impl From<Trigram> for TrigramResponse {
+    fn from(trigram: Trigram) -> Self {
+        TrigramResponse {
+            name: trigram.name().to_string(),
+            symbol: trigram.symbol(),
+            image: trigram.image().to_string(),
+            wuxing: trigram.element().to_string(),
+            family: trigram.family().to_string(),
+        }
+    }
+}
+
+impl TrigramResponse {
+    pub fn localize(&mut self, locale: Locale) {
+        for text in [
+            &mut self.name,
+            &mut self.image,
+            &mut self.wuxing,
+            &mut self.family,
+        ] {
+            *text = locale.term(text).to_string();
+        }
+    }
+}
+
+/// 六爻卦在接口中的表示，附上各种编号便于与其他软件和典籍对照。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct HexagramResponse {
+    pub name: String,
+    pub symbol: char,
+    /// '1' 阳 '2' 阴，初爻在前
+    pub index: String,
+    pub king_wen: u32,
+    pub fu_xi: u32,
+    pub upper: TrigramResponse,
+    pub lower: TrigramResponse,
+    /// 所属宫，例如 "乾"
+    pub palace: String,
+    /// 在本宫中的位置，例如 "游魂"
+    pub generation: String,
+}
+
+impl From<Hexagram> for HexagramResponse {
+    fn from(hexagram: Hexagram) -> Self {
+        HexagramResponse {
+            name: hexagram.name().to_string(),
+            symbol: hexagram.symbol(),
+            index: hexagram.index(),
+            king_wen: hexagram.king_wen(),
+            fu_xi: hexagram.fu_xi(),
+            upper: hexagram.upper().into(),
+            lower: hexagram.lower().into(),
+            palace: hexagram.palace().name().to_string(),
+            generation: gua_table::lookup(hexagram.code()).generation.to_string(),
+        }
+    }
+}
+
+impl HexagramResponse {
+    /// 把卦名、上下卦、宫和世代译成指定语言，编号和卦符不变。
+    pub fn localize(&mut self, locale: Locale) {
+        for text in [&mut self.name, &mut self.palace, &mut self.generation] {
+            *text = locale.term(text).to_string();
+        }
+        self.upper.localize(locale);
+        self.lower.localize(locale);
+    }
+}
+
+/// 查卦时数字按哪种卦序理解。
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+    #[default]
+    KingWen,
+    FuXi,
+}
+
+#[derive(Deserialize)]
+pub struct HexagramQuery {
+    #[serde(default)]
+    order: Order,
+    /// 文本字段的语言，缺省时使用配置
+    locale: Option<Locale>,
+}
+
+impl Hexagram {
+    /// 解析查卦参数：卦序数字、卦符 ䷀-䷿ 或 "122111" 形式的索引。
+    pub fn parse(key: &str, order: Order) -> Result<Self, HexagramError> {
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(symbol), None) if !symbol.is_ascii_digit() => Hexagram::from_symbol(symbol),
+            _ if key.len() == 6 && key.bytes().all(|b| b == b'1' || b == b'2') => key.parse(),
+            _ => {
+                let number = key
+                    .parse()
+                    .map_err(|_| HexagramError::Index(key.to_string()))?;
+                match order {
+                    Order::KingWen => Hexagram::from_king_wen(number),
+                    Order::FuXi => Hexagram::from_fu_xi(number),
+                }
+            }
+        }
+    }
+}
+
+/// 查询一卦的卦名、卦符、各种卦序及上下卦，例如 /hexagram/44、/hexagram/䷫、/hexagram/211111。
+pub async fn hexagram(
+    config: web::Data<Config>,
+    key: web::Path<String>,
+    query: web::Query<HexagramQuery>,
+) -> impl Responder {
+    match Hexagram::parse(&key, query.order) {
+        Ok(hexagram) => {
+            let mut response = HexagramResponse::from(hexagram);
+            response.localize(query.locale.unwrap_or(config.locale));
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => HttpResponse::BadRequest().json(e.to_string()),
+    }
+}
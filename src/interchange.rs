@@ -0,0 +1,365 @@
+use crate::{
+    api::ApiError,
+    build_guas,
+    config::Config,
+    readings::chart_of,
+    storage::{
+        ImportedReading, Location, NewReading, Outcome, Reading, ReadingFilter, Storage,
+        YingQiPrediction,
+    },
+    DiZhi, PaipanError, ZiShi, CHART_VERSION, SUPPORTED_YEARS,
+};
+use actix_web::{web, HttpResponse};
+use chrono::{Datelike, FixedOffset, NaiveDateTime};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+
+/// 交换格式的标识与当前版本，格式说明见 docs/interchange.md。
+/// 版本 2 用 chart_version 取代 engine_version，并保存起卦时的排盘设置；
+/// 版本 3 把 chart_version 记在每条记录上。
+pub const FORMAT: &str = "sixsixsix.readings";
+pub const VERSION: u32 = 3;
+
+/// 导出时每次从数据库读取的条数。
+const PAGE_SIZE: u32 = 500;
+
+/// 排盘结果中参与一致性核对的字段。
+const CHART_FIELDS: [&str; 6] = [
+    "gua_lines",
+    "year_ganzhi",
+    "month_ganzhi",
+    "day_ganzhi",
+    "hour_ganzhi",
+    "xun_kong",
+];
+
+/// 交换文件：一组占卜记录及导出方排盘结果的结构版本。
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Document {
+    pub format: String,
+    pub version: u32,
+    /// 版本 2 的文件在这里记录排盘结果的结构版本，对没有 chart_version 的记录生效；
+    /// 版本 3 起记在每条记录上
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart_version: Option<u32>,
+    /// 版本 1 的文件记录的是程序版本，据此无法判断排盘结果能否比较，导入时忽略
+    #[serde(rename = "engine_version", default, skip_serializing)]
+    #[schema(ignore)]
+    pub _engine_version: Option<IgnoredAny>,
+    pub exported_at: Option<NaiveDateTime>,
+    pub readings: Vec<Record>,
+}
+
+/// 交换文件中的一条记录。
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Record {
+    /// 六次阳面个数，从初爻到上爻
+    pub numbers: String,
+    /// 起卦的当地时间
+    pub cast_at: NaiveDateTime,
+    /// 排盘时 23 点子时的日柱归属
+    #[serde(default)]
+    pub zi_shi: ZiShi,
+    pub location: Option<Location>,
+    /// 按真太阳时排盘时起卦时间的 UTC 偏移（秒），经度取 location.longitude
+    pub solar_time_utc_offset: Option<i32>,
+    #[serde(default)]
+    pub question: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub interpretation: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    pub outcome: Option<Outcome>,
+    pub yingqi: Option<YingQiPrediction>,
+    /// 导出方的排盘结果，导入时会重新计算并核对
+    pub chart: serde_json::Value,
+    /// 计算 chart 时排盘结果的结构版本；早期保存的记录没有
+    #[serde(default)]
+    pub chart_version: Option<u32>,
+}
+
+impl Record {
+    // 早期的记录没有保存子时约定，按 zi_shi 导出
+    fn from_reading(reading: Reading, zi_shi: ZiShi) -> Self {
+        Record {
+            numbers: reading.numbers,
+            cast_at: reading.cast_at,
+            zi_shi: reading.zi_shi.unwrap_or(zi_shi),
+            location: reading.location,
+            solar_time_utc_offset: reading.solar_time_utc_offset,
+            question: reading.question,
+            category: reading.category,
+            tags: reading.tags,
+            interpretation: reading.interpretation,
+            notes: reading.notes,
+            outcome: reading.outcome,
+            yingqi: reading.yingqi,
+            chart: reading.chart,
+            chart_version: reading.chart_version,
+        }
+    }
+
+    // 格式之外的校验，返回 "字段: 原因" 形式的错误
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Err(e) = build_guas(&self.numbers) {
+            errors.push(format!("numbers: {}", e));
+        }
+        if !SUPPORTED_YEARS.contains(&(self.cast_at.year() as i64)) {
+            errors.push(format!(
+                "cast_at: {}",
+                PaipanError::DateOutOfRange(self.cast_at)
+            ));
+        }
+        let longitude = self.location.as_ref().and_then(|l| l.longitude);
+        if let Some(longitude) = longitude {
+            if !(-180.0..=180.0).contains(&longitude) {
+                errors.push(format!(
+                    "location.longitude: 经度必须在-180到180之间，收到 {}",
+                    longitude
+                ));
+            }
+        }
+        if let Some(offset) = self.solar_time_utc_offset {
+            if FixedOffset::east_opt(offset).is_none() {
+                errors.push(format!(
+                    "solar_time_utc_offset: UTC 偏移必须小于一天，收到 {}",
+                    offset
+                ));
+            }
+            if longitude.is_none() {
+                errors.push(
+                    "solar_time_utc_offset: 按真太阳时排盘需要给出 location.longitude".to_string(),
+                );
+            }
+        }
+        if let Some(yingqi) = &self.yingqi {
+            if DiZhi::try_from(yingqi.zhi.as_str()).is_err() {
+                errors.push(format!("yingqi.zhi: \"{}\" 不是地支", yingqi.zhi));
+            }
+        }
+        if !self.chart.is_object() {
+            errors.push("chart: 必须是排盘结果对象".to_string());
+        }
+        errors
+    }
+}
+
+/// 导入时单条记录的核对结果。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ImportResult {
+    pub index: usize,
+    pub id: Option<i64>,
+    /// 重新排盘的结果是否与文件中的一致
+    pub chart_matches: bool,
+    /// 文件中这条记录的排盘结果结构版本与本程序不同，或没有注明
+    pub chart_version_differs: bool,
+    /// 不一致的字段
+    pub differences: Vec<&'static str>,
+}
+
+/// 导入报告。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// 有记录的排盘结果结构版本与本程序不同，或没有注明
+    pub chart_version_differs: bool,
+    /// 本程序排盘结果的结构版本
+    pub chart_version: u32,
+    pub results: Vec<ImportResult>,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    /// 为 true 时只要有一条排盘结果不一致就全部不导入
+    #[serde(default)]
+    strict: bool,
+}
+
+/// 注册导入导出路由。
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/export", web::get().to(export))
+        .route("/import", web::post().to(import));
+}
+
+/// 按分类、标签导出记录；不给 limit 时导出全部。
+#[utoipa::path(
+    get,
+    path = "/api/v1/export",
+    tag = "readings",
+    params(ReadingFilter),
+    responses(
+        (status = 200, description = "交换文件", body = Document),
+    )
+)]
+pub async fn export(
+    config: web::Data<Config>,
+    storage: web::Data<Storage>,
+    filter: web::Query<ReadingFilter>,
+) -> actix_web::Result<HttpResponse> {
+    let filter = filter.into_inner();
+    let readings = web::block(move || {
+        if filter.limit.is_some() {
+            return storage.list(&filter);
+        }
+        let mut all = Vec::new();
+        let mut offset = filter.offset.unwrap_or(0);
+        loop {
+            let page = storage.list(&ReadingFilter {
+                category: filter.category.clone(),
+                tag: filter.tag.clone(),
+                limit: Some(PAGE_SIZE),
+                offset: Some(offset),
+            })?;
+            let done = page.len() < PAGE_SIZE as usize;
+            offset += page.len() as u32;
+            all.extend(page);
+            if done {
+                return Ok(all);
+            }
+        }
+    })
+    .await??;
+    let zi_shi = config.zi_shi;
+    Ok(HttpResponse::Ok().json(Document {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        chart_version: None,
+        _engine_version: None,
+        exported_at: Some(config.now()),
+        readings: readings
+            .into_iter()
+            .map(|reading| Record::from_reading(reading, zi_shi))
+            .collect(),
+    }))
+}
+
+/// 校验交换文件并重新排盘，全部通过后在一个事务中导入。
+#[utoipa::path(
+    post,
+    path = "/api/v1/import",
+    tag = "readings",
+    params(ImportQuery),
+    request_body = Document,
+    responses(
+        (status = 200, description = "导入报告", body = ImportReport),
+        (status = 400, description = "文件格式或内容有误，errors 中列出每一处", body = ApiError),
+        (status = 409, description = "strict 时有排盘结果不一致，没有导入任何记录", body = ImportReport),
+    )
+)]
+pub async fn import(
+    config: web::Data<Config>,
+    storage: web::Data<Storage>,
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+) -> actix_web::Result<HttpResponse> {
+    let document: Document = match serde_json::from_slice(&body) {
+        Ok(document) => document,
+        Err(e) => return Ok(invalid_document(vec![format!("格式错误: {}", e)])),
+    };
+
+    let mut errors = Vec::new();
+    if document.format != FORMAT {
+        errors.push(format!(
+            "format: 应为 \"{}\"，收到 \"{}\"",
+            FORMAT, document.format
+        ));
+    }
+    if document.version == 0 || document.version > VERSION {
+        errors.push(format!(
+            "version: 不支持的版本 {}，本程序支持 1-{}",
+            document.version, VERSION
+        ));
+    }
+    for (i, record) in document.readings.iter().enumerate() {
+        errors.extend(
+            record
+                .validate()
+                .into_iter()
+                .map(|e| format!("readings[{}].{}", i, e)),
+        );
+    }
+    if !errors.is_empty() {
+        return Ok(invalid_document(errors));
+    }
+
+    // 用本程序重新排盘，保存的是重新计算的结果，文件中的结果只用于核对
+    let mut results = Vec::new();
+    let mut imported = Vec::new();
+    // 交换文件不带保存时刻，导入的记录一律记为导入的时刻
+    let imported_at = config.now();
+    for (index, record) in document.readings.into_iter().enumerate() {
+        let chart = match chart_of(
+            &config,
+            &record.numbers,
+            record.cast_at,
+            record.zi_shi,
+            record.location.as_ref(),
+            record.solar_time_utc_offset,
+        ) {
+            Ok(chart) => serde_json::to_value(&chart)?,
+            Err(e) => return Ok(HttpResponse::InternalServerError().json(e.to_string())),
+        };
+        let differences: Vec<&'static str> = CHART_FIELDS
+            .into_iter()
+            .filter(|field| chart.get(field) != record.chart.get(field))
+            .collect();
+        let chart_version = record.chart_version.or(document.chart_version);
+        results.push(ImportResult {
+            index,
+            id: None,
+            chart_matches: differences.is_empty(),
+            chart_version_differs: chart_version != Some(CHART_VERSION),
+            differences,
+        });
+        imported.push(ImportedReading {
+            reading: NewReading {
+                numbers: record.numbers,
+                cast_at: record.cast_at,
+                question: record.question,
+                category: record.category,
+                location: record.location,
+                zi_shi: record.zi_shi,
+                solar_time_utc_offset: record.solar_time_utc_offset,
+                chart,
+                chart_version: CHART_VERSION,
+                interpretation: record.interpretation,
+                tags: record.tags,
+                created_at: imported_at,
+            },
+            notes: record.notes,
+            outcome: record.outcome,
+            yingqi: record.yingqi,
+        });
+    }
+
+    let mut report = ImportReport {
+        imported: 0,
+        chart_version_differs: results.iter().any(|r| r.chart_version_differs),
+        chart_version: CHART_VERSION,
+        results,
+    };
+    if query.strict && report.results.iter().any(|r| !r.chart_matches) {
+        return Ok(HttpResponse::Conflict().json(report));
+    }
+    let ids = web::block(move || storage.import(&imported)).await??;
+    report.imported = ids.len();
+    for (result, id) in report.results.iter_mut().zip(ids) {
+        result.id = Some(id);
+    }
+    Ok(HttpResponse::Ok().json(report))
+}
+
+// 一次列出文件中的所有错误
+fn invalid_document(errors: Vec<String>) -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiError {
+        status: 400,
+        message: format!("交换文件有 {} 处错误", errors.len()),
+        errors,
+    })
+}
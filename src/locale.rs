@@ -0,0 +1,161 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+use std::sync::OnceLock;
+
+/// 排盘输出和网页界面使用的语言。
+///
+/// 只影响面向人阅读的文本字段；枚举字段（如爻的 role）在各语言下序列化结果相同。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+    utoipa::ToSchema,
+)]
+pub enum Locale {
+    /// 简体中文，即各枚举 Display 的原文
+    #[default]
+    #[serde(rename = "zh-Hans")]
+    #[value(name = "zh-Hans")]
+    ZhHans,
+    #[serde(rename = "zh-Hant")]
+    #[value(name = "zh-Hant")]
+    ZhHant,
+    #[serde(rename = "en")]
+    #[value(name = "en")]
+    En,
+    /// 带声调的汉语拼音
+    #[serde(rename = "pinyin")]
+    #[value(name = "pinyin")]
+    Pinyin,
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Locale::ZhHans => "zh-Hans",
+                Locale::ZhHant => "zh-Hant",
+                Locale::En => "en",
+                Locale::Pinyin => "pinyin",
+            }
+        )
+    }
+}
+
+/// data/locales.toml 中的译名表。
+struct Terms {
+    /// 简体原文 -> [繁体, 英文, 拼音]
+    terms: HashMap<String, [String; 3]>,
+    /// 最长的键有几个字符，分词时最多向后看这么远
+    longest: usize,
+}
+
+#[derive(Deserialize)]
+struct LocaleFile {
+    terms: HashMap<String, [String; 3]>,
+}
+
+fn terms() -> &'static Terms {
+    static TERMS: OnceLock<Terms> = OnceLock::new();
+    TERMS.get_or_init(|| {
+        let terms = toml::from_str::<LocaleFile>(include_str!("../data/locales.toml"))
+            .expect("内置的译名表格式有误")
+            .terms;
+        let longest = terms.keys().map(|k| k.chars().count()).max().unwrap_or(0);
+        Terms { terms, longest }
+    })
+}
+
+impl Locale {
+    /// 译名表中对应的列，简体中文没有单独的列。
+    fn column(self) -> Option<usize> {
+        match self {
+            Locale::ZhHans => None,
+            Locale::ZhHant => Some(0),
+            Locale::En => Some(1),
+            Locale::Pinyin => Some(2),
+        }
+    }
+
+    /// 英文和拼音按词书写，相邻的词之间要补空格。
+    pub fn spaced(self) -> bool {
+        matches!(self, Locale::En | Locale::Pinyin)
+    }
+
+    /// 查一个词的译名，例如 En 下 "妻财" 为 "Wealth"；没有收录或译名为空时返回原文。
+    pub fn term(self, zh: &str) -> &str {
+        self.column()
+            .and_then(|column| terms().terms.get(zh).map(|t| t[column].as_str()))
+            .filter(|t| !t.is_empty())
+            .unwrap_or(zh)
+    }
+
+    /// 翻译一段排盘文本，例如 En 下 "妻财子水⚊ o" 为 "Wealth Zi Water ⚊ o"。
+    /// 从左到右按最长匹配查译名表，没有收录的字符原样保留。
+    pub fn text(self, s: &str) -> String {
+        if self.column().is_none() {
+            return s.to_string();
+        }
+        let terms = terms();
+        let mut out = String::with_capacity(s.len());
+        let mut last_translated = false;
+        let mut rest = s;
+        while let Some(c) = rest.chars().next() {
+            let (len, piece) = rest
+                .char_indices()
+                .take(terms.longest)
+                .map(|(i, c)| i + c.len_utf8())
+                .filter(|&end| terms.terms.contains_key(&rest[..end]))
+                .last()
+                .map_or((c.len_utf8(), None), |end| {
+                    (end, Some(self.term(&rest[..end])))
+                });
+            let translated = piece.is_some();
+            let piece = piece.unwrap_or(&rest[..len]);
+            if self.spaced() && (translated || last_translated) && needs_space(&out, piece) {
+                out.push(' ');
+            }
+            out.push_str(piece);
+            last_translated = translated;
+            rest = &rest[len..];
+        }
+        out
+    }
+
+    /// 整张译名表，键为简体原文，供网页界面使用。
+    pub fn table(self) -> BTreeMap<&'static str, &'static str> {
+        terms()
+            .terms
+            .keys()
+            .map(|zh| (zh.as_str(), self.term(zh)))
+            .collect()
+    }
+}
+
+// 前后都不是空白或括号、标点时才需要空格
+fn needs_space(before: &str, piece: &str) -> bool {
+    match (before.chars().last(), piece.chars().next()) {
+        (Some(prev), Some(next)) => {
+            !prev.is_whitespace()
+                && prev != '('
+                && !next.is_whitespace()
+                && !matches!(next, ')' | ':' | ',' | '.' | ';')
+        }
+        _ => false,
+    }
+}
+
+/// 取某种语言的译名表，例如 /locales/en，网页界面据此翻译按钮和提示。
+pub async fn locale_table(locale: web::Path<Locale>) -> impl Responder {
+    HttpResponse::Ok().json(locale.table())
+}
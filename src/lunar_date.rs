@@ -0,0 +1,165 @@
+use crate::{DiZhi, SUPPORTED_YEARS};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use lunar_rust::{
+    lunar::{self, LunarRefHelper},
+    solar::{self, SolarRefHelper},
+};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// 以农历给出的起卦时间，例如农历 2025 年闰六月初三午时。
+#[derive(Debug, Clone, Deserialize, utoipa::ToSchema)]
+pub struct LunarDateInput {
+    pub year: i64,
+    /// 农历月份 1-12，闰月用 leap 标记
+    pub month: i64,
+    pub day: i64,
+    #[serde(default)]
+    pub leap: bool,
+    /// 时辰地支，例如 "午"；子时取当天 0 点的早子时
+    pub shichen: String,
+}
+
+/// 农历日期无效的原因。
+#[derive(Debug)]
+pub enum LunarDateError {
+    /// 超出 lunar_rust 能换算的年份范围
+    InvalidYear(i64),
+    InvalidMonth(i64),
+    InvalidDay(i64),
+    /// 该年没有这个闰月
+    NoLeapMonth {
+        year: i64,
+        month: i64,
+    },
+    /// 该月是小月，没有三十
+    NoSuchDay {
+        year: i64,
+        month: i64,
+        day: i64,
+    },
+    InvalidShiChen(String),
+    /// 命令行参数格式错误
+    InvalidFormat(String),
+}
+
+impl std::fmt::Display for LunarDateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LunarDateError::InvalidYear(year) => write!(
+                f,
+                "农历年份必须在{}-{}之间，收到 {}",
+                SUPPORTED_YEARS.start(),
+                SUPPORTED_YEARS.end(),
+                year
+            ),
+            LunarDateError::InvalidMonth(month) => {
+                write!(f, "农历月份必须在1-12之间，收到 {}", month)
+            }
+            LunarDateError::InvalidDay(day) => write!(f, "农历日必须在1-30之间，收到 {}", day),
+            LunarDateError::NoLeapMonth { year, month } => {
+                write!(f, "农历{}年没有闰{}月", year, month)
+            }
+            LunarDateError::NoSuchDay { year, month, day } => {
+                write!(f, "农历{}年{}月没有{}日", year, month, day)
+            }
+            LunarDateError::InvalidShiChen(shichen) => {
+                write!(f, "\"{}\" 不是时辰，请用子、丑、寅……亥", shichen)
+            }
+            LunarDateError::InvalidFormat(s) => write!(
+                f,
+                "无法解析农历日期 \"{}\"，示例：2025-06-03 午、2025-闰06-03 午",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LunarDateError {}
+
+impl LunarDateInput {
+    /// 换算为公历时间，时辰取起始整点（子时为 0 点）。
+    pub fn to_solar(&self) -> Result<NaiveDateTime, LunarDateError> {
+        if !SUPPORTED_YEARS.contains(&self.year) {
+            return Err(LunarDateError::InvalidYear(self.year));
+        }
+        if !(1..=12).contains(&self.month) {
+            return Err(LunarDateError::InvalidMonth(self.month));
+        }
+        if !(1..=30).contains(&self.day) {
+            return Err(LunarDateError::InvalidDay(self.day));
+        }
+        let zhi = DiZhi::try_from(self.shichen.as_str())
+            .map_err(|_| LunarDateError::InvalidShiChen(self.shichen.clone()))?;
+        let hour = match zhi as u32 {
+            0 => 0,
+            i => i * 2 - 1,
+        };
+
+        // 非闰月一定存在，先取该月初一，闰月紧跟在同名月之后
+        let solar = lunar::from_ymd(self.year, self.month, 1).get_solar();
+        let first = NaiveDate::from_ymd_opt(
+            solar.get_year() as i32,
+            solar.get_month() as u32,
+            solar.get_day() as u32,
+        )
+        .ok_or(LunarDateError::InvalidMonth(self.month))?;
+        let first = if self.leap {
+            [29, 30]
+                .into_iter()
+                .map(|days| first + Duration::days(days))
+                .find(|date| lunar_ymd(*date) == (self.year, -self.month, 1))
+                .ok_or(LunarDateError::NoLeapMonth {
+                    year: self.year,
+                    month: self.month,
+                })?
+        } else {
+            first
+        };
+
+        // 换算回农历核对，小月的三十会落到下个月
+        let date = first + Duration::days(self.day - 1);
+        let month = if self.leap { -self.month } else { self.month };
+        if lunar_ymd(date) != (self.year, month, self.day) {
+            return Err(LunarDateError::NoSuchDay {
+                year: self.year,
+                month: self.month,
+                day: self.day,
+            });
+        }
+        Ok(date.and_hms_opt(hour, 0, 0).unwrap_or_default())
+    }
+}
+
+/// 命令行格式："2025-06-03 午"，闰月写作 "2025-闰06-03 午"。
+impl FromStr for LunarDateInput {
+    type Err = LunarDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || LunarDateError::InvalidFormat(s.to_string());
+        let (date, shichen) = s.trim().split_once(' ').ok_or_else(invalid)?;
+        let mut parts = date.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let (leap, month) = match month.strip_prefix('闰') {
+            Some(month) => (true, month),
+            None => (false, month),
+        };
+        Ok(LunarDateInput {
+            year: year.parse().map_err(|_| invalid())?,
+            month: month.parse().map_err(|_| invalid())?,
+            day: day.parse().map_err(|_| invalid())?,
+            leap,
+            shichen: shichen.trim().to_string(),
+        })
+    }
+}
+
+// 公历日期对应的农历年、月（闰月为负）、日
+fn lunar_ymd(date: NaiveDate) -> (i64, i64, i64) {
+    let lunar =
+        solar::from_ymd(date.year() as i64, date.month() as i64, date.day() as i64).get_lunar();
+    (lunar.get_year(), lunar.get_month(), lunar.get_day())
+}
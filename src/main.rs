@@ -1,16 +1,55 @@
-use actix_files::Files;
-use actix_web::{web, App, HttpResponse, HttpServer, Responder};
-use chrono::{Datelike, Local, Timelike};
+use actix_web::{
+    http::header::{self, Accept},
+    middleware::{DefaultHeaders, Logger},
+    web, App, HttpResponse, HttpServer, Responder,
+};
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use clap::{Parser, Subcommand};
 use lunar_rust::{
     lunar::LunarRefHelper,
     solar::{self, SolarRefHelper},
 };
-use rust_embed::RustEmbed;
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display};
+use std::io::Write;
+
+mod api;
+mod assets;
+mod batch;
+mod bazi;
+mod calendar;
+mod cli;
+mod config;
+mod enumeration;
+mod gua_table;
+mod hexagram;
+mod interchange;
+mod locale;
+mod lunar_date;
+mod raster;
+mod readings;
+mod render;
+mod solar_time;
+mod stats;
+mod storage;
+mod yingqi;
+mod zhouyi;
+
+#[cfg(test)]
+mod tests;
+
+use bazi::BaZi;
+use config::{Config, ConfigArgs};
+use gua_table::GuaCode;
+use hexagram::{Hexagram, HexagramResponse, Trigram};
+use locale::Locale;
+use lunar_date::LunarDateInput;
+use solar_time::{SolarTime, SolarTimeError, SolarTimeOptions};
+use storage::Storage;
 
 /// 表示五行（金、木、水、火、土）。
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
 enum WuXing {
     Jin,
     Mu,
@@ -19,6 +58,18 @@ enum WuXing {
     Tu,
 }
 
+impl WuXing {
+    /// 五行的墓库：水土墓辰，木墓未，火墓戌，金墓丑。
+    fn mu_ku(&self) -> DiZhi {
+        match self {
+            WuXing::Shui | WuXing::Tu => DiZhi::Chen,
+            WuXing::Mu => DiZhi::Wei,
+            WuXing::Huo => DiZhi::Xu,
+            WuXing::Jin => DiZhi::Chou,
+        }
+    }
+}
+
 // 实现 Display trait，用于将五行枚举转换为可打印的汉字字符串（如“金”）。
 impl Display for WuXing {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -36,8 +87,32 @@ impl Display for WuXing {
     }
 }
 
+/// 表示十天干（甲、乙、丙等），用于纳甲。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+enum TianGan {
+    Jia,
+    Yi,
+    Bing,
+    Ding,
+    Wu,
+    Ji,
+    Geng,
+    Xin,
+    Ren,
+    Gui,
+}
+
+// 实现 Display trait，用于将天干枚举转换为可打印的汉字字符串（如“甲”）。
+impl Display for TianGan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", TIAN_GAN[*self as usize])
+    }
+}
+
 /// 表示十二地支（子、丑、寅等）。
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
 enum DiZhi {
     Zi,
     Chou,
@@ -54,6 +129,33 @@ enum DiZhi {
 }
 
 impl DiZhi {
+    /// 按子、丑、寅……亥顺序排列的十二地支。
+    const ALL: [DiZhi; 12] = [
+        DiZhi::Zi,
+        DiZhi::Chou,
+        DiZhi::Yin,
+        DiZhi::Mao,
+        DiZhi::Chen,
+        DiZhi::Si,
+        DiZhi::Wu,
+        DiZhi::Wei,
+        DiZhi::Shen,
+        DiZhi::You,
+        DiZhi::Xu,
+        DiZhi::Hai,
+    ];
+
+    /// 与此地支相冲的地支，例如子冲午。
+    fn chong(&self) -> DiZhi {
+        DiZhi::ALL[(*self as usize + 6) % 12]
+    }
+
+    /// 与此地支六合的地支，例如子合丑。
+    fn he(&self) -> DiZhi {
+        // 六合两支的序号之和模12恒为1（子丑、寅亥、卯戌……）
+        DiZhi::ALL[(13 - *self as usize) % 12]
+    }
+
     /// 根据地支推算其对应的五行。
     /// 这是核心规则之一，将地支与五行关联起来。
     fn wuxing(&self) -> WuXing {
@@ -115,7 +217,8 @@ impl Display for DiZhi {
 }
 
 /// 表示六亲（兄弟、子孙、妻财、官鬼、父母）。
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
 enum LiuQin {
     XiongDi,
     ZiSun,
@@ -124,6 +227,16 @@ enum LiuQin {
     FuMu,
 }
 
+impl LiuQin {
+    const ALL: [LiuQin; 5] = [
+        LiuQin::XiongDi,
+        LiuQin::ZiSun,
+        LiuQin::QiCai,
+        LiuQin::GuanGui,
+        LiuQin::FuMu,
+    ];
+}
+
 // 实现 Display trait，用于将六亲枚举转换为可打印的汉字字符串（如“兄弟”）。
 impl Display for LiuQin {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -189,22 +302,18 @@ impl Yao {
         }
     }
 
-    /// 获取爻对应的数字索引字符（'1'代表阳，'2'代表阴）。
-    /// 用于构成卦的六位数字索引，例如 "111111"。
-    fn index_char(&self) -> char {
-        match self {
-            Yao::YinChanging | Yao::YinStatic => '2',
-            Yao::YangStatic | Yao::YangChanging => '1',
-        }
+    /// 是否为阳爻，用于构成卦的 6 位编码。
+    fn is_yang(&self) -> bool {
+        matches!(self, Yao::YangStatic | Yao::YangChanging)
     }
 
     /// 获取此爻变化后的爻（动爻变为其相反的静爻，静爻不变）。
     /// 用于从正卦计算变卦。
-    fn to_bian_yao(&self) -> Self {
+    fn to_bian_yao(self) -> Self {
         match self {
             Yao::YinChanging => Yao::YangStatic,
             Yao::YangChanging => Yao::YinStatic,
-            static_yao => *static_yao, // 不变的爻保持原样
+            static_yao => static_yao, // 不变的爻保持原样
         }
     }
 }
@@ -224,15 +333,29 @@ impl From<char> for Yao {
 }
 
 // 表示一个爻在卦中的角色（世、应或普通）。
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, utoipa::ToSchema)]
 enum YaoRole {
     Shi,    // 世爻
     Ying,   // 应爻
     Normal, // 普通爻
 }
+// 实现 Display trait，用于打印 "世"、"应"，普通爻为空字符串。
+impl Display for YaoRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                YaoRole::Shi => "世",
+                YaoRole::Ying => "应",
+                YaoRole::Normal => "",
+            }
+        )
+    }
+}
 
 /// 表示地支间的冲或合关系。
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ChongHe {
     Chong,
     He,
@@ -252,7 +375,7 @@ impl Display for ChongHe {
 }
 
 /// 表示五行间的生或克关系。
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ShengKe {
     Sheng,
     Ke,
@@ -271,63 +394,193 @@ impl Display for ShengKe {
     }
 }
 
-// 嵌入整个 static 目录（递归所有文件）
-#[derive(RustEmbed)]
-#[folder = "static/"]
-struct Asset;
+/// 冲合生克的来源：月建、日辰，或动爻变出之爻（回头）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Month,
+    Day,
+    HuiTou,
+}
 
-// 通用处理函数，根据请求路径返回嵌入的文件
-async fn embedded_file(path: web::Path<String>) -> impl Responder {
-    // 如果请求为空，则默认返回 index.html
-    let file_path = if path.is_empty() {
-        "index.html".to_string()
-    } else {
-        path.into_inner()
-    };
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Source::Month => "月",
+            Source::Day => "日",
+            Source::HuiTou => "回头",
+        })
+    }
+}
 
-    match Asset::get(&file_path) {
-        Some(content) => {
-            // 根据文件后缀可以进一步设置 Content-Type，此处省略，直接返回 body
-            HttpResponse::Ok().body(content.data.into_owned())
+/// 一个爻的旬空及所受的冲合生克，打印为 "旬空"、"月冲"、"回头克" 等。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relation {
+    XunKong,
+    ChongHe(Source, ChongHe),
+    ShengKe(Source, ShengKe),
+}
+
+impl Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Relation::XunKong => f.write_str("旬空"),
+            Relation::ChongHe(source, relation) => write!(f, "{}{}", source, relation),
+            Relation::ShengKe(source, relation) => write!(f, "{}{}", source, relation),
+        }
+    }
+}
+
+/// 一卦中一个爻的各项数据，不随语言变化。
+#[derive(Debug, Clone, Copy, Serialize, utoipa::ToSchema)]
+struct YaoResponse {
+    /// 爻位，初爻为1
+    position: usize,
+    liuqin: LiuQin,
+    /// 纳甲天干
+    tiangan: TianGan,
+    dizhi: DiZhi,
+    wuxing: WuXing,
+    /// 爻象，例如 "⚊ o"
+    symbol: &'static str,
+    yang: bool,
+    /// 是否为动爻，变卦中的爻总是 false
+    changing: bool,
+}
+
+impl YaoResponse {
+    fn new(gua: &Gua, i: usize) -> Self {
+        let yao = gua.yao_xiang[i];
+        YaoResponse {
+            position: i + 1,
+            liuqin: gua.liuqin[i],
+            tiangan: gua.tiangan[i],
+            dizhi: gua.dizhi[i],
+            wuxing: gua.wuxing[i],
+            symbol: yao.xiang(),
+            yang: yao.is_yang(),
+            changing: matches!(yao, Yao::YinChanging | Yao::YangChanging),
         }
-        None => HttpResponse::NotFound().body("File not found"),
+    }
+
+    /// 六亲地支五行，例如 "妻财子水"。
+    fn label(&self) -> String {
+        format!("{}{}{}", self.liuqin, self.dizhi, self.wuxing)
+    }
+}
+
+// 排盘文本中一个爻的写法，例如 "妻财子水⚊ o"，即 base_text、bian_text 的内容
+impl Display for YaoResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.label(), self.symbol)
     }
 }
 
 // 用于表示单行卦爻信息的结构体
-#[derive(Serialize)]
+#[derive(Serialize, utoipa::ToSchema)]
 struct GuaLineResponse {
     liushen: Option<String>,
+    /// 正卦此爻的文本，由 yao 格式化而来；卦名行为卦名
     base_text: String,
+    /// 正卦此爻的各项数据，卦名行为 None
+    yao: Option<YaoResponse>,
     role: YaoRole,
     zheng_relations_text: String,
+    /// 正卦此爻的关系，zheng_relations_text 由它格式化而来；统计断卦规则时使用
+    #[serde(skip)]
+    relations: Vec<Relation>,
     // 变卦部分可以简化，因为它没有角色和关系
     bian_text: String,
+    bian_yao: Option<YaoResponse>,
     bian_relations_text: String,
+    /// 动爻变出之爻的关系，含回头生克冲合
+    #[serde(skip)]
+    bian_relations: Vec<Relation>,
     is_changing: bool,
 }
 
-#[derive(Serialize)]
+/// 排盘结果（GuaResponse）的结构版本，字段或其含义改变时加一。
+/// 交换文件中据此判断两边的排盘结果能否逐项核对。
+/// 5: 年柱从立春当天起换，与月柱一致，不再按春节换年。
+const CHART_VERSION: u32 = 5;
+
+#[derive(Serialize, utoipa::ToSchema)]
 struct GuaResponse {
     gua_lines: Vec<GuaLineResponse>,
+    /// 正卦、变卦的卦序、卦符和上下卦
+    hexagram: HexagramResponse,
+    bian_hexagram: HexagramResponse,
     year_ganzhi: String,
     month_ganzhi: String,
     day_ganzhi: String,
     hour_ganzhi: String,
     xun_kong: String,
+    hour_xun_kong: String,
+    /// 按经度校正为真太阳时后才有
+    solar_time: Option<SolarTime>,
+    /// 给出求测人出生时间时附上八字
+    bazi: Option<BaZi>,
+    /// 文本字段所用的语言
+    locale: Locale,
 }
 
-#[derive(Deserialize)]
+impl GuaResponse {
+    /// 把排盘中的文本字段译成指定语言。排盘本身和存档始终用简体，只在输出前翻译。
+    fn localize(&mut self, locale: Locale) {
+        let text = |s: &mut String| *s = locale.text(s);
+        for line in &mut self.gua_lines {
+            if let Some(liushen) = &mut line.liushen {
+                text(liushen);
+            }
+            text(&mut line.base_text);
+            text(&mut line.zheng_relations_text);
+            text(&mut line.bian_text);
+            text(&mut line.bian_relations_text);
+        }
+        self.hexagram.localize(locale);
+        self.bian_hexagram.localize(locale);
+        for ganzhi in [
+            &mut self.year_ganzhi,
+            &mut self.month_ganzhi,
+            &mut self.day_ganzhi,
+            &mut self.hour_ganzhi,
+            &mut self.xun_kong,
+            &mut self.hour_xun_kong,
+        ] {
+            text(ganzhi);
+        }
+        if let Some(bazi) = &mut self.bazi {
+            bazi.localize(locale);
+        }
+        self.locale = locale;
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
 struct GuaRequest {
+    /// 六次阳面个数，例如 "311113"
     numbers: String,
+    /// 23点子时的日柱归属，缺省时使用配置
+    zi_shi: Option<ZiShi>,
+    /// 以农历给出的起卦时间，缺省时取当前时间
+    lunar: Option<LunarDateInput>,
+    /// 求测人出生时间，给出时附上八字命局
+    birth: Option<String>,
+    /// 文本字段的语言，缺省时使用配置
+    locale: Option<Locale>,
+    /// 六爻用神所在爻位（初爻为1），用于对照命局喜忌
+    yong_shen_line: Option<usize>,
+    /// 给出经度时按真太阳时排盘
+    #[serde(flatten)]
+    solar_time: Option<SolarTimeOptions>,
 }
 
 // 存储一个完整卦的所有信息
 #[derive(Debug)]
 struct Gua {
     yao_xiang: [Yao; 6],       // 爻象, e.g., ["⚊", "⚋ o", ...]
-    index_str: String,         // 卦的数字索引, e.g., "122111"
+    code: GuaCode,             // 卦的 6 位编码, e.g., 0b111001
     yao_roles: [YaoRole; 6],   // 每个爻都有一个角色，世、应或普通
+    tiangan: [TianGan; 6],     // 每爻纳甲的天干
     dizhi: [DiZhi; 6],         // 每爻的地支
     wuxing: [WuXing; 6],       // 每爻的五行
     liuqin: [LiuQin; 6],       // 每爻的六亲
@@ -336,13 +589,18 @@ struct Gua {
 impl Gua {
     // 创建一个新的、未填充的Gua实例
     fn new(yao_xiang: [Yao; 6]) -> Self {
-        let index_str = yao_xiang.iter().map(|y| y.index_char()).collect();
+        let code = yao_xiang
+            .iter()
+            .enumerate()
+            .filter(|(_, y)| y.is_yang())
+            .fold(0, |code, (i, _)| code | 1 << i);
         Gua {
             yao_xiang,
-            index_str,
+            code,
             // 初始化时，所有爻都是普通角色
             yao_roles: [YaoRole::Normal; 6],
             // 使用 Copy 特性可以直接创建数组，无需手动填充
+            tiangan: [TianGan::Jia; 6],
             dizhi: [DiZhi::Zi; 6],
             wuxing: [WuXing::Jin; 6],
             liuqin: [LiuQin::XiongDi; 6],
@@ -553,28 +811,92 @@ const JIAZI_CYCLE: [&str; 60] = [
 
 const XUN_KONG: [&str; 6] = ["戌亥", "申酉", "午未", "辰巳", "寅卯", "子丑"];
 
-// 获取干支信息，例如乙巳年 辛巳月 壬辰日 申时
-fn get_ganzhi_info() -> (String, String, String, String) {
-    let now = Local::now();
-    let current_solar = solar::from_ymdhms(
-        now.year() as i64,
-        now.month() as i64,
-        now.day() as i64,
-        now.hour() as i64,
-        now.minute() as i64,
-        now.second() as i64,
-    );
-    let current_lunar = current_solar.get_lunar();
+const TIAN_GAN: [char; 10] = ['甲', '乙', '丙', '丁', '戊', '己', '庚', '辛', '壬', '癸'];
+
+/// 23点（子时上半）的日柱归属。
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+enum ZiShi {
+    /// 分早晚子时：23点为当日晚子时，日柱不变，时干按次日五鼠遁
+    #[default]
+    Split,
+    /// 子初换日：23点起即算次日，日柱取次日
+    NextDay,
+}
+
+/// 五鼠遁：由日干推时干，例如甲己日起甲子时、乙庚日起丙子时。
+fn hour_ganzhi(day_gan: char, hour_zhi: DiZhi) -> Option<String> {
+    let gan_index = TIAN_GAN.iter().position(|&g| g == day_gan)?;
+    let hour_index = hour_zhi as usize;
+    Some(format!(
+        "{}{}",
+        TIAN_GAN[(gan_index % 5 * 2 + hour_index) % 10],
+        hour_zhi
+    ))
+}
+
+/// lunar_rust 能可靠计算干支的公历年份范围，超出时它可能 panic 或给出错误的结果。
+/// 调用 get_ganzhi_info 之前应先用它检查输入的时间。
+const SUPPORTED_YEARS: std::ops::RangeInclusive<i64> = 1..=9999;
+
+// 获取干支信息，例如乙巳年 辛巳月 壬辰日 庚申时
+fn get_ganzhi_info(now: NaiveDateTime, zi_shi: ZiShi) -> (String, String, String, String) {
+    let lunar_at = |t: NaiveDateTime| {
+        solar::from_ymdhms(
+            t.year() as i64,
+            t.month() as i64,
+            t.day() as i64,
+            t.hour() as i64,
+            t.minute() as i64,
+            t.second() as i64,
+        )
+        .get_lunar()
+    };
+    let current_lunar = lunar_at(now);
+    let day_ganzhi = current_lunar.get_day_in_gan_zhi();
+    let hour_zhi = current_lunar.get_time_zhi();
+
+    // 23点后的子时按次日的日干起时干；子初换日时日柱也取次日
+    let (day_ganzhi, next_day_ganzhi) = if now.hour() == 23 {
+        let next = lunar_at(now + chrono::Duration::hours(1)).get_day_in_gan_zhi();
+        match zi_shi {
+            ZiShi::Split => (day_ganzhi, next),
+            ZiShi::NextDay => (next.clone(), next),
+        }
+    } else {
+        (day_ganzhi.clone(), day_ganzhi)
+    };
+    let hour_ganzhi = next_day_ganzhi
+        .chars()
+        .next()
+        .zip(DiZhi::try_from(hour_zhi.as_str()).ok())
+        .and_then(|(gan, zhi)| hour_ganzhi(gan, zhi))
+        .unwrap_or(hour_zhi);
+
+    // 月柱从交节当天起换，其天干按立春换年推出；年柱也从立春当天起换，
+    // 否则春节到立春之间会出现上一年的年干配寅月的情况
     (
-        current_lunar.get_year_in_gan_zhi(),
+        current_lunar.get_year_in_gan_zhi_by_li_chun(),
         current_lunar.get_month_in_gan_zhi(),
-        current_lunar.get_day_in_gan_zhi(),
-        current_lunar.get_time_zhi(),
+        day_ganzhi,
+        hour_ganzhi,
     )
 }
 
 // 判断地支之间的冲合关系
-fn get_chong_he_relation(dizhi1: DiZhi, dizhi2: DiZhi) -> Option<ChongHe> {
+const fn get_chong_he_relation(dizhi1: DiZhi, dizhi2: DiZhi) -> Option<ChongHe> {
     use {ChongHe::*, DiZhi::*};
     match (dizhi1, dizhi2) {
         (Zi, Wu)
@@ -673,52 +995,19 @@ fn get_liush_shen_sequence(day_gan: char) -> [LiuShen; 6] {
     result
 }
 
-// 确定世应并填充 (仅用于正卦)
+// 确定世应并填充 (仅用于正卦)，世爻由卦在本宫中的位置决定，应爻与世爻相隔三爻
 fn determine_yao_roles(gua: &mut Gua) {
-    let nei = &gua.yao_xiang[0..3];
-    let wai = &gua.yao_xiang[3..6];
-
-    let (shi_idx, ying_idx) = if nei[2].index_char() == wai[2].index_char()
-        && nei[0].index_char() != wai[0].index_char()
-        && nei[1].index_char() != wai[1].index_char()
-    {
-        (1, 4)
-    } else if nei[2].index_char() != wai[2].index_char()
-        && nei[0].index_char() == wai[0].index_char()
-        && nei[1].index_char() == wai[1].index_char()
-    {
-        (4, 1)
-    } else if nei[0].index_char() == wai[0].index_char()
-        && nei[1].index_char() != wai[1].index_char()
-        && nei[2].index_char() != wai[2].index_char()
-    {
-        (3, 0)
-    } else if nei[0].index_char() != wai[0].index_char()
-        && nei[1].index_char() == wai[1].index_char()
-        && nei[2].index_char() == wai[2].index_char()
-    {
-        (0, 3)
-    } else if nei[1].index_char() == wai[1].index_char()
-        && nei[0].index_char() != wai[0].index_char()
-        && nei[2].index_char() != wai[2].index_char()
-    {
-        (3, 0)
-    } else if nei[1].index_char() != wai[1].index_char()
-        && nei[0].index_char() == wai[0].index_char()
-        && nei[2].index_char() == wai[2].index_char()
-    {
-        (2, 5)
-    } else if nei[0].index_char() == wai[0].index_char()
-        && nei[1].index_char() == wai[1].index_char()
-        && nei[2].index_char() == wai[2].index_char()
-    {
-        (5, 2)
-    } else {
-        (2, 5)
-    };
+    let info = gua_table::lookup(gua.code);
+    gua.yao_roles[info.shi()] = YaoRole::Shi;
+    gua.yao_roles[info.ying()] = YaoRole::Ying;
+}
 
-    gua.yao_roles[shi_idx] = YaoRole::Shi;
-    gua.yao_roles[ying_idx] = YaoRole::Ying;
+// 填充纳甲天干：内卦三爻取内卦的天干，外卦三爻取外卦的天干
+fn append_tiangan(gua: &mut Gua) {
+    for i in 0..6 {
+        let outer = i >= 3;
+        gua.tiangan[i] = Trigram::from_code(gua.code >> (i / 3 * 3)).na_gan(outer);
+    }
 }
 
 // 填充五行
@@ -728,18 +1017,6 @@ fn append_wuxing(gua: &mut Gua) {
     }
 }
 
-// 填充地支
-fn append_dizhi(gua: &mut Gua) {
-    let (nei_index, wai_index) = gua.index_str.split_at(3);
-    if let (Some(hun_tian_nei), Some(hun_tian_wai)) = (
-        SIXTYFOURGUA_DATA.iter().find(|h| h.index == nei_index),
-        SIXTYFOURGUA_DATA.iter().find(|h| h.index == wai_index),
-    ) {
-        gua.dizhi[0..3].copy_from_slice(&hun_tian_nei.nei_dizhi);
-        gua.dizhi[3..6].copy_from_slice(&hun_tian_wai.wai_dizhi);
-    }
-}
-
 // 填充六亲 (依赖五行和宫位五行)
 fn append_liuqin(gua: &mut Gua, palace_element: WuXing) {
     use LiuQin::*;
@@ -774,46 +1051,252 @@ fn append_liuqin(gua: &mut Gua, palace_element: WuXing) {
     }
 }
 
-// 找到卦宫对应的五行属性用于判断六亲
-fn find_palace_element(gua_index: &str) -> Option<WuXing> {
-    SIXTYFOURGUA_DATA.iter().find_map(|gua| {
-        gua.gua_index
-            .iter()
-            .any(|&idx| idx == gua_index)
-            .then_some(gua.palace_element)
-    })
-}
-
-// 查找对应的卦宫名称
-fn find_palace_name(gua_index: &str) -> Option<&'static str> {
-    SIXTYFOURGUA_DATA.iter().find_map(|gua| {
-        gua.gua_index
-            .iter()
-            .position(|&idx| idx == gua_index)
-            .map(|pos| gua.gua_name[pos])
-    })
-}
-
 // 处理卦
 fn process_gua(gua: &mut Gua, palace_element: WuXing) {
     // 正卦和变卦的六亲都是根据正卦的宫位五行来定的，所以 palace_element 需要传入
-    append_dizhi(gua);
+    let info = gua_table::lookup(gua.code);
+    gua.dizhi = info.najia;
+    append_tiangan(gua);
     append_wuxing(gua);
     append_liuqin(gua, palace_element);
-    gua.palace_name = find_palace_name(&gua.index_str).unwrap_or("未知卦");
+    gua.palace_name = info.name;
 }
 
-async fn generate_gua_xian(req: web::Json<GuaRequest>) -> impl Responder {
-    let numbers = &req.numbers;
-    // 获取干支信息
-    let (year_ganzhi, month_ganzhi, day_ganzhi, hour_ganzhi) = get_ganzhi_info();
+/// 排盘过程中可能出现的错误。
+#[derive(Debug)]
+enum PaipanError {
+    /// 输入不是6位0-3的数字
+    InvalidNumbers(String),
+    /// 从lunar_rust获取的日干支为空
+    MissingDayGan,
+    /// 真太阳时校正参数有误
+    SolarTime(SolarTimeError),
+    /// 用神爻位不在1-6之间
+    InvalidLine(usize),
+    /// 时间超出 SUPPORTED_YEARS
+    DateOutOfRange(NaiveDateTime),
+}
 
-    // 计算前端显示的旬空
-    let xun_kong_opt = calculate_xun_kong(&day_ganzhi);
-    let xun_kong = xun_kong_opt.unwrap_or("").to_string();
+impl PaipanError {
+    /// 是否由请求的输入引起，应返回 400；否则是排盘程序内部的错误。
+    fn is_input_error(&self) -> bool {
+        !matches!(self, PaipanError::MissingDayGan)
+    }
+}
 
-    // 获取旬空的地支拿给爻判断
-    let xun_kong_dizhi = xun_kong_opt.and_then(parse_xun_kong);
+impl Display for PaipanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaipanError::InvalidNumbers(numbers) => {
+                write!(f, "请输入6位数字（0-3），收到 \"{}\"", numbers)
+            }
+            PaipanError::MissingDayGan => write!(f, "无法获取日干，排盘中断"),
+            PaipanError::SolarTime(e) => write!(f, "{}", e),
+            PaipanError::InvalidLine(line) => {
+                write!(f, "用神爻位必须在1-6之间，收到 {}", line)
+            }
+            PaipanError::DateOutOfRange(at) => write!(
+                f,
+                "时间 {} 超出支持的范围（公元{}年至{}年）",
+                at,
+                SUPPORTED_YEARS.start(),
+                SUPPORTED_YEARS.end()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PaipanError {}
+
+/// 排盘结果的响应格式，由请求头 Accept 决定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChartFormat {
+    Json,
+    Text,
+    Markdown,
+}
+
+impl ChartFormat {
+    // 按 Accept 中的优先级取第一个支持的格式，没有或无法识别时返回 JSON
+    fn negotiate(accept: Option<&Accept>) -> ChartFormat {
+        accept
+            .into_iter()
+            .flat_map(|accept| accept.ranked())
+            .find_map(|mime| match mime.essence_str() {
+                "application/json" | "*/*" | "application/*" => Some(ChartFormat::Json),
+                "text/plain" => Some(ChartFormat::Text),
+                "text/markdown" | "text/x-markdown" => Some(ChartFormat::Markdown),
+                _ => None,
+            })
+            .unwrap_or(ChartFormat::Json)
+    }
+
+    fn respond(self, gua: &GuaResponse) -> HttpResponse {
+        let mut response = HttpResponse::Ok();
+        response.insert_header((header::VARY, "Accept"));
+        match self {
+            ChartFormat::Json => response.json(gua),
+            ChartFormat::Text => response
+                .content_type("text/plain; charset=utf-8")
+                .body(render::text(gua)),
+            ChartFormat::Markdown => response
+                .content_type("text/markdown; charset=utf-8")
+                .body(render::markdown(gua)),
+        }
+    }
+}
+
+/// 按六次阳面个数起卦排盘，起卦时间缺省取当前时间。
+///
+/// 按 Accept 返回 JSON、纯文本或 Markdown。旧路由 /generate_gua_xian 仍可用，但已弃用。
+#[utoipa::path(
+    post,
+    path = "/api/v1/cast",
+    tag = "cast",
+    request_body = GuaRequest,
+    responses(
+        (status = 200, description = "排盘结果", content(
+            (GuaResponse = "application/json"),
+            (String = "text/plain"),
+            (String = "text/markdown"),
+        )),
+        (status = 400, description = "卦数、时间或经度无效", body = crate::api::ApiError),
+    )
+)]
+async fn generate_gua_xian(
+    config: web::Data<Config>,
+    accept: Option<web::Header<Accept>>,
+    req: web::Json<GuaRequest>,
+) -> impl Responder {
+    // 按配置的时区取当前时间起卦，给出农历日期时按农历换算
+    let at = match req.lunar.as_ref().map(LunarDateInput::to_solar).transpose() {
+        Ok(at) => at.unwrap_or_else(|| config.now()),
+        Err(e) => return HttpResponse::BadRequest().json(e.to_string()),
+    };
+    let birth = match req.birth.as_deref().map(cli::parse_datetime).transpose() {
+        Ok(birth) => birth,
+        Err(e) => return HttpResponse::BadRequest().json(e),
+    };
+    if birth.is_none() && req.yong_shen_line.is_some() {
+        return HttpResponse::BadRequest().json("对照用神需要同时给出出生时间 birth");
+    }
+    let zi_shi = req.zi_shi.unwrap_or(config.zi_shi);
+    let result = paipan_solar(&config, &req.numbers, at, None, zi_shi, req.solar_time).and_then(
+        |mut response| {
+            if let Some(birth) = birth {
+                response.bazi = Some(bazi::for_reading(
+                    &req.numbers,
+                    birth,
+                    zi_shi,
+                    req.yong_shen_line,
+                )?);
+            }
+            response.localize(req.locale.unwrap_or(config.locale));
+            Ok(response)
+        },
+    );
+    match result {
+        // 按 Accept 返回 JSON、纯文本或 Markdown，便于贴到论坛、笔记或提示词中
+        Ok(response) => ChartFormat::negotiate(accept.as_deref()).respond(&response),
+        Err(e) if e.is_input_error() => HttpResponse::BadRequest().json(e.to_string()),
+        Err(e) => {
+            // 在服务器端打印错误日志，方便排查问题
+            log::error!("严重错误: {}", e);
+            // 向前端返回一个明确的内部服务器错误
+            HttpResponse::InternalServerError().json(e.to_string())
+        }
+    }
+}
+
+/// 排盘图片的查询参数，便于直接用链接嵌入文档。
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RenderQuery {
+    /// 六次阳面个数，例如 "311113"
+    numbers: String,
+    /// 起卦时间，缺省时取当前时间
+    at: Option<String>,
+    zi_shi: Option<ZiShi>,
+    /// 图中文字的语言，缺省时使用配置
+    locale: Option<Locale>,
+    /// PNG 的缩放倍数
+    scale: Option<f32>,
+}
+
+// 按查询参数排盘并画成 SVG，出错时直接给出错误响应
+fn render_query(config: &Config, query: &RenderQuery) -> Result<String, HttpResponse> {
+    let at = match query.at.as_deref().map(cli::parse_datetime).transpose() {
+        Ok(at) => at.unwrap_or_else(|| config.now()),
+        Err(e) => return Err(HttpResponse::BadRequest().json(e)),
+    };
+    match paipan(&query.numbers, at, query.zi_shi.unwrap_or(config.zi_shi)) {
+        Ok(mut gua) => {
+            gua.localize(query.locale.unwrap_or(config.locale));
+            Ok(render::svg(&gua))
+        }
+        Err(e) if e.is_input_error() => Err(HttpResponse::BadRequest().json(e.to_string())),
+        Err(e) => {
+            log::error!("严重错误: {}", e);
+            Err(HttpResponse::InternalServerError().json(e.to_string()))
+        }
+    }
+}
+
+/// 在服务端把排盘画成 SVG。
+#[utoipa::path(
+    get,
+    path = "/api/v1/chart.svg",
+    tag = "chart",
+    params(RenderQuery),
+    responses(
+        (status = 200, description = "排盘图片", content_type = "image/svg+xml", body = String),
+        (status = 400, description = "卦数或时间无效", body = crate::api::ApiError),
+    )
+)]
+async fn render_svg(config: web::Data<Config>, query: web::Query<RenderQuery>) -> impl Responder {
+    match render_query(&config, &query) {
+        Ok(svg) => HttpResponse::Ok()
+            .content_type("image/svg+xml; charset=utf-8")
+            .body(svg),
+        Err(response) => response,
+    }
+}
+
+/// 把排盘渲染成 PNG，便于发到不支持 SVG 的聊天软件。
+#[utoipa::path(
+    get,
+    path = "/api/v1/chart.png",
+    tag = "chart",
+    params(RenderQuery),
+    responses(
+        (status = 200, description = "排盘图片", content_type = "image/png", body = Vec<u8>),
+        (status = 400, description = "卦数、时间或缩放倍数无效", body = crate::api::ApiError),
+    )
+)]
+async fn render_png(config: web::Data<Config>, query: web::Query<RenderQuery>) -> impl Responder {
+    let svg = match render_query(&config, &query) {
+        Ok(svg) => svg,
+        Err(response) => return response,
+    };
+    let scale = query.scale.unwrap_or(raster::DEFAULT_SCALE);
+    match web::block(move || raster::png(&svg, scale)).await {
+        Ok(Ok(png)) => HttpResponse::Ok().content_type("image/png").body(png),
+        Ok(Err(e @ raster::RasterError::InvalidScale(_))) => {
+            HttpResponse::BadRequest().json(e.to_string())
+        }
+        Ok(Err(e)) => {
+            log::error!("{}", e);
+            HttpResponse::InternalServerError().json(e.to_string())
+        }
+        Err(e) => HttpResponse::InternalServerError().json(e.to_string()),
+    }
+}
+
+/// 根据六次阳面个数生成填充好的正卦和变卦（与起卦时间无关的部分）。
+fn build_guas(numbers: &str) -> Result<(Gua, Gua), PaipanError> {
+    if numbers.chars().count() != 6 || !numbers.chars().all(|c| ('0'..='3').contains(&c)) {
+        return Err(PaipanError::InvalidNumbers(numbers.to_string()));
+    }
 
     // 1. === 初始化正卦 (Zheng Gua) ===
     let mut zheng_yao_xiang = [Yao::YinStatic; 6];
@@ -823,14 +1306,11 @@ async fn generate_gua_xian(req: web::Json<GuaRequest>) -> impl Responder {
     let mut zheng_gua = Gua::new(zheng_yao_xiang);
 
     // 2. === 初始化变卦 (Bian Gua) ===
-    let mut bian_yao_xiang = [Yao::YinStatic; 6];
-    for i in 0..6 {
-        bian_yao_xiang[i] = zheng_gua.yao_xiang[i].to_bian_yao();
-    }
+    let bian_yao_xiang = zheng_yao_xiang.map(|yao| yao.to_bian_yao());
     let mut bian_gua = Gua::new(bian_yao_xiang);
 
     // 3. === 数据处理和填充 ===
-    let palace_element = find_palace_element(&zheng_gua.index_str).unwrap_or(WuXing::Jin); // 默认为金
+    let palace_element = gua_table::lookup(zheng_gua.code).palace_element();
 
     // 处理正卦
     process_gua(&mut zheng_gua, palace_element);
@@ -839,16 +1319,69 @@ async fn generate_gua_xian(req: web::Json<GuaRequest>) -> impl Responder {
     // 处理变卦 (使用正卦的宫位五行)
     process_gua(&mut bian_gua, palace_element);
 
-    // 获取日干，如果失败则直接返回错误
-    let day_gan_char = match day_ganzhi.chars().next() {
-        Some(gan) => gan,
-        None => {
-            // 在服务器端打印错误日志，方便排查问题
-            eprintln!("严重错误: 从lunar_rust获取的日干支为空字符串!");
-            // 向前端返回一个明确的内部服务器错误
-            return HttpResponse::InternalServerError().json("无法获取日干，排盘中断");
+    Ok((zheng_gua, bian_gua))
+}
+
+// 一个爻的旬空及月、日对它的冲合生克
+fn relations(
+    yao: &YaoResponse,
+    xun_kong: Option<(DiZhi, DiZhi)>,
+    month_dizhi: Option<DiZhi>,
+    day_dizhi: Option<DiZhi>,
+) -> Vec<Relation> {
+    let mut relations = Vec::new();
+    // 优先判断旬空
+    if let Some((dz1, dz2)) = xun_kong {
+        if yao.dizhi == dz1 || yao.dizhi == dz2 {
+            relations.push(Relation::XunKong);
         }
-    };
+    }
+    // 先月后日，各自先冲合后生克
+    for (source, dizhi) in [(Source::Month, month_dizhi), (Source::Day, day_dizhi)] {
+        let Some(dizhi) = dizhi else { continue };
+        if let Some(relation) = get_chong_he_relation(yao.dizhi, dizhi) {
+            relations.push(Relation::ChongHe(source, relation));
+        }
+        if let Some(relation) = get_sheng_ke_relation(dizhi.wuxing(), yao.wuxing) {
+            relations.push(Relation::ShengKe(source, relation));
+        }
+    }
+    relations
+}
+
+// 排盘文本中的写法，例如 " 旬空 月冲 日生"；回头冲合紧跟在前一项之后，不加空格
+fn relations_text(relations: &[Relation]) -> String {
+    relations
+        .iter()
+        .map(|relation| match relation {
+            Relation::ChongHe(Source::HuiTou, _) => relation.to_string(),
+            _ => format!(" {}", relation),
+        })
+        .collect()
+}
+
+/// 根据六次阳面个数和起卦时间排盘，网页接口与命令行共用此函数。
+fn paipan(numbers: &str, at: NaiveDateTime, zi_shi: ZiShi) -> Result<GuaResponse, PaipanError> {
+    let (zheng_gua, bian_gua) = build_guas(numbers)?;
+    if !SUPPORTED_YEARS.contains(&(at.year() as i64)) {
+        return Err(PaipanError::DateOutOfRange(at));
+    }
+    // 获取干支信息
+    let (year_ganzhi, month_ganzhi, day_ganzhi, hour_ganzhi) = get_ganzhi_info(at, zi_shi);
+    let hour_xun_kong = calculate_xun_kong(&hour_ganzhi).unwrap_or("").to_string();
+
+    // 计算前端显示的旬空
+    let xun_kong_opt = calculate_xun_kong(&day_ganzhi);
+    let xun_kong = xun_kong_opt.unwrap_or("").to_string();
+
+    // 获取旬空的地支拿给爻判断
+    let xun_kong_dizhi = xun_kong_opt.and_then(parse_xun_kong);
+
+    // 获取日干，如果失败则直接返回错误
+    let day_gan_char = day_ganzhi
+        .chars()
+        .next()
+        .ok_or(PaipanError::MissingDayGan)?;
 
     // 成功获取日干后获取六神
     let liushen_sequence = get_liush_shen_sequence(day_gan_char);
@@ -870,140 +1403,209 @@ async fn generate_gua_xian(req: web::Json<GuaRequest>) -> impl Responder {
     // 4. === 格式化最终输出 ===
     let mut gua_lines = Vec::with_capacity(7);
 
-    for i in 0..6 {
-        // 构建正卦的基础文本（不含世应）
-        let base_text = format!(
-            "{}{}{}{}",
-            zheng_gua.liuqin[i],
-            zheng_gua.dizhi[i],
-            zheng_gua.wuxing[i],
-            zheng_gua.yao_xiang[i].xiang()
-        );
-        // 构建正卦关系文本
-        let mut zheng_relations_text = String::new();
-        // 优先判断旬空
-        if let Some((dz1, dz2)) = xun_kong_dizhi {
-            if zheng_gua.dizhi[i] == dz1 || zheng_gua.dizhi[i] == dz2 {
-                zheng_relations_text.push_str(" 旬空");
-            }
-        }
-        // 判断月的影响
-        if let Some(md) = month_dizhi {
-            // 判断并追加冲合关系 (月对爻)
-            if let Some(relation) = get_chong_he_relation(zheng_gua.dizhi[i], md) {
-                zheng_relations_text.push_str(&format!(" 月{}", relation));
-            }
-            // 判断并追加生克关系 (月对爻)
-            if let Some(relation) = get_sheng_ke_relation(md.wuxing(), zheng_gua.wuxing[i]) {
-                zheng_relations_text.push_str(&format!(" 月{}", relation));
-            }
-        }
-        // 判断日的影响
-        if let Some(dd) = day_dizhi {
-            // 判断并追加冲合关系 (日对爻)
-            if let Some(relation) = get_chong_he_relation(zheng_gua.dizhi[i], dd) {
-                zheng_relations_text.push_str(&format!(" 日{}", relation));
-            }
-            // 判断并追加生克关系 (日对爻)
-            if let Some(relation) = get_sheng_ke_relation(dd.wuxing(), zheng_gua.wuxing[i]) {
-                zheng_relations_text.push_str(&format!(" 日{}", relation));
-            }
-        }
+    for (i, liushen) in liushen_sequence.iter().enumerate() {
+        let yao = YaoResponse::new(&zheng_gua, i);
+        let bian_yao = YaoResponse::new(&bian_gua, i);
+        // 正卦此爻的关系
+        let zheng_relations = relations(&yao, xun_kong_dizhi, month_dizhi, day_dizhi);
 
-        // 构建变卦关系文本
-        let mut bian_relations_text = String::new();
-        // 只有当正卦的爻是动爻时，才计算回头关系
-        let is_changing = matches!(zheng_gua.yao_xiang[i], Yao::YinChanging | Yao::YangChanging);
-        if is_changing {
-            // 判断旬空
-            if let Some((dz1, dz2)) = xun_kong_dizhi {
-                if bian_gua.dizhi[i] == dz1 || bian_gua.dizhi[i] == dz2 {
-                    bian_relations_text.push_str(" 旬空");
-                }
-            }
-            // 判断月的影响
-            if let Some(md) = month_dizhi {
-                // 判断并追加冲合关系 (月对爻)
-                if let Some(relation) = get_chong_he_relation(bian_gua.dizhi[i], md) {
-                    bian_relations_text.push_str(&format!(" 月{}", relation));
-                }
-                // 判断并追加生克关系 (月对爻)
-                if let Some(relation) = get_sheng_ke_relation(md.wuxing(), bian_gua.wuxing[i]) {
-                    bian_relations_text.push_str(&format!(" 月{}", relation));
-                }
-            }
-            // 判断日的影响
-            if let Some(dd) = day_dizhi {
-                // 判断并追加冲合关系 (日对爻)
-                if let Some(relation) = get_chong_he_relation(bian_gua.dizhi[i], dd) {
-                    bian_relations_text.push_str(&format!(" 日{}", relation));
-                }
-                // 判断并追加生克关系 (日对爻)
-                if let Some(relation) = get_sheng_ke_relation(dd.wuxing(), bian_gua.wuxing[i]) {
-                    bian_relations_text.push_str(&format!(" 日{}", relation));
-                }
-            }
+        // 变卦此爻的关系，只有当正卦的爻是动爻时才计算，包括回头关系
+        let mut bian_relations = Vec::new();
+        if yao.changing {
+            bian_relations = relations(&bian_yao, xun_kong_dizhi, month_dizhi, day_dizhi);
             // 变爻回头生克 (变爻的五行 -> 正爻的五行)
-            if let Some(relation) = get_sheng_ke_relation(bian_gua.wuxing[i], zheng_gua.wuxing[i]) {
-                bian_relations_text.push_str(&format!(" 回头{}", relation));
+            if let Some(relation) = get_sheng_ke_relation(bian_yao.wuxing, yao.wuxing) {
+                bian_relations.push(Relation::ShengKe(Source::HuiTou, relation));
             }
             // 变爻回头冲合 (变爻的地支 vs 正爻的地支)
-            if let Some(relation) = get_chong_he_relation(bian_gua.dizhi[i], zheng_gua.dizhi[i]) {
-                bian_relations_text.push_str(&format!("回头{}", relation));
+            if let Some(relation) = get_chong_he_relation(bian_yao.dizhi, yao.dizhi) {
+                bian_relations.push(Relation::ChongHe(Source::HuiTou, relation));
             }
         }
 
-        // 构建变卦的基础文本
-        let bian_text = format!(
-            "{}{}{}{}",
-            bian_gua.liuqin[i],
-            bian_gua.dizhi[i],
-            bian_gua.wuxing[i],
-            bian_gua.yao_xiang[i].xiang()
-        );
-
         gua_lines.push(GuaLineResponse {
-            liushen: Some(liushen_sequence[i].to_string()),
-            base_text,
+            liushen: Some(liushen.to_string()),
+            base_text: yao.to_string(),
+            yao: Some(yao),
             role: zheng_gua.yao_roles[i],
-            zheng_relations_text,
-            bian_text,
-            bian_relations_text,
-            is_changing,
+            zheng_relations_text: relations_text(&zheng_relations),
+            relations: zheng_relations,
+            bian_text: bian_yao.to_string(),
+            bian_yao: Some(bian_yao),
+            bian_relations_text: relations_text(&bian_relations),
+            bian_relations,
+            is_changing: yao.changing,
         });
     }
     // 单独处理卦名 离为火䷝(六冲)震为雷䷲(六) 等
     let name_line = GuaLineResponse {
         liushen: None,
         base_text: zheng_gua.palace_name.to_string(),
+        yao: None,
         role: YaoRole::Normal,
         zheng_relations_text: String::new(),
+        relations: Vec::new(),
         bian_text: bian_gua.palace_name.to_string(),
+        bian_yao: None,
         bian_relations_text: String::new(),
+        bian_relations: Vec::new(),
         is_changing: false,
     };
     gua_lines.push(name_line);
 
-    HttpResponse::Ok().json(GuaResponse {
+    Ok(GuaResponse {
         gua_lines,
+        hexagram: Hexagram::from_code(zheng_gua.code).into(),
+        bian_hexagram: Hexagram::from_code(bian_gua.code).into(),
         year_ganzhi,
         month_ganzhi,
         day_ganzhi,
         hour_ganzhi,
         xun_kong,
+        hour_xun_kong,
+        solar_time: None,
+        bazi: None,
+        locale: Locale::ZhHans,
     })
 }
 
+/// 排盘，给出经度时先把钟表时间校正为真太阳时，并在结果中附上校正信息。
+fn paipan_solar(
+    config: &Config,
+    numbers: &str,
+    at: NaiveDateTime,
+    utc_offset: Option<i32>,
+    zi_shi: ZiShi,
+    options: Option<SolarTimeOptions>,
+) -> Result<GuaResponse, PaipanError> {
+    let Some(options) = options else {
+        return paipan(numbers, at, zi_shi);
+    };
+    // 输入带时区时按该时区定标准经线，否则按配置的时区
+    let utc_offset = utc_offset.unwrap_or_else(|| config.utc_offset_seconds(at));
+    let solar_time =
+        solar_time::correct(at, utc_offset, options).map_err(PaipanError::SolarTime)?;
+    let mut response = paipan(numbers, solar_time.solar_time, zi_shi)?;
+    response.solar_time = Some(solar_time);
+    Ok(response)
+}
+
+/// 命令行入口
+#[derive(Parser)]
+#[command(name = "sixsixsix", version, about = "六爻在线排盘")]
+struct Cli {
+    #[command(flatten)]
+    config: ConfigArgs,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 启动 HTTP 排盘服务（不带子命令时的默认行为）
+    Serve,
+    /// 在终端排盘，输出正卦/变卦、六神、世应和日月关系
+    Paipan(cli::PaipanArgs),
+    /// 批量排盘：从 JSON Lines 或 CSV 逐行读入记录，以 JSON Lines 逐行输出排盘结果
+    Batch(cli::BatchArgs),
+    /// 枚举全部 4096 种起卦输入，统计本宫、冲合转换、伏神、反吟伏吟及两种起卦法的概率
+    Enumerate(cli::EnumerateArgs),
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| {
-        App::new()
-            .route("/generate_gua_xian", web::post().to(generate_gua_xian))
-            // 捕获static所有文件路径请求，注意这里的正则表达式
-            .route("/{filename:.*}", web::get().to(embedded_file))
-            .service(Files::new("/", "./static").index_file("index.html"))
-    })
-    .bind("[::]:8080")?
-    .run()
-    .await
+    let cli = Cli::parse();
+    let config = match Config::load(cli.config) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("配置错误: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    match cli.command {
+        Some(Command::Paipan(args)) => match cli::run_paipan(&args, &config) {
+            Ok(output) => std::io::stdout().write_all(&output),
+            Err(e) => {
+                eprintln!("排盘失败: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Enumerate(args)) => match cli::run_enumerate(&args) {
+            Ok(output) => std::io::stdout().write_all(&output),
+            Err(e) => {
+                eprintln!("枚举失败: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Some(Command::Batch(args)) => {
+            if let Err(e) = cli::run_batch(&args, &config) {
+                eprintln!("批量排盘失败: {}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some(Command::Serve) | None => serve(config).await,
+    }
+}
+
+/// 注册排盘接口（不含静态页面），测试中也用它构造服务。
+fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.configure(api::configure)
+        // 旧的排盘接口，保留为 /api/v1/cast 的别名，响应头中注明已弃用及替代地址
+        .service(
+            web::resource("/generate_gua_xian")
+                .wrap(
+                    DefaultHeaders::new()
+                        .add(("Deprecation", "true"))
+                        .add((header::LINK, "</api/v1/cast>; rel=\"successor-version\"")),
+                )
+                .route(web::post().to(generate_gua_xian)),
+        )
+        .route("/yingqi", web::post().to(yingqi::yingqi))
+        .route("/calendar", web::get().to(calendar::calendar))
+        .route("/render.svg", web::get().to(render_svg))
+        .route("/render.png", web::get().to(render_png))
+        .route("/hexagram/{key}", web::get().to(hexagram::hexagram))
+        .route("/texts", web::get().to(zhouyi::texts))
+        .route("/locales/{locale}", web::get().to(locale::locale_table))
+        .configure(readings::configure)
+        .configure(interchange::configure);
+}
+
+/// 启动 HTTP 服务
+async fn serve(config: Config) -> std::io::Result<()> {
+    env_logger::Builder::new()
+        .parse_filters(&config.log_level)
+        .init();
+
+    let bind_addr = (config.bind, config.port);
+    let workers = config.workers;
+    let serve_ui = config.serve_ui;
+    let storage = match Storage::open(&config.database) {
+        Ok(storage) => web::Data::new(storage),
+        Err(e) => {
+            eprintln!("无法打开数据库 {}: {}", config.database.display(), e);
+            std::process::exit(2);
+        }
+    };
+    let config = web::Data::new(config);
+    log::info!("listening on {}", std::net::SocketAddr::from(bind_addr));
+
+    let mut server = HttpServer::new(move || {
+        let app = App::new()
+            .app_data(config.clone())
+            .app_data(storage.clone())
+            .wrap(Logger::default())
+            .configure(routes);
+        if serve_ui {
+            app.configure(assets::configure)
+        } else {
+            app
+        }
+    });
+    if let Some(workers) = workers {
+        server = server.workers(workers);
+    }
+    server.bind(bind_addr)?.run().await
 }
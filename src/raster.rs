@@ -0,0 +1,180 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use resvg::{tiny_skia, usvg};
+use rust_embed::RustEmbed;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// 内置的中文字体子集，由 fonts/subset.sh 从完整字体中截取排盘用到的字。
+#[derive(RustEmbed)]
+#[folder = "fonts/"]
+struct Fonts;
+
+/// 允许的缩放倍数，默认 2 倍以便在手机上清晰显示。
+pub const MIN_SCALE: f32 = 0.5;
+pub const MAX_SCALE: f32 = 4.0;
+pub const DEFAULT_SCALE: f32 = 2.0;
+
+/// 没有内置字体时查找字体文件的系统目录。
+const SYSTEM_FONT_DIRS: [&str; 5] = [
+    "/usr/share/fonts",
+    "/usr/local/share/fonts",
+    "/Library/Fonts",
+    "/System/Library/Fonts",
+    "C:\\Windows\\Fonts",
+];
+
+/// 栅格化可能出现的错误。
+#[derive(Debug)]
+pub enum RasterError {
+    InvalidScale(f32),
+    /// 编译时 fonts/ 目录中没有字体文件，系统字体目录中也没有
+    MissingFont,
+    Svg(usvg::Error),
+    Encode(String),
+}
+
+impl std::fmt::Display for RasterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RasterError::InvalidScale(scale) => write!(
+                f,
+                "缩放倍数必须在{}到{}之间，收到 {}",
+                MIN_SCALE, MAX_SCALE, scale
+            ),
+            RasterError::MissingFont => write!(
+                f,
+                "没有内置字体，系统中也找不到字体，无法生成图片，请按 fonts/README.md 生成字体子集后重新编译"
+            ),
+            RasterError::Svg(e) => write!(f, "解析SVG失败: {}", e),
+            RasterError::Encode(e) => write!(f, "生成PNG失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RasterError {}
+
+/// 以 data URI 写成 @font-face 的内置字体，供 SVG 自带字体，不依赖看图机器上的字体。
+pub struct FontFace {
+    /// 字体族名，取字体文件名，例如 "NotoSerifSC-Regular-subset"
+    pub family: String,
+    pub css: String,
+}
+
+/// 内置字体的 @font-face 规则；浏览器不支持字体集合，只取 .ttf 和 .otf。没有内置字体时为空。
+pub fn font_faces() -> &'static [FontFace] {
+    static FACES: OnceLock<Vec<FontFace>> = OnceLock::new();
+    FACES.get_or_init(|| {
+        Fonts::iter()
+            .filter_map(|name| {
+                let (family, ext) = name.rsplit_once('.')?;
+                let format = match ext {
+                    "ttf" => "truetype",
+                    "otf" => "opentype",
+                    _ => return None,
+                };
+                let file = Fonts::get(&name)?;
+                Some(FontFace {
+                    family: family.to_string(),
+                    css: format!(
+                        "@font-face {{ font-family: '{}'; src: url(data:font/{};base64,{}) format('{}'); }}",
+                        family,
+                        ext,
+                        STANDARD.encode(&file.data),
+                        format
+                    ),
+                })
+            })
+            .collect()
+    })
+}
+
+fn is_font_file(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    [".ttf", ".otf", ".ttc"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+}
+
+// 递归加载目录中的字体文件，不跟随符号链接，避免目录成环
+fn load_font_dir(db: &mut usvg::fontdb::Database, dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => load_font_dir(db, &path),
+            Ok(kind) if kind.is_file() && is_font_file(&entry.file_name().to_string_lossy()) => {
+                if let Ok(data) = std::fs::read(&path) {
+                    db.load_font_data(data);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// 优先只用内置字体，保证在任何机器上渲染结果一致；没有内置字体时退回系统字体，
+// 缺字时 usvg 会在已加载的字体中找含有该字的
+fn font_options() -> Option<&'static usvg::Options<'static>> {
+    static OPTIONS: OnceLock<Option<usvg::Options<'static>>> = OnceLock::new();
+    OPTIONS
+        .get_or_init(|| {
+            let mut db = usvg::fontdb::Database::new();
+            for name in Fonts::iter().filter(|name| is_font_file(name)) {
+                if let Some(file) = Fonts::get(&name) {
+                    db.load_font_data(file.data.into_owned());
+                }
+            }
+            if db.is_empty() {
+                for dir in SYSTEM_FONT_DIRS {
+                    load_font_dir(&mut db, Path::new(dir));
+                }
+                if !db.is_empty() {
+                    log::warn!("没有内置字体，用系统字体生成图片，不同机器上的结果可能不同");
+                }
+            }
+            // 默认字体取第一个常规字重的正体，没有时取第一个
+            let face = db
+                .faces()
+                .find(|face| {
+                    face.weight == usvg::fontdb::Weight::NORMAL
+                        && face.style == usvg::fontdb::Style::Normal
+                })
+                .or_else(|| db.faces().next())?;
+            let family = face.families.first()?.0.clone();
+            // SVG 中写的字体族在这里未必找得到，统一落到默认字体上
+            db.set_serif_family(&family);
+            db.set_sans_serif_family(&family);
+            Some(usvg::Options {
+                font_family: family,
+                fontdb: Arc::new(db),
+                ..Default::default()
+            })
+        })
+        .as_ref()
+}
+
+/// 把 SVG 渲染为 PNG，scale 为相对 SVG 尺寸的缩放倍数。
+pub fn png(svg: &str, scale: f32) -> Result<Vec<u8>, RasterError> {
+    if !(MIN_SCALE..=MAX_SCALE).contains(&scale) {
+        return Err(RasterError::InvalidScale(scale));
+    }
+    let options = font_options().ok_or(RasterError::MissingFont)?;
+    let tree = usvg::Tree::from_str(svg, options).map_err(RasterError::Svg)?;
+    let size = tree
+        .size()
+        .to_int_size()
+        .scale_by(scale)
+        .ok_or(RasterError::InvalidScale(scale))?;
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| RasterError::Encode("图片尺寸无效".to_string()))?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .encode_png()
+        .map_err(|e| RasterError::Encode(e.to_string()))
+}
@@ -0,0 +1,398 @@
+use crate::{
+    api::ApiError,
+    cli::parse_datetime_with_offset,
+    config::Config,
+    paipan_solar,
+    solar_time::SolarTimeOptions,
+    stats,
+    storage::{
+        Location, NewReading, Outcome, Reading, ReadingFilter, ReadingUpdate, Storage,
+        StorageError, YingQiPrediction,
+    },
+    DiZhi, GuaResponse, LunarDateInput, PaipanError, ZiShi, CHART_VERSION,
+};
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+
+/// 新建占卜记录的请求体，at 缺省时取当前时间。
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateReading {
+    numbers: String,
+    at: Option<String>,
+    /// 以农历给出起卦时间，与 at 二选一
+    lunar: Option<LunarDateInput>,
+    #[serde(default)]
+    question: String,
+    #[serde(default)]
+    category: String,
+    /// 起卦地点
+    location: Option<Location>,
+    /// 按起卦地点的经度校正为真太阳时（含均时差），需要给出 location.longitude
+    #[serde(default)]
+    true_solar_time: bool,
+    /// 23 点子时的日柱归属，缺省时按配置；与记录一起保存
+    zi_shi: Option<ZiShi>,
+    interpretation: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+// 记录不存在返回 404，其余存储错误返回 500
+impl ResponseError for StorageError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            StorageError::NotFound(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if self.status_code().is_server_error() {
+            log::error!("{}", self);
+        }
+        HttpResponse::build(self.status_code()).json(self.to_string())
+    }
+}
+
+/// 注册占卜记录相关的路由。
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/readings")
+            .route(web::get().to(list_readings))
+            .route(web::post().to(create_reading)),
+    )
+    // 需要注册在 /readings/{id} 之前，否则 "report" 会被当成 id
+    .service(web::resource("/readings/report").route(web::get().to(outcome_report)))
+    .service(
+        web::resource("/readings/{id}")
+            .route(web::get().to(get_reading))
+            .route(web::patch().to(update_reading))
+            .route(web::delete().to(delete_reading)),
+    )
+    .service(web::resource("/readings/{id}/chart").route(web::get().to(render_reading)))
+    .service(
+        web::resource("/readings/{id}/outcome")
+            .route(web::put().to(set_outcome))
+            .route(web::delete().to(clear_outcome)),
+    )
+    .service(
+        web::resource("/readings/{id}/yingqi")
+            .route(web::put().to(set_yingqi))
+            .route(web::delete().to(clear_yingqi)),
+    );
+}
+
+/// 排盘并保存为一条新记录。
+#[utoipa::path(
+    post,
+    path = "/api/v1/readings",
+    tag = "readings",
+    request_body = CreateReading,
+    responses(
+        (status = 201, description = "新建的记录", body = Reading),
+        (status = 400, description = "卦数或时间无效", body = ApiError),
+    )
+)]
+pub async fn create_reading(
+    config: web::Data<Config>,
+    storage: web::Data<Storage>,
+    req: web::Json<CreateReading>,
+) -> actix_web::Result<HttpResponse> {
+    let req = req.into_inner();
+    let (cast_at, utc_offset) = match (&req.at, &req.lunar) {
+        (Some(_), Some(_)) => {
+            return Ok(HttpResponse::BadRequest().json("at 和 lunar 只能给出一个"))
+        }
+        (Some(at), None) => match parse_datetime_with_offset(at) {
+            Ok(at) => at,
+            Err(e) => return Ok(HttpResponse::BadRequest().json(e)),
+        },
+        (None, Some(lunar)) => match lunar.to_solar() {
+            Ok(at) => (at, None),
+            Err(e) => return Ok(HttpResponse::BadRequest().json(e.to_string())),
+        },
+        (None, None) => (config.now(), None),
+    };
+    // 按真太阳时排盘时保存所用的 UTC 偏移，重新排盘时不再依赖配置的时区
+    let solar_time_utc_offset = if req.true_solar_time {
+        if req.location.as_ref().and_then(|l| l.longitude).is_none() {
+            return Ok(HttpResponse::BadRequest().json("按真太阳时排盘需要给出 location.longitude"));
+        }
+        Some(utc_offset.unwrap_or_else(|| config.utc_offset_seconds(cast_at)))
+    } else {
+        None
+    };
+    let zi_shi = req.zi_shi.unwrap_or(config.zi_shi);
+    let chart = match chart_of(
+        &config,
+        &req.numbers,
+        cast_at,
+        zi_shi,
+        req.location.as_ref(),
+        solar_time_utc_offset,
+    ) {
+        Ok(chart) => chart,
+        Err(e) if e.is_input_error() => return Ok(HttpResponse::BadRequest().json(e.to_string())),
+        Err(e) => return Ok(HttpResponse::InternalServerError().json(e.to_string())),
+    };
+    let new_reading = NewReading {
+        numbers: req.numbers,
+        cast_at,
+        question: req.question,
+        category: req.category,
+        location: req.location,
+        zi_shi,
+        solar_time_utc_offset,
+        chart: serde_json::to_value(&chart)?,
+        chart_version: CHART_VERSION,
+        interpretation: req.interpretation,
+        tags: req.tags,
+        created_at: config.now(),
+    };
+    let reading = web::block(move || storage.insert(&new_reading)).await??;
+    Ok(HttpResponse::Created().json(reading))
+}
+
+/// 按分类、标签分页列出占卜记录，新记录在前。
+#[utoipa::path(
+    get,
+    path = "/api/v1/readings",
+    tag = "readings",
+    params(ReadingFilter),
+    responses(
+        (status = 200, description = "占卜记录", body = Vec<Reading>),
+    )
+)]
+pub async fn list_readings(
+    storage: web::Data<Storage>,
+    filter: web::Query<ReadingFilter>,
+) -> actix_web::Result<HttpResponse> {
+    let readings = web::block(move || storage.list(&filter)).await??;
+    Ok(HttpResponse::Ok().json(readings))
+}
+
+/// 取一条占卜记录。
+#[utoipa::path(
+    get,
+    path = "/api/v1/readings/{id}",
+    tag = "readings",
+    params(("id" = i64, Path, description = "记录编号")),
+    responses(
+        (status = 200, description = "占卜记录", body = Reading),
+        (status = 404, description = "记录不存在", body = ApiError),
+    )
+)]
+pub async fn get_reading(
+    storage: web::Data<Storage>,
+    id: web::Path<i64>,
+) -> actix_web::Result<HttpResponse> {
+    let reading = web::block(move || storage.get(*id)).await??;
+    Ok(HttpResponse::Ok().json(reading))
+}
+
+/// 修改问题、分类、解读、批注，或整体替换标签。
+#[utoipa::path(
+    patch,
+    path = "/api/v1/readings/{id}",
+    tag = "readings",
+    params(("id" = i64, Path, description = "记录编号")),
+    request_body = ReadingUpdate,
+    responses(
+        (status = 200, description = "修改后的记录", body = Reading),
+        (status = 404, description = "记录不存在", body = ApiError),
+    )
+)]
+pub async fn update_reading(
+    storage: web::Data<Storage>,
+    id: web::Path<i64>,
+    update: web::Json<ReadingUpdate>,
+) -> actix_web::Result<HttpResponse> {
+    let reading = web::block(move || storage.update(*id, &update)).await??;
+    Ok(HttpResponse::Ok().json(reading))
+}
+
+/// 删除一条占卜记录。
+#[utoipa::path(
+    delete,
+    path = "/api/v1/readings/{id}",
+    tag = "readings",
+    params(("id" = i64, Path, description = "记录编号")),
+    responses(
+        (status = 204, description = "已删除"),
+        (status = 404, description = "记录不存在", body = ApiError),
+    )
+)]
+pub async fn delete_reading(
+    storage: web::Data<Storage>,
+    id: web::Path<i64>,
+) -> actix_web::Result<HttpResponse> {
+    web::block(move || storage.delete(*id)).await??;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 用保存的输入重新排盘，便于在排盘逻辑更新后查看最新结果。
+#[utoipa::path(
+    get,
+    path = "/api/v1/readings/{id}/chart",
+    tag = "readings",
+    params(("id" = i64, Path, description = "记录编号")),
+    responses(
+        (status = 200, description = "排盘结果", body = GuaResponse),
+        (status = 404, description = "记录不存在", body = ApiError),
+    )
+)]
+pub async fn render_reading(
+    config: web::Data<Config>,
+    storage: web::Data<Storage>,
+    id: web::Path<i64>,
+) -> actix_web::Result<HttpResponse> {
+    let reading = web::block(move || storage.get(*id)).await??;
+    // 早期的记录没有保存子时约定，按当前配置
+    let zi_shi = reading.zi_shi.unwrap_or(config.zi_shi);
+    Ok(
+        match chart_of(
+            &config,
+            &reading.numbers,
+            reading.cast_at,
+            zi_shi,
+            reading.location.as_ref(),
+            reading.solar_time_utc_offset,
+        ) {
+            Ok(chart) => HttpResponse::Ok().json(chart),
+            Err(e) => HttpResponse::InternalServerError().json(e.to_string()),
+        },
+    )
+}
+
+/// 按记录的设置排盘：给出 UTC 偏移时按起卦地点的经度校正为真太阳时。
+pub fn chart_of(
+    config: &Config,
+    numbers: &str,
+    cast_at: NaiveDateTime,
+    zi_shi: ZiShi,
+    location: Option<&Location>,
+    solar_time_utc_offset: Option<i32>,
+) -> Result<GuaResponse, PaipanError> {
+    let solar_time = solar_time_utc_offset.and_then(|_| {
+        Some(SolarTimeOptions {
+            longitude: location?.longitude?,
+            equation_of_time: true,
+        })
+    });
+    paipan_solar(
+        config,
+        numbers,
+        cast_at,
+        solar_time_utc_offset,
+        zi_shi,
+        solar_time,
+    )
+}
+
+/// 记录应验结果（应验/未应验/部分应验）及实际发生的日期。
+#[utoipa::path(
+    put,
+    path = "/api/v1/readings/{id}/outcome",
+    tag = "readings",
+    params(("id" = i64, Path, description = "记录编号")),
+    request_body = Outcome,
+    responses(
+        (status = 200, description = "修改后的记录", body = Reading),
+        (status = 404, description = "记录不存在", body = ApiError),
+    )
+)]
+pub async fn set_outcome(
+    storage: web::Data<Storage>,
+    id: web::Path<i64>,
+    outcome: web::Json<Outcome>,
+) -> actix_web::Result<HttpResponse> {
+    let reading = web::block(move || storage.set_outcome(*id, Some(&outcome))).await??;
+    Ok(HttpResponse::Ok().json(reading))
+}
+
+/// 清除应验结果。
+#[utoipa::path(
+    delete,
+    path = "/api/v1/readings/{id}/outcome",
+    tag = "readings",
+    params(("id" = i64, Path, description = "记录编号")),
+    responses(
+        (status = 200, description = "修改后的记录", body = Reading),
+        (status = 404, description = "记录不存在", body = ApiError),
+    )
+)]
+pub async fn clear_outcome(
+    storage: web::Data<Storage>,
+    id: web::Path<i64>,
+) -> actix_web::Result<HttpResponse> {
+    let reading = web::block(move || storage.set_outcome(*id, None)).await??;
+    Ok(HttpResponse::Ok().json(reading))
+}
+
+/// 记录断卦时给出的应期，例如逢冲之日应在午日。
+#[utoipa::path(
+    put,
+    path = "/api/v1/readings/{id}/yingqi",
+    tag = "readings",
+    params(("id" = i64, Path, description = "记录编号")),
+    request_body = YingQiPrediction,
+    responses(
+        (status = 200, description = "修改后的记录", body = Reading),
+        (status = 400, description = "zhi 不是地支", body = ApiError),
+        (status = 404, description = "记录不存在", body = ApiError),
+    )
+)]
+pub async fn set_yingqi(
+    storage: web::Data<Storage>,
+    id: web::Path<i64>,
+    yingqi: web::Json<YingQiPrediction>,
+) -> actix_web::Result<HttpResponse> {
+    if DiZhi::try_from(yingqi.zhi.as_str()).is_err() {
+        return Ok(HttpResponse::BadRequest().json(format!("\"{}\" 不是地支", yingqi.zhi)));
+    }
+    let reading = web::block(move || storage.set_yingqi(*id, Some(&yingqi))).await??;
+    Ok(HttpResponse::Ok().json(reading))
+}
+
+/// 清除应期预测。
+#[utoipa::path(
+    delete,
+    path = "/api/v1/readings/{id}/yingqi",
+    tag = "readings",
+    params(("id" = i64, Path, description = "记录编号")),
+    responses(
+        (status = 200, description = "修改后的记录", body = Reading),
+        (status = 404, description = "记录不存在", body = ApiError),
+    )
+)]
+pub async fn clear_yingqi(
+    storage: web::Data<Storage>,
+    id: web::Path<i64>,
+) -> actix_web::Result<HttpResponse> {
+    let reading = web::block(move || storage.set_yingqi(*id, None)).await??;
+    Ok(HttpResponse::Ok().json(reading))
+}
+
+/// 按分类、断卦规则统计准确率，并检验应期预测。
+#[utoipa::path(
+    get,
+    path = "/api/v1/readings/report",
+    tag = "readings",
+    responses(
+        (status = 200, description = "统计报告", body = stats::Report),
+    )
+)]
+pub async fn outcome_report(
+    config: web::Data<Config>,
+    storage: web::Data<Storage>,
+) -> actix_web::Result<HttpResponse> {
+    // 每条记录都要重新排盘，连同读库一起放到阻塞线程池
+    let report = web::block(move || {
+        storage
+            .with_outcomes()
+            .map(|readings| stats::report(&config, &readings))
+    })
+    .await??;
+    Ok(HttpResponse::Ok().json(report))
+}
@@ -0,0 +1,449 @@
+use crate::{raster, GuaLineResponse, GuaResponse, WuXing, YaoResponse, YaoRole};
+
+/// 卦名行在 gua_lines 中的位置（六个爻之后）。
+const NAME_LINE: usize = 6;
+
+/// 终端中字符的显示宽度：汉字等全角字符占两列，其余占一列。
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+// 按显示宽度在右侧补空格
+fn pad(s: &str, width: usize) -> String {
+    let fill = width.saturating_sub(display_width(s));
+    format!("{}{}", s, " ".repeat(fill))
+}
+
+/// 干支与旬空信息，例如 "乙巳年 辛巳月 壬辰日 申时 旬空:午未"。
+/// 干支已按 gua.locale 翻译过，这里只翻译其中的标签。
+fn ganzhi_header(gua: &GuaResponse) -> String {
+    gua.locale.text(&format!(
+        "{}年 {}月 {}日 {}时 旬空:{}",
+        gua.year_ganzhi, gua.month_ganzhi, gua.day_ganzhi, gua.hour_ganzhi, gua.xun_kong
+    ))
+}
+
+// 附上求测人八字，例如 "八字 甲子 乙丑 丙寅 丁卯 日主丙火 身强 喜用:土金水"，以及用神对照。
+// 命局已按 gua.locale 翻译过，这里只翻译标签
+fn bazi_note(gua: &GuaResponse) -> Option<String> {
+    let bazi = gua.bazi.as_ref()?;
+    let pillars: Vec<&str> = bazi.pillars.iter().map(|p| p.ganzhi.as_str()).collect();
+    let separator = if gua.locale.spaced() { " " } else { "" };
+    let mut note = gua.locale.text(&format!(
+        "八字 {} 日主{} {} 喜用:{}",
+        pillars.join(" "),
+        bazi.rizhu,
+        bazi.strength,
+        bazi.favourable.join(separator)
+    ));
+    if let Some(yong_shen) = &bazi.yong_shen {
+        note.push('\n');
+        note.push_str(&yong_shen.text);
+    }
+    Some(note)
+}
+
+// 排盘表格前的附注：真太阳时校正、求测人八字
+fn notes(gua: &GuaResponse) -> Vec<String> {
+    [solar_time_note(gua), bazi_note(gua)]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+// 按真太阳时排盘时注明校正后的时间和偏移，例如 "真太阳时 2025-06-01 09:39（东经100.0°，校正 -20分12秒）"
+fn solar_time_note(gua: &GuaResponse) -> Option<String> {
+    let solar = gua.solar_time.as_ref()?;
+    let offset = solar.offset_seconds.abs();
+    Some(gua.locale.text(&format!(
+        "真太阳时 {}（{}{:.1}°，校正 {}{}分{}秒）",
+        solar.solar_time.format("%Y-%m-%d %H:%M"),
+        if solar.longitude < 0.0 {
+            "西经"
+        } else {
+            "东经"
+        },
+        solar.longitude.abs(),
+        if solar.offset_seconds < 0 { "-" } else { "+" },
+        offset / 60,
+        offset % 60
+    )))
+}
+
+/// 一个爻在表格中的各列：六神、正卦、世应、关系、变卦、回头关系。
+fn columns(gua: &GuaResponse, line: &GuaLineResponse) -> [String; 6] {
+    [
+        line.liushen.clone().unwrap_or_default(),
+        line.base_text.clone(),
+        gua.locale.term(&line.role.to_string()).to_string(),
+        line.zheng_relations_text.trim().to_string(),
+        line.bian_text.clone(),
+        line.bian_relations_text.trim().to_string(),
+    ]
+}
+
+/// 等宽字体下对齐的纯文本排盘，上爻在上、初爻在下。
+pub fn text(gua: &GuaResponse) -> String {
+    let name_line = &gua.gua_lines[NAME_LINE];
+    let rows: Vec<[String; 6]> = gua.gua_lines[..NAME_LINE]
+        .iter()
+        .rev()
+        .map(|line| columns(gua, line))
+        .collect();
+
+    // 每列取最大显示宽度
+    let mut widths = [0usize; 6];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+    // 正卦一侧（六神、正卦、世应、关系）的总宽度，卦名行按此对齐变卦名
+    let zheng_width = widths[..4].iter().sum::<usize>() + 3;
+
+    let mut out = String::new();
+    out.push_str(&ganzhi_header(gua));
+    for note in notes(gua) {
+        out.push('\n');
+        out.push_str(&note);
+    }
+    out.push_str("\n\n");
+    out.push_str(&pad(
+        &format!("{}{}", " ".repeat(widths[0] + 1), name_line.base_text),
+        zheng_width,
+    ));
+    out.push_str(" || ");
+    out.push_str(&name_line.bian_text);
+    out.push('\n');
+    for row in &rows {
+        let line = format!(
+            "{} {} {} {} || {} {}",
+            pad(&row[0], widths[0]),
+            pad(&row[1], widths[1]),
+            pad(&row[2], widths[2]),
+            pad(&row[3], widths[3]),
+            pad(&row[4], widths[4]),
+            row[5],
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Markdown 表格形式的排盘，适合粘贴到笔记或论坛。
+pub fn markdown(gua: &GuaResponse) -> String {
+    let name_line = &gua.gua_lines[NAME_LINE];
+    let mut out = String::new();
+    out.push_str(&format!("**{}**\n\n", ganzhi_header(gua)));
+    for note in notes(gua) {
+        out.push_str(&format!("{}\n\n", note.replace('\n', "  \n")));
+    }
+    out.push_str(&gua.locale.text(&format!(
+        "正卦：{} → 变卦：{}\n\n",
+        name_line.base_text, name_line.bian_text
+    )));
+    out.push_str(
+        &gua.locale
+            .text("| 六神 | 正卦 | 世应 | 关系 | 变卦 | 回头关系 |\n"),
+    );
+    out.push_str("| --- | --- | :-: | --- | --- | --- |\n");
+    for line in gua.gua_lines[..NAME_LINE].iter().rev() {
+        out.push_str(&format!("| {} |\n", columns(gua, line).join(" | ")));
+    }
+    out
+}
+
+// SVG 排版参数，单位为像素
+const FONT_SIZE: usize = 16;
+const SMALL_FONT_SIZE: usize = 13;
+const ROW_HEIGHT: usize = 36;
+const MARGIN: usize = 24;
+const GAP: usize = 12;
+const BAR_WIDTH: usize = 56;
+const BAR_HEIGHT: usize = 8;
+const COLOR_TEXT: &str = "#333333";
+const COLOR_SUBTLE: &str = "#757575";
+const COLOR_PRIMARY: &str = "#a85a33";
+const COLOR_SECONDARY: &str = "#3d4a51";
+const COLOR_UNCHANGED: &str = "#999999";
+
+/// 没有内置字体时写在 SVG desc 中的说明。
+const FONT_DEPENDENCY: &str = "本图未内嵌字体，文字按 font-family 使用看图机器上的中文字体显示";
+
+// 与前端 addWuxingColorClass 的配色一致；金用样式表中注明的沉稳金，白底上更易辨认
+fn wuxing_color(wuxing: WuXing) -> &'static str {
+    match wuxing {
+        WuXing::Shui => "#4a90e2",
+        WuXing::Mu => "#417505",
+        WuXing::Huo => "#d0021b",
+        WuXing::Jin => "#b8932f",
+        WuXing::Tu => "#8b572a",
+    }
+}
+
+// 文字在 SVG 中的大致宽度：全角字符占一个字号，半角占半个
+fn text_width(s: &str, font_size: usize) -> usize {
+    display_width(s) * font_size / 2
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 动爻在右侧标的记号：老阳 o，老阴 x
+fn marker(yao: &YaoResponse) -> Option<char> {
+    match (yao.changing, yao.yang) {
+        (false, _) => None,
+        (true, true) => Some('o'),
+        (true, false) => Some('x'),
+    }
+}
+
+fn svg_text(out: &mut String, x: usize, y: usize, size: usize, fill: &str, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    out.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>\n",
+        x,
+        y,
+        size,
+        fill,
+        escape(text)
+    ));
+}
+
+// 画一根爻：阳爻一整条，阴爻中间断开，动爻在右侧标 o/x
+fn svg_bar(out: &mut String, x: usize, y: usize, yang: bool, marker: Option<char>, fill: &str) {
+    let top = y - FONT_SIZE / 2 - BAR_HEIGHT / 2 + 2;
+    if yang {
+        out.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            x, top, BAR_WIDTH, BAR_HEIGHT, fill
+        ));
+    } else {
+        let half = (BAR_WIDTH - GAP) / 2;
+        for left in [x, x + BAR_WIDTH - half] {
+            out.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                left, top, half, BAR_HEIGHT, fill
+            ));
+        }
+    }
+    if let Some(marker) = marker {
+        svg_text(
+            out,
+            x + BAR_WIDTH + 6,
+            y,
+            FONT_SIZE,
+            COLOR_PRIMARY,
+            &marker.to_string(),
+        );
+    }
+}
+
+/// SVG 排盘图：干支旬空、正卦与变卦两栏，六神、六亲地支五行按五行着色、爻象、世应和关系。
+/// 有内置字体时以 @font-face 内嵌，图片自带字体；否则在 desc 中注明依赖看图机器上的中文字体。
+pub fn svg(gua: &GuaResponse) -> String {
+    let name_line = &gua.gua_lines[NAME_LINE];
+    let lines: Vec<&GuaLineResponse> = gua.gua_lines[..NAME_LINE].iter().rev().collect();
+    let max_width = |f: &dyn Fn(&GuaLineResponse) -> String, size: usize| {
+        lines
+            .iter()
+            .map(|line| text_width(f(line).trim(), size))
+            .max()
+            .unwrap_or(0)
+    };
+
+    // 各列的横坐标；中文下六神两字、爻四字、世应一字，译文较长时按最宽的一格放宽
+    let label = |yao: Option<&YaoResponse>| {
+        yao.map(|yao| gua.locale.text(&yao.label()))
+            .unwrap_or_default()
+    };
+    let role = |line: &GuaLineResponse| gua.locale.term(&line.role.to_string()).to_string();
+    let liushen_width =
+        max_width(&|l| l.liushen.clone().unwrap_or_default(), FONT_SIZE).max(2 * FONT_SIZE);
+    let label_width = max_width(&|l| label(l.yao.as_ref()), FONT_SIZE)
+        .max(max_width(&|l| label(l.bian_yao.as_ref()), FONT_SIZE))
+        .max(4 * FONT_SIZE);
+    let role_width = max_width(&role, FONT_SIZE).max(FONT_SIZE);
+    let liushen_x = MARGIN;
+    let label_x = liushen_x + liushen_width + GAP;
+    let bar_x = label_x + label_width + GAP;
+    let role_x = bar_x + BAR_WIDTH + FONT_SIZE + GAP;
+    let relation_x = role_x + role_width + 8 + GAP;
+    let zheng_end = (relation_x + max_width(&|l| l.zheng_relations_text.clone(), SMALL_FONT_SIZE))
+        .max(label_x + text_width(&name_line.base_text, FONT_SIZE));
+    let divider_x = zheng_end + GAP;
+    let bian_label_x = divider_x + GAP * 2;
+    let bian_bar_x = bian_label_x + label_width + GAP;
+    let bian_relation_x = bian_bar_x + BAR_WIDTH + FONT_SIZE + GAP;
+
+    let header = ganzhi_header(gua);
+    let notes: Vec<String> = notes(gua)
+        .iter()
+        .flat_map(|note| note.lines().map(String::from).collect::<Vec<_>>())
+        .collect();
+    let header_width = std::iter::once(text_width(&header, FONT_SIZE))
+        .chain(notes.iter().map(|n| text_width(n, SMALL_FONT_SIZE)))
+        .max()
+        .unwrap_or(0);
+    let width = (bian_relation_x + max_width(&|l| l.bian_relations_text.clone(), SMALL_FONT_SIZE))
+        .max(bian_label_x + text_width(&name_line.bian_text, FONT_SIZE))
+        .max(MARGIN + header_width)
+        + MARGIN;
+
+    let mut body = String::new();
+    let mut y = MARGIN + FONT_SIZE;
+    svg_text(&mut body, MARGIN, y, FONT_SIZE, COLOR_TEXT, &header);
+    for note in &notes {
+        y += SMALL_FONT_SIZE + 8;
+        svg_text(&mut body, MARGIN, y, SMALL_FONT_SIZE, COLOR_SUBTLE, note);
+    }
+
+    y += ROW_HEIGHT;
+    let table_top = y - FONT_SIZE - GAP / 2;
+    svg_text(
+        &mut body,
+        label_x,
+        y,
+        FONT_SIZE,
+        COLOR_SECONDARY,
+        &name_line.base_text,
+    );
+    svg_text(
+        &mut body,
+        bian_label_x,
+        y,
+        FONT_SIZE,
+        COLOR_SECONDARY,
+        &name_line.bian_text,
+    );
+
+    for line in &lines {
+        y += ROW_HEIGHT;
+        svg_text(
+            &mut body,
+            liushen_x,
+            y,
+            FONT_SIZE,
+            COLOR_SUBTLE,
+            line.liushen.as_deref().unwrap_or(""),
+        );
+        if let Some(yao) = &line.yao {
+            svg_text(
+                &mut body,
+                label_x,
+                y,
+                FONT_SIZE,
+                wuxing_color(yao.wuxing),
+                &label(Some(yao)),
+            );
+            svg_bar(&mut body, bar_x, y, yao.yang, marker(yao), COLOR_SECONDARY);
+        }
+
+        let role = role(line);
+        if !role.is_empty() {
+            let fill = if line.role == YaoRole::Shi {
+                COLOR_PRIMARY
+            } else {
+                COLOR_SECONDARY
+            };
+            body.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"5\" fill=\"{}\"/>\n",
+                role_x,
+                y - FONT_SIZE,
+                text_width(&role, FONT_SIZE).max(FONT_SIZE) + 8,
+                FONT_SIZE + 6,
+                fill
+            ));
+            svg_text(&mut body, role_x + 4, y, FONT_SIZE, "#ffffff", &role);
+        }
+        svg_text(
+            &mut body,
+            relation_x,
+            y,
+            SMALL_FONT_SIZE,
+            COLOR_SUBTLE,
+            line.zheng_relations_text.trim(),
+        );
+
+        // 未变的爻在变卦中置灰，与网页一致
+        if let Some(bian_yao) = &line.bian_yao {
+            let (label_fill, bar_fill) = if line.is_changing {
+                (wuxing_color(bian_yao.wuxing), COLOR_SECONDARY)
+            } else {
+                (COLOR_UNCHANGED, COLOR_UNCHANGED)
+            };
+            svg_text(
+                &mut body,
+                bian_label_x,
+                y,
+                FONT_SIZE,
+                label_fill,
+                &label(Some(bian_yao)),
+            );
+            svg_bar(&mut body, bian_bar_x, y, bian_yao.yang, None, bar_fill);
+        }
+        svg_text(
+            &mut body,
+            bian_relation_x,
+            y,
+            SMALL_FONT_SIZE,
+            COLOR_SUBTLE,
+            line.bian_relations_text.trim(),
+        );
+    }
+    let height = y + MARGIN;
+    body.push_str(&format!(
+        "<line x1=\"{x}\" y1=\"{}\" x2=\"{x}\" y2=\"{}\" stroke=\"#eaeaea\"/>\n",
+        table_top,
+        y + GAP / 2,
+        x = divider_x
+    ));
+
+    let faces = raster::font_faces();
+    let embedded: String = faces
+        .iter()
+        .map(|face| format!("'{}', ", face.family))
+        .collect();
+    let fonts = if faces.is_empty() {
+        format!(
+            "<desc>{}</desc>\n",
+            escape(&gua.locale.text(FONT_DEPENDENCY))
+        )
+    } else {
+        let css: Vec<&str> = faces.iter().map(|face| face.css.as_str()).collect();
+        format!("<defs><style>{}</style></defs>\n", css.join("\n"))
+    };
+    format!(
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" ",
+            "font-family=\"{embedded}'Noto Serif SC', 'Source Han Serif SC', 'Songti SC', SimSun, serif\">\n",
+            "{fonts}<rect width=\"100%\" height=\"100%\" fill=\"#fdfdfc\"/>\n{body}</svg>\n"
+        ),
+        w = width,
+        h = height,
+        embedded = embedded,
+        fonts = fonts,
+        body = body
+    )
+}
@@ -0,0 +1,87 @@
+use chrono::{Datelike, Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// 真太阳时校正的请求参数：出生地/起卦地经度（东经为正），以及是否加上均时差。
+#[derive(Debug, Clone, Copy, Deserialize, utoipa::ToSchema)]
+pub struct SolarTimeOptions {
+    pub longitude: f64,
+    #[serde(default = "default_equation_of_time")]
+    pub equation_of_time: bool,
+}
+
+fn default_equation_of_time() -> bool {
+    true
+}
+
+/// 真太阳时校正结果，随排盘结果一起返回。
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SolarTime {
+    pub longitude: f64,
+    /// 所用时区的标准经线，例如东八区为 120
+    pub standard_meridian: f64,
+    /// 校正前的钟表时间
+    pub clock_time: NaiveDateTime,
+    /// 校正后的真太阳时，排盘按此时间计算
+    pub solar_time: NaiveDateTime,
+    /// 经度差带来的偏移（秒）
+    pub longitude_offset_seconds: i64,
+    /// 均时差（秒），未启用时为 0
+    pub equation_of_time_seconds: i64,
+    /// 总偏移（秒）
+    pub offset_seconds: i64,
+}
+
+/// 真太阳时校正可能出现的错误。
+#[derive(Debug)]
+pub enum SolarTimeError {
+    InvalidLongitude(f64),
+}
+
+impl std::fmt::Display for SolarTimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolarTimeError::InvalidLongitude(longitude) => {
+                write!(f, "经度必须在-180到180之间，收到 {}", longitude)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolarTimeError {}
+
+/// 把钟表时间校正为真太阳时。utc_offset_seconds 为钟表时间所在时区的 UTC 偏移，
+/// 用来确定标准经线；每差一度经度差四分钟。
+pub fn correct(
+    clock_time: NaiveDateTime,
+    utc_offset_seconds: i32,
+    options: SolarTimeOptions,
+) -> Result<SolarTime, SolarTimeError> {
+    let longitude = options.longitude;
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(SolarTimeError::InvalidLongitude(longitude));
+    }
+    let standard_meridian = utc_offset_seconds as f64 / 3600.0 * 15.0;
+    let longitude_offset_seconds = ((longitude - standard_meridian) * 240.0).round() as i64;
+    let equation_of_time_seconds = if options.equation_of_time {
+        (equation_of_time_minutes(clock_time.ordinal()) * 60.0).round() as i64
+    } else {
+        0
+    };
+    let offset_seconds = longitude_offset_seconds + equation_of_time_seconds;
+    Ok(SolarTime {
+        longitude,
+        standard_meridian,
+        clock_time,
+        solar_time: clock_time + Duration::seconds(offset_seconds),
+        longitude_offset_seconds,
+        equation_of_time_seconds,
+        offset_seconds,
+    })
+}
+
+// 均时差的近似公式（分钟），误差在一分钟以内，对划分时辰足够
+fn equation_of_time_minutes(day_of_year: u32) -> f64 {
+    let b = 2.0 * PI * (day_of_year as f64 - 81.0) / 364.0;
+    9.87 * (2.0 * b).sin() - 7.53 * b.cos() - 1.5 * b.sin()
+}
@@ -0,0 +1,196 @@
+use crate::{
+    config::Config,
+    get_ganzhi_info, gua_table,
+    readings::chart_of,
+    storage::{OutcomeResult, Reading, YingQiUnit},
+    ChongHe, GuaResponse, Relation, Source, YaoRole, ZiShi, SUPPORTED_YEARS,
+};
+use chrono::Datelike;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 一组记录的应验计数。部分应验按半次计入准确率。
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct Tally {
+    pub total: u32,
+    pub fulfilled: u32,
+    pub partial: u32,
+    pub unfulfilled: u32,
+    pub accuracy: f64,
+}
+
+impl Tally {
+    fn add(&mut self, result: OutcomeResult) {
+        self.total += 1;
+        match result {
+            OutcomeResult::Fulfilled => self.fulfilled += 1,
+            OutcomeResult::Partial => self.partial += 1,
+            OutcomeResult::Unfulfilled => self.unfulfilled += 1,
+        }
+        self.accuracy = (self.fulfilled as f64 + self.partial as f64 * 0.5) / self.total as f64;
+    }
+}
+
+/// 按某个维度（分类或断卦规则）分组后的计数。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct GroupTally {
+    pub key: String,
+    #[serde(flatten)]
+    pub tally: Tally,
+}
+
+/// 应期预测的命中计数。
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct YingQiTally {
+    /// 有应期预测且记录了实际发生日期的记录数
+    pub checked: u32,
+    pub matched: u32,
+    pub match_rate: f64,
+}
+
+impl YingQiTally {
+    fn add(&mut self, matched: bool) {
+        self.checked += 1;
+        if matched {
+            self.matched += 1;
+        }
+        self.match_rate = self.matched as f64 / self.checked as f64;
+    }
+}
+
+/// 应期预测统计：总体以及按预测规则（如逢冲之日）分组。
+#[derive(Debug, Default, Serialize, utoipa::ToSchema)]
+pub struct YingQiReport {
+    pub predicted: u32,
+    #[serde(flatten)]
+    pub overall: YingQiTally,
+    pub by_rule: BTreeMap<String, YingQiTally>,
+}
+
+/// 应验统计报告。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Report {
+    pub overall: Tally,
+    pub by_category: Vec<GroupTally>,
+    pub by_rule: Vec<GroupTally>,
+    pub yingqi: YingQiReport,
+}
+
+/// 根据已记录应验结果的记录生成统计报告。断卦规则按记录保存的设置重新排盘得出。
+pub fn report(config: &Config, readings: &[Reading]) -> Report {
+    let mut overall = Tally::default();
+    let mut by_category: BTreeMap<String, Tally> = BTreeMap::new();
+    let mut by_rule: BTreeMap<String, Tally> = BTreeMap::new();
+    let mut yingqi = YingQiReport::default();
+
+    for reading in readings {
+        let Some(outcome) = &reading.outcome else {
+            continue;
+        };
+        overall.add(outcome.result);
+        let category = if reading.category.is_empty() {
+            "未分类".to_string()
+        } else {
+            reading.category.clone()
+        };
+        by_category.entry(category).or_default().add(outcome.result);
+        let chart = chart_of(
+            config,
+            &reading.numbers,
+            reading.cast_at,
+            reading.zi_shi.unwrap_or(config.zi_shi),
+            reading.location.as_ref(),
+            reading.solar_time_utc_offset,
+        );
+        match chart {
+            Ok(chart) => {
+                for rule in fired_rules(&chart) {
+                    by_rule.entry(rule).or_default().add(outcome.result);
+                }
+            }
+            Err(e) => log::warn!("记录 {} 无法重新排盘: {}", reading.id, e),
+        }
+
+        if let Some(prediction) = &reading.yingqi {
+            yingqi.predicted += 1;
+            // 只有实际发生了且记录了日期，才能检验应期
+            if let Some(date) = outcome.date.filter(|date| {
+                outcome.result != OutcomeResult::Unfulfilled
+                    && SUPPORTED_YEARS.contains(&(date.year() as i64))
+            }) {
+                let (_, month_ganzhi, day_ganzhi, _) = get_ganzhi_info(
+                    date.and_hms_opt(12, 0, 0).unwrap_or_default(),
+                    ZiShi::default(),
+                );
+                let actual = match prediction.unit {
+                    YingQiUnit::Day => day_ganzhi,
+                    YingQiUnit::Month => month_ganzhi,
+                };
+                let matched = actual.chars().nth(1).map(|c| c.to_string()).as_deref()
+                    == Some(prediction.zhi.as_str());
+                yingqi.overall.add(matched);
+                yingqi
+                    .by_rule
+                    .entry(prediction.rule.clone())
+                    .or_default()
+                    .add(matched);
+            }
+        }
+    }
+
+    let grouped = |map: BTreeMap<String, Tally>| {
+        map.into_iter()
+            .map(|(key, tally)| GroupTally { key, tally })
+            .collect()
+    };
+    Report {
+        overall,
+        by_category: grouped(by_category),
+        by_rule: grouped(by_rule),
+        yingqi,
+    }
+}
+
+/// 从排盘结果的爻位数据中提取触发的断卦规则，例如 "世爻旬空"、"本卦六冲"、"动爻回头克"。
+pub fn fired_rules(chart: &GuaResponse) -> Vec<String> {
+    let lines = &chart.gua_lines[..chart.gua_lines.len().min(6)];
+    let mut rules = Vec::new();
+    let has_changing = lines.iter().any(|line| line.is_changing);
+    for line in lines {
+        let prefix = match line.role {
+            YaoRole::Shi => "世爻",
+            YaoRole::Ying => "应爻",
+            YaoRole::Normal => "",
+        };
+        if !prefix.is_empty() {
+            for relation in &line.relations {
+                rules.push(format!("{}{}", prefix, relation));
+            }
+        }
+        if line.is_changing {
+            for relation in &line.bian_relations {
+                if let Relation::ChongHe(Source::HuiTou, _) | Relation::ShengKe(Source::HuiTou, _) =
+                    relation
+                {
+                    rules.push(format!("动爻{}", relation));
+                }
+            }
+        }
+    }
+
+    for (label, hexagram) in [("本卦", &chart.hexagram), ("变卦", &chart.bian_hexagram)] {
+        if label == "变卦" && !has_changing {
+            continue;
+        }
+        match gua_table::lookup(gua_table::parse_code(&hexagram.index)).chong_he {
+            Some(ChongHe::Chong) => rules.push(format!("{}六冲", label)),
+            Some(ChongHe::He) => rules.push(format!("{}六合", label)),
+            None => {}
+        }
+    }
+    rules.push(if has_changing { "动卦" } else { "静卦" }.to_string());
+
+    rules.sort();
+    rules.dedup();
+    rules
+}
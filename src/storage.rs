@@ -0,0 +1,616 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::ZiShi;
+
+/// 按顺序执行的数据库迁移，已执行到第几条记录在 PRAGMA user_version 中。
+pub const MIGRATIONS: &[&str] = &[
+    // 1: 占卜记录与标签
+    "CREATE TABLE readings (
+        id             INTEGER PRIMARY KEY AUTOINCREMENT,
+        numbers        TEXT NOT NULL,
+        cast_at        TEXT NOT NULL,
+        question       TEXT NOT NULL DEFAULT '',
+        category       TEXT NOT NULL DEFAULT '',
+        chart_json     TEXT NOT NULL,
+        interpretation TEXT,
+        notes          TEXT NOT NULL DEFAULT '',
+        created_at     TEXT NOT NULL
+    );
+    CREATE TABLE reading_tags (
+        reading_id INTEGER NOT NULL REFERENCES readings(id) ON DELETE CASCADE,
+        tag        TEXT NOT NULL,
+        PRIMARY KEY (reading_id, tag)
+    );
+    CREATE INDEX readings_category ON readings(category);",
+    // 2: 应验结果与应期预测
+    "ALTER TABLE readings ADD COLUMN outcome TEXT;
+    ALTER TABLE readings ADD COLUMN outcome_date TEXT;
+    ALTER TABLE readings ADD COLUMN outcome_notes TEXT NOT NULL DEFAULT '';
+    ALTER TABLE readings ADD COLUMN yingqi_rule TEXT;
+    ALTER TABLE readings ADD COLUMN yingqi_zhi TEXT;
+    ALTER TABLE readings ADD COLUMN yingqi_unit TEXT;",
+    // 3: 起卦地点
+    "ALTER TABLE readings ADD COLUMN location_name TEXT;
+    ALTER TABLE readings ADD COLUMN longitude REAL;",
+    // 4: 起卦时的排盘设置，重新排盘和导出时沿用，不随配置变化
+    "ALTER TABLE readings ADD COLUMN zi_shi TEXT;
+    ALTER TABLE readings ADD COLUMN solar_time_utc_offset INTEGER;",
+    // 5: 保存的排盘结果是哪个结构版本算出的，早期记录为 NULL
+    "ALTER TABLE readings ADD COLUMN chart_version INTEGER;",
+];
+
+/// 读取列表时默认与最多返回的条数。
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+
+/// 一条保存下来的占卜记录。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Reading {
+    pub id: i64,
+    pub numbers: String,
+    pub cast_at: NaiveDateTime,
+    pub question: String,
+    pub category: String,
+    pub location: Option<Location>,
+    /// 起卦时 23 点子时的日柱归属；早期的记录没有保存，为 None
+    pub zi_shi: Option<ZiShi>,
+    /// 按真太阳时排盘时，起卦时间所在时区的 UTC 偏移（秒）；未校正时为 None
+    pub solar_time_utc_offset: Option<i32>,
+    /// 保存时计算出的排盘结果（与 /api/v1/cast 的返回一致）
+    pub chart: serde_json::Value,
+    /// 计算 chart 时排盘结果的结构版本（CHART_VERSION）；早期的记录没有保存，为 None
+    pub chart_version: Option<u32>,
+    pub interpretation: Option<String>,
+    pub notes: String,
+    pub tags: Vec<String>,
+    pub created_at: NaiveDateTime,
+    /// 事后记录的应验情况
+    pub outcome: Option<Outcome>,
+    /// 断卦时给出的应期
+    pub yingqi: Option<YingQiPrediction>,
+}
+
+/// 起卦地点。只有按真太阳时排盘的记录才用到经度。
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Location {
+    pub name: Option<String>,
+    /// 经度，东经为正
+    pub longitude: Option<f64>,
+}
+
+/// 应验结果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeResult {
+    #[serde(alias = "应验")]
+    Fulfilled,
+    #[serde(alias = "未应验")]
+    Unfulfilled,
+    #[serde(alias = "部分应验")]
+    Partial,
+}
+
+impl OutcomeResult {
+    fn as_str(&self) -> &'static str {
+        match self {
+            OutcomeResult::Fulfilled => "fulfilled",
+            OutcomeResult::Unfulfilled => "unfulfilled",
+            OutcomeResult::Partial => "partial",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "fulfilled" => Some(OutcomeResult::Fulfilled),
+            "unfulfilled" => Some(OutcomeResult::Unfulfilled),
+            "partial" => Some(OutcomeResult::Partial),
+            _ => None,
+        }
+    }
+}
+
+/// 一条记录的应验情况：结果、实际发生的日期和说明。
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct Outcome {
+    pub result: OutcomeResult,
+    pub date: Option<NaiveDate>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// 应期以日还是以月论。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum YingQiUnit {
+    Day,
+    Month,
+}
+
+impl YingQiUnit {
+    fn as_str(&self) -> &'static str {
+        match self {
+            YingQiUnit::Day => "day",
+            YingQiUnit::Month => "month",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "day" => Some(YingQiUnit::Day),
+            "month" => Some(YingQiUnit::Month),
+            _ => None,
+        }
+    }
+}
+
+/// 断卦时给出的应期，例如 rule="逢冲之日"、zhi="午"、unit=day 表示应在午日。
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct YingQiPrediction {
+    pub rule: String,
+    pub zhi: String,
+    pub unit: YingQiUnit,
+}
+
+/// 新建记录所需的数据，与标签在同一个事务中写入。
+#[derive(Debug)]
+pub struct NewReading {
+    pub numbers: String,
+    pub cast_at: NaiveDateTime,
+    pub question: String,
+    pub category: String,
+    pub location: Option<Location>,
+    pub zi_shi: ZiShi,
+    pub solar_time_utc_offset: Option<i32>,
+    pub chart: serde_json::Value,
+    /// 计算 chart 时的 CHART_VERSION
+    pub chart_version: u32,
+    pub interpretation: Option<String>,
+    pub tags: Vec<String>,
+    /// 保存的时刻，由调用方按配置的时钟给出
+    pub created_at: NaiveDateTime,
+}
+
+/// 导入的一条完整记录，包括批注、应验结果和应期。
+#[derive(Debug)]
+pub struct ImportedReading {
+    pub reading: NewReading,
+    pub notes: String,
+    pub outcome: Option<Outcome>,
+    pub yingqi: Option<YingQiPrediction>,
+}
+
+/// 修改记录时可选的字段，未提供的字段保持不变；tags 会整体替换。
+#[derive(Debug, Default, Deserialize, utoipa::ToSchema)]
+pub struct ReadingUpdate {
+    pub question: Option<String>,
+    pub category: Option<String>,
+    /// 给出 null 时清除解读
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub interpretation: Option<Option<String>>,
+    pub notes: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+// 区分未提供的字段与显式的 null：未提供时为 None（由 serde(default) 给出），null 为 Some(None)
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// 列表查询条件。
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReadingFilter {
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// 存储层错误。
+#[derive(Debug)]
+pub enum StorageError {
+    NotFound(i64),
+    Sqlite(rusqlite::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::NotFound(id) => write!(f, "记录 {} 不存在", id),
+            StorageError::Sqlite(e) => write!(f, "数据库错误: {}", e),
+            StorageError::Json(e) => write!(f, "排盘数据格式错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(e: rusqlite::Error) -> Self {
+        StorageError::Sqlite(e)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(e: serde_json::Error) -> Self {
+        StorageError::Json(e)
+    }
+}
+
+/// 基于内嵌 SQLite 的占卜记录存储。
+pub struct Storage {
+    conn: Mutex<Connection>,
+}
+
+impl Storage {
+    /// 打开（或创建）数据库文件并执行未完成的迁移。
+    pub fn open(path: &Path) -> Result<Self, StorageError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, StorageError> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let tx = conn.transaction()?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+        }
+        tx.commit()?;
+        Ok(Storage {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    // 数据库连接只在这里加锁，持锁期间不会 panic，因此忽略中毒状态
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 保存一条新记录及其标签并返回完整记录，任何一步失败则都不保存。
+    pub fn insert(&self, reading: &NewReading) -> Result<Reading, StorageError> {
+        let id = {
+            let mut conn = self.conn();
+            let tx = conn.transaction()?;
+            let id = insert_reading(&tx, reading)?;
+            tx.commit()?;
+            id
+        };
+        self.get(id)
+    }
+
+    /// 在一个事务中导入多条记录，任何一条失败则全部不导入，返回新记录的 id。
+    pub fn import(&self, readings: &[ImportedReading]) -> Result<Vec<i64>, StorageError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut ids = Vec::with_capacity(readings.len());
+        for imported in readings {
+            let id = insert_reading(&tx, &imported.reading)?;
+            let outcome = imported.outcome.as_ref();
+            let yingqi = imported.yingqi.as_ref();
+            tx.execute(
+                "UPDATE readings SET
+                    notes = ?2, outcome = ?3, outcome_date = ?4, outcome_notes = ?5,
+                    yingqi_rule = ?6, yingqi_zhi = ?7, yingqi_unit = ?8
+                 WHERE id = ?1",
+                params![
+                    id,
+                    imported.notes,
+                    outcome.map(|o| o.result.as_str()),
+                    outcome.and_then(|o| o.date),
+                    outcome.map(|o| o.notes.as_str()).unwrap_or(""),
+                    yingqi.map(|y| y.rule.as_str()),
+                    yingqi.map(|y| y.zhi.as_str()),
+                    yingqi.map(|y| y.unit.as_str()),
+                ],
+            )?;
+            ids.push(id);
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    /// 按 id 读取一条记录。
+    pub fn get(&self, id: i64) -> Result<Reading, StorageError> {
+        let conn = self.conn();
+        let reading = conn
+            .query_row(
+                "SELECT * FROM readings WHERE id = ?1",
+                params![id],
+                ReadingRow::from_row,
+            )
+            .optional()?
+            .ok_or(StorageError::NotFound(id))?;
+        reading.into_reading(&conn)
+    }
+
+    /// 按分类和标签筛选记录，最新的在前。
+    pub fn list(&self, filter: &ReadingFilter) -> Result<Vec<Reading>, StorageError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT * FROM readings r
+             WHERE (?1 IS NULL OR r.category = ?1)
+               AND (?2 IS NULL OR EXISTS (
+                    SELECT 1 FROM reading_tags t WHERE t.reading_id = r.id AND t.tag = ?2))
+             ORDER BY r.cast_at DESC, r.id DESC
+             LIMIT ?3 OFFSET ?4",
+        )?;
+        let rows = stmt
+            .query_map(
+                params![
+                    filter.category,
+                    filter.tag,
+                    filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+                    filter.offset.unwrap_or(0),
+                ],
+                ReadingRow::from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter()
+            .map(|row| row.into_reading(&conn))
+            .collect()
+    }
+
+    /// 修改记录的问题、分类、解读、批注或标签。
+    pub fn update(&self, id: i64, update: &ReadingUpdate) -> Result<Reading, StorageError> {
+        {
+            let mut conn = self.conn();
+            let tx = conn.transaction()?;
+            let changed = tx.execute(
+                "UPDATE readings SET
+                    question       = COALESCE(?2, question),
+                    category       = COALESCE(?3, category),
+                    interpretation = IIF(?6, ?4, interpretation),
+                    notes          = COALESCE(?5, notes)
+                 WHERE id = ?1",
+                params![
+                    id,
+                    update.question,
+                    update.category,
+                    update.interpretation.as_ref().and_then(Option::as_deref),
+                    update.notes,
+                    update.interpretation.is_some(),
+                ],
+            )?;
+            if changed == 0 {
+                return Err(StorageError::NotFound(id));
+            }
+            if let Some(tags) = &update.tags {
+                tx.execute(
+                    "DELETE FROM reading_tags WHERE reading_id = ?1",
+                    params![id],
+                )?;
+                insert_tags(&tx, id, tags)?;
+            }
+            tx.commit()?;
+        }
+        self.get(id)
+    }
+
+    /// 记录或清除应验结果。
+    pub fn set_outcome(&self, id: i64, outcome: Option<&Outcome>) -> Result<Reading, StorageError> {
+        let changed = self.conn().execute(
+            "UPDATE readings SET outcome = ?2, outcome_date = ?3, outcome_notes = ?4 WHERE id = ?1",
+            params![
+                id,
+                outcome.map(|o| o.result.as_str()),
+                outcome.and_then(|o| o.date),
+                outcome.map(|o| o.notes.as_str()).unwrap_or(""),
+            ],
+        )?;
+        if changed == 0 {
+            return Err(StorageError::NotFound(id));
+        }
+        self.get(id)
+    }
+
+    /// 记录或清除应期预测。
+    pub fn set_yingqi(
+        &self,
+        id: i64,
+        yingqi: Option<&YingQiPrediction>,
+    ) -> Result<Reading, StorageError> {
+        let changed = self.conn().execute(
+            "UPDATE readings SET yingqi_rule = ?2, yingqi_zhi = ?3, yingqi_unit = ?4 WHERE id = ?1",
+            params![
+                id,
+                yingqi.map(|y| y.rule.as_str()),
+                yingqi.map(|y| y.zhi.as_str()),
+                yingqi.map(|y| y.unit.as_str()),
+            ],
+        )?;
+        if changed == 0 {
+            return Err(StorageError::NotFound(id));
+        }
+        self.get(id)
+    }
+
+    /// 所有已记录应验结果的记录，用于统计。
+    pub fn with_outcomes(&self) -> Result<Vec<Reading>, StorageError> {
+        let conn = self.conn();
+        let mut stmt =
+            conn.prepare("SELECT * FROM readings WHERE outcome IS NOT NULL ORDER BY id")?;
+        let rows = stmt
+            .query_map([], ReadingRow::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter()
+            .map(|row| row.into_reading(&conn))
+            .collect()
+    }
+
+    /// 删除一条记录（标签随之级联删除）。
+    pub fn delete(&self, id: i64) -> Result<(), StorageError> {
+        let deleted = self
+            .conn()
+            .execute("DELETE FROM readings WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(StorageError::NotFound(id));
+        }
+        Ok(())
+    }
+}
+
+// 写入记录和标签，调用方负责事务
+fn insert_reading(conn: &Connection, reading: &NewReading) -> Result<i64, StorageError> {
+    let location = reading.location.as_ref();
+    conn.execute(
+        "INSERT INTO readings
+            (numbers, cast_at, question, category, chart_json, interpretation, created_at,
+             location_name, longitude, zi_shi, solar_time_utc_offset, chart_version)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            reading.numbers,
+            reading.cast_at,
+            reading.question,
+            reading.category,
+            serde_json::to_string(&reading.chart)?,
+            reading.interpretation,
+            reading.created_at,
+            location.and_then(|l| l.name.as_deref()),
+            location.and_then(|l| l.longitude),
+            zi_shi_str(reading.zi_shi),
+            reading.solar_time_utc_offset,
+            reading.chart_version,
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    insert_tags(conn, id, &reading.tags)?;
+    Ok(id)
+}
+
+// 去掉首尾空白，忽略空标签和重复的标签
+fn insert_tags(conn: &Connection, id: i64, tags: &[String]) -> Result<(), StorageError> {
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        conn.execute(
+            "INSERT OR IGNORE INTO reading_tags (reading_id, tag) VALUES (?1, ?2)",
+            params![id, tag],
+        )?;
+    }
+    Ok(())
+}
+
+// 子时约定在数据库中的写法，与接口中的取值一致
+fn zi_shi_str(zi_shi: ZiShi) -> &'static str {
+    match zi_shi {
+        ZiShi::Split => "split",
+        ZiShi::NextDay => "next_day",
+    }
+}
+
+fn parse_zi_shi(s: &str) -> Option<ZiShi> {
+    match s {
+        "split" => Some(ZiShi::Split),
+        "next_day" => Some(ZiShi::NextDay),
+        _ => None,
+    }
+}
+
+// readings 表中的一行，标签需要另外查询
+struct ReadingRow {
+    id: i64,
+    numbers: String,
+    cast_at: NaiveDateTime,
+    question: String,
+    category: String,
+    location: Option<Location>,
+    zi_shi: Option<ZiShi>,
+    solar_time_utc_offset: Option<i32>,
+    chart_json: String,
+    chart_version: Option<u32>,
+    interpretation: Option<String>,
+    notes: String,
+    created_at: NaiveDateTime,
+    outcome: Option<Outcome>,
+    yingqi: Option<YingQiPrediction>,
+}
+
+impl ReadingRow {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        Ok(ReadingRow {
+            id: row.get("id")?,
+            numbers: row.get("numbers")?,
+            cast_at: row.get("cast_at")?,
+            question: row.get("question")?,
+            category: row.get("category")?,
+            location: match (
+                row.get::<_, Option<String>>("location_name")?,
+                row.get::<_, Option<f64>>("longitude")?,
+            ) {
+                (None, None) => None,
+                (name, longitude) => Some(Location { name, longitude }),
+            },
+            zi_shi: match row.get::<_, Option<String>>("zi_shi")? {
+                Some(zi_shi) => Some(parse_zi_shi(&zi_shi).ok_or_else(|| invalid_text(&zi_shi))?),
+                None => None,
+            },
+            solar_time_utc_offset: row.get("solar_time_utc_offset")?,
+            chart_json: row.get("chart_json")?,
+            chart_version: row.get("chart_version")?,
+            interpretation: row.get("interpretation")?,
+            notes: row.get("notes")?,
+            created_at: row.get("created_at")?,
+            outcome: match row.get::<_, Option<String>>("outcome")? {
+                Some(result) => Some(Outcome {
+                    result: OutcomeResult::parse(&result).ok_or_else(|| invalid_text(&result))?,
+                    date: row.get("outcome_date")?,
+                    notes: row.get("outcome_notes")?,
+                }),
+                None => None,
+            },
+            yingqi: match row.get::<_, Option<String>>("yingqi_unit")? {
+                Some(unit) => Some(YingQiPrediction {
+                    rule: row.get("yingqi_rule")?,
+                    zhi: row.get("yingqi_zhi")?,
+                    unit: YingQiUnit::parse(&unit).ok_or_else(|| invalid_text(&unit))?,
+                }),
+                None => None,
+            },
+        })
+    }
+
+    fn into_reading(self, conn: &Connection) -> Result<Reading, StorageError> {
+        let mut stmt =
+            conn.prepare_cached("SELECT tag FROM reading_tags WHERE reading_id = ?1 ORDER BY tag")?;
+        let tags = stmt
+            .query_map(params![self.id], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(Reading {
+            id: self.id,
+            numbers: self.numbers,
+            cast_at: self.cast_at,
+            question: self.question,
+            category: self.category,
+            location: self.location,
+            zi_shi: self.zi_shi,
+            solar_time_utc_offset: self.solar_time_utc_offset,
+            chart: serde_json::from_str(&self.chart_json)?,
+            chart_version: self.chart_version,
+            interpretation: self.interpretation,
+            notes: self.notes,
+            tags,
+            created_at: self.created_at,
+            outcome: self.outcome,
+            yingqi: self.yingqi,
+        })
+    }
+}
+
+// 数据库中出现了无法识别的枚举取值
+fn invalid_text(value: &str) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        0,
+        rusqlite::types::Type::Text,
+        format!("无法识别的取值 \"{}\"", value).into(),
+    )
+}
@@ -0,0 +1,124 @@
+//! 嵌入静态文件的处理函数测试。
+
+use actix_web::{http::header, test, App};
+
+async fn get(uri: &str, headers: &[(header::HeaderName, &str)]) -> (u16, header::HeaderMap) {
+    let app = test::init_service(App::new().configure(crate::assets::configure)).await;
+    let mut req = test::TestRequest::get().uri(uri);
+    for (name, value) in headers {
+        req = req.insert_header((name.clone(), *value));
+    }
+    let resp = test::call_service(&app, req.to_request()).await;
+    (resp.status().as_u16(), resp.headers().clone())
+}
+
+fn header_str(headers: &header::HeaderMap, name: header::HeaderName) -> &str {
+    headers.get(name).unwrap().to_str().unwrap()
+}
+
+#[actix_web::test]
+async fn content_type_follows_extension() {
+    for (uri, expected) in [
+        ("/", "text/html; charset=utf-8"),
+        ("/css/style.css", "text/css; charset=utf-8"),
+        ("/wuxing.jpg", "image/jpeg"),
+    ] {
+        let (status, headers) = get(uri, &[]).await;
+        assert_eq!(status, 200, "{}", uri);
+        assert_eq!(
+            header_str(&headers, header::CONTENT_TYPE),
+            expected,
+            "{}",
+            uri
+        );
+        assert_eq!(header_str(&headers, header::CACHE_CONTROL), "no-cache");
+    }
+    let (_, headers) = get("/js/app.js", &[]).await;
+    let content_type = header_str(&headers, header::CONTENT_TYPE);
+    assert!(content_type.contains("javascript"), "{}", content_type);
+    assert!(content_type.ends_with("charset=utf-8"), "{}", content_type);
+}
+
+#[actix_web::test]
+async fn matching_etag_gives_304() {
+    let (_, headers) = get("/js/app.js", &[]).await;
+    let etag = header_str(&headers, header::ETAG).to_string();
+
+    let (status, headers) = get("/js/app.js", &[(header::IF_NONE_MATCH, &etag)]).await;
+    assert_eq!(status, 304);
+    assert_eq!(header_str(&headers, header::ETAG), etag);
+    assert!(headers.get(header::CONTENT_TYPE).is_none());
+
+    let weak = format!("\"other\", W/{}", etag);
+    let (status, _) = get("/js/app.js", &[(header::IF_NONE_MATCH, &weak)]).await;
+    assert_eq!(status, 304);
+
+    let (status, _) = get("/js/app.js", &[(header::IF_NONE_MATCH, "\"other\"")]).await;
+    assert_eq!(status, 200);
+}
+
+#[actix_web::test]
+async fn unknown_path_is_404() {
+    let (status, headers) = get("/js/missing.js", &[]).await;
+    assert_eq!(status, 404);
+    assert_eq!(
+        header_str(&headers, header::CONTENT_TYPE),
+        "text/plain; charset=utf-8"
+    );
+}
+
+// 预压缩版本在构建时生成，每种编码有自己的 ETag，并声明 Vary
+#[actix_web::test]
+async fn serves_precompressed_variants() {
+    let (status, identity) = get("/js/app.js", &[]).await;
+    assert_eq!(status, 200);
+    assert!(identity.get(header::CONTENT_ENCODING).is_none());
+    assert_eq!(header_str(&identity, header::VARY), "Accept-Encoding");
+
+    let mut etags = vec![header_str(&identity, header::ETAG).to_string()];
+    for (accept, expected) in [
+        ("gzip", "gzip"),
+        ("gzip, br", "br"),
+        ("br;q=0, gzip", "gzip"),
+    ] {
+        let (status, headers) = get("/js/app.js", &[(header::ACCEPT_ENCODING, accept)]).await;
+        assert_eq!(status, 200);
+        assert_eq!(
+            header_str(&headers, header::CONTENT_ENCODING),
+            expected,
+            "{}",
+            accept
+        );
+        assert_eq!(header_str(&headers, header::VARY), "Accept-Encoding");
+        etags.push(header_str(&headers, header::ETAG).to_string());
+    }
+    assert_ne!(etags[0], etags[1]);
+    assert_ne!(etags[1], etags[2]);
+    assert_eq!(etags[1], etags[3]);
+
+    // gzip 的 ETag 不能让只接受原文的请求得到 304
+    let (status, _) = get("/js/app.js", &[(header::IF_NONE_MATCH, &etags[1])]).await;
+    assert_eq!(status, 200);
+    let (status, _) = get(
+        "/js/app.js",
+        &[
+            (header::IF_NONE_MATCH, &etags[1]),
+            (header::ACCEPT_ENCODING, "gzip"),
+        ],
+    )
+    .await;
+    assert_eq!(status, 304);
+
+    let app = test::init_service(App::new().configure(crate::assets::configure)).await;
+    let req = test::TestRequest::get()
+        .uri("/js/app.js")
+        .insert_header((header::ACCEPT_ENCODING, "gzip"))
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(&body[..2], b"\x1f\x8b");
+
+    // 图片不压缩
+    let (_, headers) = get("/wuxing.jpg", &[(header::ACCEPT_ENCODING, "gzip")]).await;
+    assert!(headers.get(header::CONTENT_ENCODING).is_none());
+    assert!(headers.get(header::VARY).is_none());
+}
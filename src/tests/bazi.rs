@@ -0,0 +1,32 @@
+//! 八字的测试：年柱在立春、月柱在交节的时刻更换。
+
+use crate::bazi::bazi;
+use crate::ZiShi;
+use chrono::NaiveDateTime;
+
+fn year_month(at: &str) -> (String, String) {
+    let birth = NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M").unwrap();
+    let chart = bazi(birth, ZiShi::Split, None);
+    (
+        chart.pillars[0].ganzhi.clone(),
+        chart.pillars[1].ganzhi.clone(),
+    )
+}
+
+// 2025 年春节在 1 月 29 日，立春在 2 月 3 日 22:10；春节后、立春前仍是甲辰年丑月
+#[test]
+fn year_changes_at_li_chun() {
+    for at in ["2025-02-01T10:00", "2025-02-03T22:00"] {
+        assert_eq!(year_month(at), ("甲辰".into(), "丁丑".into()), "{}", at);
+    }
+    for at in ["2025-02-03T22:30", "2025-02-04T10:00"] {
+        assert_eq!(year_month(at), ("乙巳".into(), "戊寅".into()), "{}", at);
+    }
+}
+
+// 惊蛰在 2025 年 3 月 5 日 16:07，当天交节前仍是寅月
+#[test]
+fn month_changes_at_jie() {
+    assert_eq!(year_month("2025-03-05T10:00").1, "戊寅");
+    assert_eq!(year_month("2025-03-05T18:00").1, "己卯");
+}
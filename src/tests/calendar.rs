@@ -0,0 +1,100 @@
+//! 干支历的测试：跨节换月、中气不换月、立春换年，以及晚子时用次日日干。
+
+use crate::calendar::calendar_range;
+use chrono::NaiveDate;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+// 2025 年雨水在 2 月 18 日，惊蛰在 3 月 5 日 16 时许：月柱在惊蛰当天就换为己卯
+#[test]
+fn month_changes_on_jie_day() {
+    let days = calendar_range(date("2025-02-17"), date("2025-03-06"));
+    assert_eq!(days.len(), 18);
+    let changes: Vec<NaiveDate> = days
+        .iter()
+        .filter(|day| day.month_changes)
+        .map(|day| day.date)
+        .collect();
+    assert_eq!(changes, [date("2025-03-05")]);
+
+    let yushui = &days[1];
+    let jieqi = yushui.jieqi.as_ref().unwrap();
+    assert_eq!(jieqi.name, "雨水");
+    assert!(!jieqi.is_jie);
+    assert_eq!(yushui.month_ganzhi, "戊寅");
+
+    let before = &days[15];
+    let jingzhe = &days[16];
+    assert_eq!(before.date, date("2025-03-04"));
+    assert_eq!(before.month_ganzhi, "戊寅");
+    assert!(before.jieqi.is_none());
+    assert_eq!(jingzhe.month_ganzhi, "己卯");
+    let jieqi = jingzhe.jieqi.as_ref().unwrap();
+    assert_eq!(jieqi.name, "惊蛰");
+    assert!(jieqi.is_jie);
+    assert_eq!(
+        jieqi.time.format("%Y-%m-%dT%H:%M").to_string(),
+        "2025-03-05T16:07"
+    );
+}
+
+// 范围的第一天就是交节日时，也要和前一天比较
+#[test]
+fn first_day_of_range_can_change_month() {
+    let days = calendar_range(date("2025-03-05"), date("2025-03-05"));
+    assert!(days[0].month_changes);
+}
+
+// 23 点的晚子时按五鼠遁用次日的日干，与次日早子时的时干支相同
+#[test]
+fn late_zi_hour_takes_next_day_stem() {
+    let days = calendar_range(date("2025-06-01"), date("2025-06-02"));
+    assert_eq!(days[0].day_ganzhi, "辛丑");
+    assert_eq!(days[1].day_ganzhi, "壬寅");
+    let shichen = &days[0].shichen;
+    assert_eq!(shichen.len(), 13);
+    assert_eq!((shichen[0].zhi, shichen[0].start), ("子", "00:00"));
+    // 辛日早子时为戊子，晚子时按壬日为庚子
+    assert_eq!(shichen[0].ganzhi, "戊子");
+    assert_eq!((shichen[12].zhi, shichen[12].start), ("子", "23:00"));
+    assert_eq!(shichen[12].ganzhi, "庚子");
+    assert_eq!(shichen[12].ganzhi, days[1].shichen[0].ganzhi);
+}
+
+// 2025 年春节在 1 月 29 日，立春在 2 月 3 日：春节后立春前仍是甲辰年丑月，立春当天起为乙巳年寅月
+#[test]
+fn year_changes_on_li_chun_day() {
+    let days = calendar_range(date("2025-02-01"), date("2025-02-04"));
+    let pillars: Vec<(&str, &str)> = days
+        .iter()
+        .map(|day| (day.year_ganzhi.as_str(), day.month_ganzhi.as_str()))
+        .collect();
+    assert_eq!(
+        pillars,
+        [
+            ("甲辰", "丁丑"),
+            ("甲辰", "丁丑"),
+            ("乙巳", "戊寅"),
+            ("乙巳", "戊寅"),
+        ]
+    );
+    assert_eq!(days[0].lunar.year, 2025);
+}
+
+// 六爻排盘的年柱与历法一致，也从立春当天起换
+#[test]
+fn chart_year_follows_li_chun() {
+    for (at, year, month) in [
+        ("2025-02-01T10:00", "甲辰", "丁丑"),
+        ("2025-02-03T10:00", "乙巳", "戊寅"),
+    ] {
+        let at = chrono::NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M").unwrap();
+        let chart = crate::paipan("013212", at, crate::ZiShi::Split).unwrap();
+        assert_eq!(
+            (chart.year_ganzhi.as_str(), chart.month_ganzhi.as_str()),
+            (year, month)
+        );
+    }
+}
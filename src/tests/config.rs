@@ -0,0 +1,157 @@
+//! 配置加载的测试：配置文件、环境变量、命令行参数的优先级和取值校验。
+
+use crate::config::{Config, ConfigArgs, ConfigError};
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct Cli {
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+fn load(args: &[&str]) -> Result<Config, ConfigError> {
+    let cli = Cli::try_parse_from(std::iter::once("sixsixsix").chain(args.iter().copied()))
+        .expect("命令行参数格式正确");
+    Config::load(cli.config)
+}
+
+// 每个测试用自己的配置文件，测试并行时互不影响
+fn config_file(name: &str, text: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sixsixsix-{}-{}.toml", name, std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+// 环境变量是进程级的，只有这一个测试设置 SIXSIXSIX_* 变量
+#[test]
+fn flag_overrides_env_overrides_file() {
+    let path = config_file(
+        "precedence",
+        "port = 7000\nlog_level = \"warn\"\nworkers = 3\n\n\
+         [llm]\nendpoint = \"https://llm.example.com/v1\"\nmodel = \"file-model\"\ntimeout_secs = 30\n",
+    );
+    std::env::set_var("SIXSIXSIX_PORT", "8000");
+    std::env::set_var("SIXSIXSIX_LOG_LEVEL", "debug");
+    std::env::set_var("SIXSIXSIX_LLM_MODEL", "env-model");
+    let config = load(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--port",
+        "9000",
+        "--llm-timeout-secs",
+        "90",
+    ]);
+    let env_only = load(&["--config", path.to_str().unwrap()]);
+    std::env::remove_var("SIXSIXSIX_PORT");
+    std::env::remove_var("SIXSIXSIX_LOG_LEVEL");
+    std::env::remove_var("SIXSIXSIX_LLM_MODEL");
+    let file_only = load(&["--config", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+
+    let config = config.unwrap();
+    assert_eq!(config.port, 9000);
+    assert_eq!(config.log_level, "debug");
+    assert_eq!(config.workers, Some(3));
+    assert_eq!(config.llm.timeout_secs, 90);
+    assert_eq!(config.llm.model.as_deref(), Some("env-model"));
+
+    let env_only = env_only.unwrap();
+    assert_eq!(env_only.port, 8000);
+    assert_eq!(env_only.log_level, "debug");
+    assert_eq!(env_only.llm.timeout_secs, 30);
+
+    let file_only = file_only.unwrap();
+    assert_eq!(file_only.port, 7000);
+    assert_eq!(file_only.log_level, "warn");
+    assert_eq!(
+        file_only.llm.endpoint.as_deref(),
+        Some("https://llm.example.com/v1")
+    );
+    assert_eq!(file_only.llm.model.as_deref(), Some("file-model"));
+    // 没有给出的项沿用默认值
+    assert_eq!(file_only.database, Config::default().database);
+}
+
+#[test]
+fn timezone_must_be_iana_name() {
+    let config = load(&["--timezone", "Asia/Shanghai"]).unwrap();
+    assert_eq!(config.timezone, Some(chrono_tz::Asia::Shanghai));
+
+    for args in [
+        &["--timezone", "Beijing"][..],
+        &["--timezone", "+08:00"][..],
+    ] {
+        match load(args) {
+            Err(ConfigError::Invalid { field, value, .. }) => {
+                assert_eq!(field, "timezone");
+                assert_eq!(value, args[1]);
+            }
+            other => panic!("{:?} 应报时区无效，实际为 {:?}", args, other.map(|_| ())),
+        }
+    }
+
+    let path = config_file("timezone", "timezone = \"Mars/Olympus\"\n");
+    let from_file = load(&["--config", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        from_file,
+        Err(ConfigError::Invalid {
+            field: "timezone",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    for (args, field) in [
+        (&["--port", "0"][..], "port"),
+        (&["--workers", "0"][..], "workers"),
+        (&["--bind", "localhost"][..], "bind"),
+        (&["--log-level", "sixsixsix=loud"][..], "log_level"),
+        (&["--llm-endpoint", "llm.example.com"][..], "llm.endpoint"),
+        (&["--llm-model", " "][..], "llm.model"),
+        (&["--llm-api-key-env", "LLM-KEY"][..], "llm.api_key_env"),
+        (&["--llm-timeout-secs", "0"][..], "llm.timeout_secs"),
+    ] {
+        match load(args) {
+            Err(ConfigError::Invalid { field: actual, .. }) => assert_eq!(actual, field),
+            other => panic!(
+                "{:?} 应报 {} 无效，实际为 {:?}",
+                args,
+                field,
+                other.map(|_| ())
+            ),
+        }
+    }
+    let path = config_file("unknown", "prot = 8080\n");
+    let typo = load(&["--config", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(typo, Err(ConfigError::Parse(..))));
+}
+
+// 日志级别与 RUST_LOG 的写法相同，可以按模块分别设置
+#[test]
+fn log_level_accepts_env_logger_directives() {
+    for level in ["info", "WARN", "sixsixsix=debug,actix_web=warn", "off"] {
+        assert_eq!(load(&["--log-level", level]).unwrap().log_level, level);
+    }
+}
+
+// 配置里只有密钥所在的环境变量名，不含密钥本身
+#[test]
+fn llm_defaults() {
+    let config = load(&["--llm-api-key-env", "OPENAI_API_KEY"]).unwrap();
+    assert_eq!(config.llm.api_key_env, "OPENAI_API_KEY");
+    assert_eq!(config.llm.endpoint, None);
+    assert_eq!(Config::default().llm.api_key_env, "SIXSIXSIX_LLM_API_KEY");
+    assert_eq!(Config::default().llm.timeout_secs, 60);
+}
+
+#[test]
+fn debug_output_does_not_read_the_clock() {
+    let config = Config::default();
+    assert_eq!(format!("{:?}", config), format!("{:?}", config));
+    assert!(format!("{:?}", config).contains("clock: Clock"));
+}
@@ -0,0 +1,137 @@
+//! 4096 种输入的枚举统计，数值可以用组合计数直接核对。
+
+use crate::enumeration::{self, Frequency};
+use chrono::NaiveDate;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+fn assert_sums_to(frequencies: impl Iterator<Item = Frequency>, count: u64, probability: f64) {
+    let (mut total, mut coin, mut yarrow) = (0, 0.0, 0.0);
+    for f in frequencies {
+        total += f.count;
+        coin += f.coin;
+        yarrow += f.yarrow;
+    }
+    assert_eq!(total, count);
+    assert_close(coin, probability);
+    assert_close(yarrow, probability);
+}
+
+#[test]
+fn line_and_changing_probabilities() {
+    let report = enumeration::enumerate(None);
+    assert_eq!(report.inputs, 4096);
+    assert_close(report.lines.iter().map(|l| l.coin).sum(), 1.0);
+    assert_close(report.lines.iter().map(|l| l.yarrow).sum(), 1.0);
+
+    // 两种起卦法得动爻的概率都是 1/4，动爻个数服从 B(6, 1/4)
+    let counts: Vec<u64> = report
+        .changing_lines
+        .iter()
+        .map(|g| g.frequency.count)
+        .collect();
+    assert_eq!(counts, [64, 384, 960, 1280, 960, 384, 64]);
+    let static_gua = &report.changing_lines[0].frequency;
+    assert_close(static_gua.coin, 0.75f64.powi(6));
+    assert_close(static_gua.yarrow, 0.75f64.powi(6));
+}
+
+#[test]
+fn palaces_of_ben_gua_are_uniform_but_not_of_bian_gua() {
+    let report = enumeration::enumerate(None);
+    // 两种起卦法得阳爻的概率都是 1/2，本卦在八宫中均匀分布
+    for group in &report.palaces {
+        assert_eq!(group.frequency.count, 512, "{}", group.key);
+        assert_close(group.frequency.coin, 0.125);
+        assert_close(group.frequency.yarrow, 0.125);
+    }
+    let moving = 1.0 - 0.75f64.powi(6);
+    assert_sums_to(
+        report.bian_palaces.iter().map(|g| g.frequency),
+        4096 - 64,
+        moving,
+    );
+    // 揲蓍老阳多于老阴，之卦偏向阴爻，坤宫多于乾宫
+    let yarrow = |name: &str| {
+        report
+            .bian_palaces
+            .iter()
+            .find(|g| g.key == name)
+            .unwrap()
+            .frequency
+            .yarrow
+    };
+    assert!(yarrow("坤") > yarrow("乾"));
+}
+
+#[test]
+fn chong_he_transitions_cover_every_moving_input() {
+    let report = enumeration::enumerate(None);
+    assert_eq!(report.chong_he.len(), 9);
+    let moving = 1.0 - 0.75f64.powi(6);
+    assert_sums_to(report.chong_he.iter().map(|t| t.frequency), 4032, moving);
+    let count = |from: &str, to: &str| {
+        report
+            .chong_he
+            .iter()
+            .find(|t| t.from == from && t.to == to)
+            .unwrap()
+            .frequency
+            .count
+    };
+    // 冲合转换是对称的：A 变 B 与 B 变 A 的输入一一对应（动爻阴阳互换）
+    assert_eq!(count("六冲", "六合"), count("六合", "六冲"));
+}
+
+#[test]
+fn missing_liuqin_lists_hexagrams_needing_fu_shen() {
+    let report = enumeration::enumerate(None);
+    for missing in &report.missing_liuqin {
+        // 缺不缺六亲只看本卦，每卦对应 64 种输入
+        assert_eq!(missing.frequency.count, missing.hexagrams.len() as u64 * 64);
+        assert!(!missing.hexagrams.contains(&"乾为天"), "{}", missing.liuqin);
+    }
+    // 天风姤：丑亥酉午申戌，乾宫属金，缺木为妻财
+    let qi_cai = report
+        .missing_liuqin
+        .iter()
+        .find(|m| m.liuqin == "妻财")
+        .unwrap();
+    assert!(qi_cai.hexagrams.contains(&"天风姤"));
+}
+
+#[test]
+fn fan_yin_and_fu_yin_match_combinatorial_counts() {
+    let report = enumeration::enumerate(None);
+    // 内外卦各有 8 种输入变为相对之卦，两半都不反吟的有 56×56 种
+    assert_eq!(report.fan_yin.gua.count, 4096 - 56 * 56);
+    // 伏吟只有乾震互变，内外卦各 2 种输入
+    assert_eq!(report.fu_yin.count, 4096 - 62 * 62);
+    assert_close(
+        report.fu_yin.coin,
+        1.0 - (1.0 - 2.0 * 3.0 / 512.0f64).powi(2),
+    );
+    assert!(report.fan_yin.yao.count > 0);
+    assert!(report.rules.is_none());
+}
+
+#[test]
+fn rules_over_date_range_weight_each_day() {
+    let day = NaiveDate::from_ymd_opt(2024, 6, 29).unwrap();
+    let report = enumeration::enumerate(Some((day, day)));
+    let rules = report.rules.unwrap();
+    assert_eq!(rules.days, 1);
+    let rule = |key: &str| rules.rules.iter().find(|g| g.key == key).unwrap().frequency;
+    // 静卦、动卦与日期无关，概率即有无动爻的概率
+    assert_eq!(rule("静卦").count, 64);
+    assert_close(rule("静卦").coin, 0.75f64.powi(6));
+    assert_eq!(rule("动卦").count + rule("静卦").count, 4096);
+    assert!(rule("世爻旬空").count > 0);
+}
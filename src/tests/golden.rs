@@ -0,0 +1,923 @@
+//! 六十四卦黄金表测试。
+//!
+//! 参考表按京房八宫、纳甲法独立整理，不从 SIXTYFOURGUA_DATA 推导：
+//! 每行依次为卦的数字索引（初爻到上爻，1 阳 2 阴）、文王卦序、卦名、所属宫、宫五行、世爻位置、
+//! 初爻到上爻的六亲地支五行，以及六冲、六合标记。
+
+use crate::{
+    build_guas,
+    gua_table::{lookup, GuaCode},
+    hexagram::{Hexagram, Trigram},
+    WuXing, Yao, YaoRole, SIXTYFOURGUA_DATA,
+};
+
+type Row = (
+    &'static str,
+    u32,
+    &'static str,
+    &'static str,
+    &'static str,
+    usize,
+    &'static str,
+    &'static str,
+);
+
+const REFERENCE: [Row; 64] = [
+    (
+        "111111",
+        1,
+        "乾为天",
+        "乾",
+        "金",
+        6,
+        "子孙子水 妻财寅木 父母辰土 官鬼午火 兄弟申金 父母戌土",
+        "六冲",
+    ),
+    (
+        "222222",
+        2,
+        "坤为地",
+        "坤",
+        "土",
+        6,
+        "兄弟未土 父母巳火 官鬼卯木 兄弟丑土 妻财亥水 子孙酉金",
+        "六冲",
+    ),
+    (
+        "122212",
+        3,
+        "水雷屯",
+        "坎",
+        "水",
+        2,
+        "兄弟子水 子孙寅木 官鬼辰土 父母申金 官鬼戌土 兄弟子水",
+        "",
+    ),
+    (
+        "212221",
+        4,
+        "山水蒙",
+        "离",
+        "火",
+        4,
+        "父母寅木 子孙辰土 兄弟午火 子孙戌土 官鬼子水 父母寅木",
+        "",
+    ),
+    (
+        "111212",
+        5,
+        "水天需",
+        "坤",
+        "土",
+        4,
+        "妻财子水 官鬼寅木 兄弟辰土 子孙申金 兄弟戌土 妻财子水",
+        "",
+    ),
+    (
+        "212111",
+        6,
+        "天水讼",
+        "离",
+        "火",
+        4,
+        "父母寅木 子孙辰土 兄弟午火 兄弟午火 妻财申金 子孙戌土",
+        "",
+    ),
+    (
+        "212222",
+        7,
+        "地水师",
+        "坎",
+        "水",
+        3,
+        "子孙寅木 官鬼辰土 妻财午火 官鬼丑土 兄弟亥水 父母酉金",
+        "",
+    ),
+    (
+        "222212",
+        8,
+        "水地比",
+        "坤",
+        "土",
+        3,
+        "兄弟未土 父母巳火 官鬼卯木 子孙申金 兄弟戌土 妻财子水",
+        "",
+    ),
+    (
+        "111211",
+        9,
+        "风天小畜",
+        "巽",
+        "木",
+        1,
+        "父母子水 兄弟寅木 妻财辰土 妻财未土 子孙巳火 兄弟卯木",
+        "",
+    ),
+    (
+        "112111",
+        10,
+        "天泽履",
+        "艮",
+        "土",
+        5,
+        "父母巳火 官鬼卯木 兄弟丑土 父母午火 子孙申金 兄弟戌土",
+        "",
+    ),
+    (
+        "111222",
+        11,
+        "地天泰",
+        "坤",
+        "土",
+        3,
+        "妻财子水 官鬼寅木 兄弟辰土 兄弟丑土 妻财亥水 子孙酉金",
+        "六合",
+    ),
+    (
+        "222111",
+        12,
+        "天地否",
+        "乾",
+        "金",
+        3,
+        "父母未土 官鬼巳火 妻财卯木 官鬼午火 兄弟申金 父母戌土",
+        "六合",
+    ),
+    (
+        "121111",
+        13,
+        "天火同人",
+        "离",
+        "火",
+        3,
+        "父母卯木 子孙丑土 官鬼亥水 兄弟午火 妻财申金 子孙戌土",
+        "",
+    ),
+    (
+        "111121",
+        14,
+        "火天大有",
+        "乾",
+        "金",
+        3,
+        "子孙子水 妻财寅木 父母辰土 兄弟酉金 父母未土 官鬼巳火",
+        "",
+    ),
+    (
+        "221222",
+        15,
+        "地山谦",
+        "兑",
+        "金",
+        5,
+        "父母辰土 官鬼午火 兄弟申金 父母丑土 子孙亥水 兄弟酉金",
+        "",
+    ),
+    (
+        "222122",
+        16,
+        "雷地豫",
+        "震",
+        "木",
+        1,
+        "妻财未土 子孙巳火 兄弟卯木 子孙午火 官鬼申金 妻财戌土",
+        "六合",
+    ),
+    (
+        "122112",
+        17,
+        "泽雷随",
+        "震",
+        "木",
+        3,
+        "父母子水 兄弟寅木 妻财辰土 父母亥水 官鬼酉金 妻财未土",
+        "",
+    ),
+    (
+        "211221",
+        18,
+        "山风蛊",
+        "巽",
+        "木",
+        3,
+        "妻财丑土 父母亥水 官鬼酉金 妻财戌土 父母子水 兄弟寅木",
+        "",
+    ),
+    (
+        "112222",
+        19,
+        "地泽临",
+        "坤",
+        "土",
+        2,
+        "父母巳火 官鬼卯木 兄弟丑土 兄弟丑土 妻财亥水 子孙酉金",
+        "",
+    ),
+    (
+        "222211",
+        20,
+        "风地观",
+        "乾",
+        "金",
+        4,
+        "父母未土 官鬼巳火 妻财卯木 父母未土 官鬼巳火 妻财卯木",
+        "",
+    ),
+    (
+        "122121",
+        21,
+        "火雷噬嗑",
+        "巽",
+        "木",
+        5,
+        "父母子水 兄弟寅木 妻财辰土 官鬼酉金 妻财未土 子孙巳火",
+        "",
+    ),
+    (
+        "121221",
+        22,
+        "山火贲",
+        "艮",
+        "土",
+        1,
+        "官鬼卯木 兄弟丑土 妻财亥水 兄弟戌土 妻财子水 官鬼寅木",
+        "六合",
+    ),
+    (
+        "222221",
+        23,
+        "山地剥",
+        "乾",
+        "金",
+        5,
+        "父母未土 官鬼巳火 妻财卯木 父母戌土 子孙子水 妻财寅木",
+        "",
+    ),
+    (
+        "122222",
+        24,
+        "地雷复",
+        "坤",
+        "土",
+        1,
+        "妻财子水 官鬼寅木 兄弟辰土 兄弟丑土 妻财亥水 子孙酉金",
+        "六合",
+    ),
+    (
+        "122111",
+        25,
+        "天雷无妄",
+        "巽",
+        "木",
+        4,
+        "父母子水 兄弟寅木 妻财辰土 子孙午火 官鬼申金 妻财戌土",
+        "六冲",
+    ),
+    (
+        "111221",
+        26,
+        "山天大畜",
+        "艮",
+        "土",
+        2,
+        "妻财子水 官鬼寅木 兄弟辰土 兄弟戌土 妻财子水 官鬼寅木",
+        "",
+    ),
+    (
+        "122221",
+        27,
+        "山雷颐",
+        "巽",
+        "木",
+        4,
+        "父母子水 兄弟寅木 妻财辰土 妻财戌土 父母子水 兄弟寅木",
+        "",
+    ),
+    (
+        "211112",
+        28,
+        "泽风大过",
+        "震",
+        "木",
+        4,
+        "妻财丑土 父母亥水 官鬼酉金 父母亥水 官鬼酉金 妻财未土",
+        "",
+    ),
+    (
+        "212212",
+        29,
+        "坎为水",
+        "坎",
+        "水",
+        6,
+        "子孙寅木 官鬼辰土 妻财午火 父母申金 官鬼戌土 兄弟子水",
+        "六冲",
+    ),
+    (
+        "121121",
+        30,
+        "离为火",
+        "离",
+        "火",
+        6,
+        "父母卯木 子孙丑土 官鬼亥水 妻财酉金 子孙未土 兄弟巳火",
+        "六冲",
+    ),
+    (
+        "221112",
+        31,
+        "泽山咸",
+        "兑",
+        "金",
+        3,
+        "父母辰土 官鬼午火 兄弟申金 子孙亥水 兄弟酉金 父母未土",
+        "",
+    ),
+    (
+        "211122",
+        32,
+        "雷风恒",
+        "震",
+        "木",
+        3,
+        "妻财丑土 父母亥水 官鬼酉金 子孙午火 官鬼申金 妻财戌土",
+        "",
+    ),
+    (
+        "221111",
+        33,
+        "天山遁",
+        "乾",
+        "金",
+        2,
+        "父母辰土 官鬼午火 兄弟申金 官鬼午火 兄弟申金 父母戌土",
+        "",
+    ),
+    (
+        "111122",
+        34,
+        "雷天大壮",
+        "坤",
+        "土",
+        4,
+        "妻财子水 官鬼寅木 兄弟辰土 父母午火 子孙申金 兄弟戌土",
+        "六冲",
+    ),
+    (
+        "222121",
+        35,
+        "火地晋",
+        "乾",
+        "金",
+        4,
+        "父母未土 官鬼巳火 妻财卯木 兄弟酉金 父母未土 官鬼巳火",
+        "",
+    ),
+    (
+        "121222",
+        36,
+        "地火明夷",
+        "坎",
+        "水",
+        4,
+        "子孙卯木 官鬼丑土 兄弟亥水 官鬼丑土 兄弟亥水 父母酉金",
+        "",
+    ),
+    (
+        "121211",
+        37,
+        "风火家人",
+        "巽",
+        "木",
+        2,
+        "兄弟卯木 妻财丑土 父母亥水 妻财未土 子孙巳火 兄弟卯木",
+        "",
+    ),
+    (
+        "112121",
+        38,
+        "火泽睽",
+        "艮",
+        "土",
+        4,
+        "父母巳火 官鬼卯木 兄弟丑土 子孙酉金 兄弟未土 父母巳火",
+        "",
+    ),
+    (
+        "221212",
+        39,
+        "水山蹇",
+        "兑",
+        "金",
+        4,
+        "父母辰土 官鬼午火 兄弟申金 兄弟申金 父母戌土 子孙子水",
+        "",
+    ),
+    (
+        "212122",
+        40,
+        "雷水解",
+        "震",
+        "木",
+        2,
+        "兄弟寅木 妻财辰土 子孙午火 子孙午火 官鬼申金 妻财戌土",
+        "",
+    ),
+    (
+        "112221",
+        41,
+        "山泽损",
+        "艮",
+        "土",
+        3,
+        "父母巳火 官鬼卯木 兄弟丑土 兄弟戌土 妻财子水 官鬼寅木",
+        "",
+    ),
+    (
+        "122211",
+        42,
+        "风雷益",
+        "巽",
+        "木",
+        3,
+        "父母子水 兄弟寅木 妻财辰土 妻财未土 子孙巳火 兄弟卯木",
+        "",
+    ),
+    (
+        "111112",
+        43,
+        "泽天夬",
+        "坤",
+        "土",
+        5,
+        "妻财子水 官鬼寅木 兄弟辰土 妻财亥水 子孙酉金 兄弟未土",
+        "",
+    ),
+    (
+        "211111",
+        44,
+        "天风姤",
+        "乾",
+        "金",
+        1,
+        "父母丑土 子孙亥水 兄弟酉金 官鬼午火 兄弟申金 父母戌土",
+        "",
+    ),
+    (
+        "222112",
+        45,
+        "泽地萃",
+        "兑",
+        "金",
+        2,
+        "父母未土 官鬼巳火 妻财卯木 子孙亥水 兄弟酉金 父母未土",
+        "",
+    ),
+    (
+        "211222",
+        46,
+        "地风升",
+        "震",
+        "木",
+        4,
+        "妻财丑土 父母亥水 官鬼酉金 妻财丑土 父母亥水 官鬼酉金",
+        "",
+    ),
+    (
+        "212112",
+        47,
+        "泽水困",
+        "兑",
+        "金",
+        1,
+        "妻财寅木 父母辰土 官鬼午火 子孙亥水 兄弟酉金 父母未土",
+        "六合",
+    ),
+    (
+        "211212",
+        48,
+        "水风井",
+        "震",
+        "木",
+        5,
+        "妻财丑土 父母亥水 官鬼酉金 官鬼申金 妻财戌土 父母子水",
+        "",
+    ),
+    (
+        "121112",
+        49,
+        "泽火革",
+        "坎",
+        "水",
+        4,
+        "子孙卯木 官鬼丑土 兄弟亥水 兄弟亥水 父母酉金 官鬼未土",
+        "",
+    ),
+    (
+        "211121",
+        50,
+        "火风鼎",
+        "离",
+        "火",
+        2,
+        "子孙丑土 官鬼亥水 妻财酉金 妻财酉金 子孙未土 兄弟巳火",
+        "",
+    ),
+    (
+        "122122",
+        51,
+        "震为雷",
+        "震",
+        "木",
+        6,
+        "父母子水 兄弟寅木 妻财辰土 子孙午火 官鬼申金 妻财戌土",
+        "六冲",
+    ),
+    (
+        "221221",
+        52,
+        "艮为山",
+        "艮",
+        "土",
+        6,
+        "兄弟辰土 父母午火 子孙申金 兄弟戌土 妻财子水 官鬼寅木",
+        "六冲",
+    ),
+    (
+        "221211",
+        53,
+        "风山渐",
+        "艮",
+        "土",
+        3,
+        "兄弟辰土 父母午火 子孙申金 兄弟未土 父母巳火 官鬼卯木",
+        "",
+    ),
+    (
+        "112122",
+        54,
+        "雷泽归妹",
+        "兑",
+        "金",
+        3,
+        "官鬼巳火 妻财卯木 父母丑土 官鬼午火 兄弟申金 父母戌土",
+        "",
+    ),
+    (
+        "121122",
+        55,
+        "雷火丰",
+        "坎",
+        "水",
+        5,
+        "子孙卯木 官鬼丑土 兄弟亥水 妻财午火 父母申金 官鬼戌土",
+        "",
+    ),
+    (
+        "221121",
+        56,
+        "火山旅",
+        "离",
+        "火",
+        1,
+        "子孙辰土 兄弟午火 妻财申金 妻财酉金 子孙未土 兄弟巳火",
+        "六合",
+    ),
+    (
+        "211211",
+        57,
+        "巽为风",
+        "巽",
+        "木",
+        6,
+        "妻财丑土 父母亥水 官鬼酉金 妻财未土 子孙巳火 兄弟卯木",
+        "六冲",
+    ),
+    (
+        "112112",
+        58,
+        "兑为泽",
+        "兑",
+        "金",
+        6,
+        "官鬼巳火 妻财卯木 父母丑土 子孙亥水 兄弟酉金 父母未土",
+        "六冲",
+    ),
+    (
+        "212211",
+        59,
+        "风水涣",
+        "离",
+        "火",
+        5,
+        "父母寅木 子孙辰土 兄弟午火 子孙未土 兄弟巳火 父母卯木",
+        "",
+    ),
+    (
+        "112212",
+        60,
+        "水泽节",
+        "坎",
+        "水",
+        1,
+        "妻财巳火 子孙卯木 官鬼丑土 父母申金 官鬼戌土 兄弟子水",
+        "六合",
+    ),
+    (
+        "112211",
+        61,
+        "风泽中孚",
+        "艮",
+        "土",
+        4,
+        "父母巳火 官鬼卯木 兄弟丑土 兄弟未土 父母巳火 官鬼卯木",
+        "",
+    ),
+    (
+        "221122",
+        62,
+        "雷山小过",
+        "兑",
+        "金",
+        4,
+        "父母辰土 官鬼午火 兄弟申金 官鬼午火 兄弟申金 父母戌土",
+        "",
+    ),
+    (
+        "121212",
+        63,
+        "水火既济",
+        "坎",
+        "水",
+        3,
+        "子孙卯木 官鬼丑土 兄弟亥水 父母申金 官鬼戌土 兄弟子水",
+        "",
+    ),
+    (
+        "212121",
+        64,
+        "火水未济",
+        "离",
+        "火",
+        3,
+        "父母寅木 子孙辰土 兄弟午火 妻财酉金 子孙未土 兄弟巳火",
+        "",
+    ),
+];
+
+// 八宫本卦的三爻索引
+const PALACE_TRIGRAMS: [(&str, &str); 8] = [
+    ("乾", "111"),
+    ("兑", "112"),
+    ("离", "121"),
+    ("震", "122"),
+    ("巽", "211"),
+    ("坎", "212"),
+    ("艮", "221"),
+    ("坤", "222"),
+];
+
+// 把 "112212" 这样的卦索引转成 6 位编码，初爻为最低位
+fn code(index: &str) -> GuaCode {
+    index
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| *c == '1')
+        .fold(0, |code, (i, _)| code | 1 << i)
+}
+
+// 把 "112212" 这样的卦索引转成静爻的起卦数字
+fn static_numbers(index: &str) -> String {
+    index
+        .chars()
+        .map(|c| if c == '1' { '1' } else { '2' })
+        .collect()
+}
+
+#[test]
+fn reference_covers_every_hexagram_once() {
+    let mut indexes: Vec<&str> = REFERENCE.iter().map(|row| row.0).collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    assert_eq!(indexes.len(), 64);
+    for (i, row) in REFERENCE.iter().enumerate() {
+        assert_eq!(row.1 as usize, i + 1, "{} 的卦序", row.2);
+    }
+}
+
+#[test]
+fn palace_tables_match_reference() {
+    let mut listed: Vec<&str> = SIXTYFOURGUA_DATA
+        .iter()
+        .flat_map(|palace| palace.gua_index)
+        .collect();
+    listed.sort_unstable();
+    listed.dedup();
+    assert_eq!(listed.len(), 64, "八宫表应恰好列出六十四卦");
+
+    for (index, number, name, palace, element, _, _, tag) in REFERENCE {
+        let trigram = PALACE_TRIGRAMS
+            .iter()
+            .find(|(p, _)| *p == palace)
+            .map(|(_, t)| *t)
+            .unwrap();
+        let data = SIXTYFOURGUA_DATA
+            .iter()
+            .find(|data| data.gua_index.contains(&index))
+            .unwrap();
+        assert_eq!(data.index, trigram, "{} 应属{}宫", name, palace);
+        let info = lookup(code(index));
+        assert_eq!(
+            info.palace_element().to_string(),
+            element,
+            "{} 的宫五行",
+            name
+        );
+        assert_eq!(SIXTYFOURGUA_DATA[info.palace].index, trigram);
+
+        // 卦名后紧跟 Unicode 卦符（U+4DC0 起按文王卦序排列），再跟六冲、六合标记
+        let symbol = char::from_u32(0x4DC0 + number - 1).unwrap();
+        let expected = if tag.is_empty() {
+            format!("{}{}", name, symbol)
+        } else {
+            format!("{}{}({})", name, symbol, tag)
+        };
+        assert_eq!(info.name, expected);
+    }
+}
+
+#[test]
+fn lines_match_reference() {
+    for (index, _, name, _, _, shi, lines, _) in REFERENCE {
+        let (gua, _) = build_guas(&static_numbers(index)).unwrap();
+        assert_eq!(gua.code, code(index));
+        let actual: Vec<String> = (0..6)
+            .map(|i| format!("{}{}{}", gua.liuqin[i], gua.dizhi[i], gua.wuxing[i]))
+            .collect();
+        assert_eq!(actual.join(" "), lines, "{} 的六亲纳甲", name);
+        for i in 0..6 {
+            assert_eq!(gua.wuxing[i], gua.dizhi[i].wuxing());
+        }
+
+        // 世应相隔三爻
+        let ying = if shi > 3 { shi - 3 } else { shi + 3 };
+        for (i, role) in gua.yao_roles.iter().enumerate() {
+            let expected = match i + 1 {
+                line if line == shi => YaoRole::Shi,
+                line if line == ying => YaoRole::Ying,
+                _ => YaoRole::Normal,
+            };
+            assert_eq!(*role, expected, "{} 第{}爻", name, i + 1);
+        }
+    }
+}
+
+// 纳甲天干：乾内甲外壬，坤内乙外癸，其余六卦内外同干
+#[test]
+fn najia_tiangan_matches_reference() {
+    for (index, expected) in [
+        ("111111", "甲甲甲壬壬壬"),
+        ("222222", "乙乙乙癸癸癸"),
+        ("122122", "庚庚庚庚庚庚"),
+        ("211211", "辛辛辛辛辛辛"),
+        ("212212", "戊戊戊戊戊戊"),
+        ("121121", "己己己己己己"),
+        ("221221", "丙丙丙丙丙丙"),
+        ("112112", "丁丁丁丁丁丁"),
+        ("222111", "乙乙乙壬壬壬"),
+        ("111222", "甲甲甲癸癸癸"),
+    ] {
+        let (gua, _) = build_guas(&static_numbers(index)).unwrap();
+        let actual: String = gua.tiangan.iter().map(|gan| gan.to_string()).collect();
+        assert_eq!(actual, expected, "{}", index);
+    }
+}
+
+#[test]
+fn to_bian_yao_flips_only_changing_lines() {
+    for yao in [
+        Yao::YinChanging,
+        Yao::YangStatic,
+        Yao::YinStatic,
+        Yao::YangChanging,
+    ] {
+        let bian = yao.to_bian_yao();
+        let changing = matches!(yao, Yao::YinChanging | Yao::YangChanging);
+        assert_eq!(bian.is_yang() != yao.is_yang(), changing);
+        assert!(matches!(bian, Yao::YangStatic | Yao::YinStatic));
+        assert_eq!(bian.to_bian_yao(), bian);
+    }
+}
+
+// 六亲按五行相生顺序的距离：同我兄弟、我生子孙、我克妻财、克我官鬼、生我父母
+fn liuqin(palace: WuXing, wuxing: WuXing) -> &'static str {
+    const ORDER: [WuXing; 5] = [
+        WuXing::Mu,
+        WuXing::Huo,
+        WuXing::Tu,
+        WuXing::Jin,
+        WuXing::Shui,
+    ];
+    let position = |w| ORDER.iter().position(|&o| o == w).unwrap();
+    ["兄弟", "子孙", "妻财", "官鬼", "父母"][(position(wuxing) + 5 - position(palace)) % 5]
+}
+
+// 4^6 种起卦结果逐一检查：变卦只翻动爻，纳甲与直接排出的同名卦一致，六亲仍按正卦的宫五行
+#[test]
+fn bian_gua_is_consistent_for_every_cast() {
+    for n in 0..4096u32 {
+        let numbers: String = (0..6)
+            .map(|i| char::from_digit((n >> (2 * i)) & 3, 4).unwrap())
+            .collect();
+        let (zheng, bian) = build_guas(&numbers).unwrap();
+
+        let expected_index: String = numbers
+            .chars()
+            .map(|c| if matches!(c, '0' | '1') { '1' } else { '2' })
+            .collect();
+        assert_eq!(bian.code, code(&expected_index), "{}", numbers);
+        for (i, c) in numbers.chars().enumerate() {
+            assert_eq!(bian.yao_xiang[i], Yao::from(c).to_bian_yao(), "{}", numbers);
+        }
+        assert_ne!(zheng.palace_name, "未知卦", "{}", numbers);
+        assert_ne!(bian.palace_name, "未知卦", "{}", numbers);
+        assert_eq!(bian.yao_roles, [YaoRole::Normal; 6], "{}", numbers);
+
+        let (direct, _) = build_guas(&static_numbers(&expected_index)).unwrap();
+        assert_eq!(bian.tiangan, direct.tiangan, "{}", numbers);
+        assert_eq!(bian.dizhi, direct.dizhi, "{}", numbers);
+        assert_eq!(bian.wuxing, direct.wuxing, "{}", numbers);
+        assert_eq!(bian.palace_name, direct.palace_name, "{}", numbers);
+
+        let palace = lookup(zheng.code).palace_element();
+        for i in 0..6 {
+            assert_eq!(
+                bian.liuqin[i].to_string(),
+                liuqin(palace, bian.wuxing[i]),
+                "{} 第{}爻",
+                numbers,
+                i + 1
+            );
+        }
+    }
+}
+
+#[test]
+fn hexagram_numbering_matches_reference() {
+    for (index, number, name, palace, _, _, _, _) in REFERENCE {
+        let hexagram: Hexagram = index.parse().unwrap();
+        assert_eq!(hexagram.code(), code(index));
+        assert_eq!(hexagram.index(), index);
+        assert_eq!(hexagram.name(), name);
+        assert_eq!(hexagram.king_wen(), number);
+        assert_eq!(Hexagram::from_king_wen(number).unwrap(), hexagram);
+        assert_eq!(hexagram.symbol() as u32, 0x4DC0 + number - 1);
+        assert_eq!(Hexagram::from_symbol(hexagram.symbol()).unwrap(), hexagram);
+        assert_eq!(Hexagram::from_fu_xi(hexagram.fu_xi()).unwrap(), hexagram);
+        assert_eq!(hexagram.palace().name(), palace);
+
+        // 卦名前两字为上下卦之象，八纯卦为 "某为某"
+        let images: Vec<char> = name.chars().collect();
+        let (upper, lower) = if images[1] == '为' {
+            (images[2], images[2])
+        } else {
+            (images[0], images[1])
+        };
+        assert_eq!(hexagram.upper().image(), upper.to_string(), "{}", name);
+        assert_eq!(hexagram.lower().image(), lower.to_string(), "{}", name);
+    }
+    assert!(Hexagram::from_king_wen(0).is_err());
+    assert!(Hexagram::from_king_wen(65).is_err());
+    assert!("1211".parse::<Hexagram>().is_err());
+}
+
+// 伏羲卦序：乾、夬、大有、大壮……剥、坤
+#[test]
+fn fu_xi_order_starts_and_ends_as_in_xian_tian_diagram() {
+    let names: Vec<&str> = (1..=64)
+        .map(|n| Hexagram::from_fu_xi(n).unwrap().name())
+        .collect();
+    assert_eq!(names[..4], ["乾为天", "泽天夬", "火天大有", "雷天大壮"]);
+    assert_eq!(names[60..], ["风地观", "水地比", "山地剥", "坤为地"]);
+}
+
+#[test]
+fn trigram_symbols_and_families() {
+    let expected = [
+        ("乾", '☰', "父", "金"),
+        ("兑", '☱', "少女", "金"),
+        ("离", '☲', "中女", "火"),
+        ("震", '☳', "长男", "木"),
+        ("巽", '☴', "长女", "木"),
+        ("坎", '☵', "中男", "水"),
+        ("艮", '☶', "少男", "土"),
+        ("坤", '☷', "母", "土"),
+    ];
+    for (trigram, (name, symbol, family, element)) in Trigram::ALL.into_iter().zip(expected) {
+        assert_eq!(trigram.name(), name);
+        assert_eq!(trigram.symbol(), symbol);
+        assert_eq!(trigram.family(), family);
+        assert_eq!(trigram.element().to_string(), element);
+        assert_eq!(Trigram::from_code(trigram.code()), trigram);
+    }
+}
@@ -0,0 +1,716 @@
+//! 排盘接口的集成测试，用固定时钟使输出可以逐字断言。
+
+use crate::config::{Clock, Config};
+use crate::storage::Storage;
+use actix_web::{test, web, App};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Asia::Shanghai;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::Arc;
+
+struct FixedClock(DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+// 把时钟固定在北京时间 at，其他测试模块也用它
+pub fn config_at(at: &str) -> Config {
+    let at = NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M").unwrap();
+    Config {
+        timezone: Some(Shanghai),
+        clock: Arc::new(FixedClock(
+            Shanghai
+                .from_local_datetime(&at)
+                .unwrap()
+                .with_timezone(&Utc),
+        )),
+        ..Default::default()
+    }
+}
+
+async fn generate(at: &str, body: Value) -> (u16, Value) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config_at(at)))
+            .configure(crate::routes),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/generate_gua_xian")
+        .set_json(body)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+fn line_field<'a>(body: &'a Value, field: &str) -> Vec<&'a str> {
+    body["gua_lines"].as_array().unwrap()[..6]
+        .iter()
+        .map(|line| line[field].as_str().unwrap())
+        .collect()
+}
+
+// 一个爻的类型化字段，阴阳和动静由爻象推出
+fn yao(
+    position: usize,
+    liuqin: &str,
+    tiangan: &str,
+    dizhi: &str,
+    wuxing: &str,
+    symbol: &str,
+) -> Value {
+    json!({
+        "position": position,
+        "liuqin": liuqin,
+        "tiangan": tiangan,
+        "dizhi": dizhi,
+        "wuxing": wuxing,
+        "symbol": symbol,
+        "yang": symbol.starts_with('⚊'),
+        "changing": symbol.len() > "⚊".len(),
+    })
+}
+
+// 甲子日巳时，乾为天初爻、上爻动，变泽风大过
+#[actix_web::test]
+async fn generate_returns_exact_chart_for_fixed_instant() {
+    let (status, body) = generate("2024-06-29T10:30", json!({"numbers": "311113"})).await;
+    assert_eq!(status, 200);
+    let line = |liushen: &str,
+                role: &str,
+                (base, yao): (&str, Value),
+                zheng: &str,
+                (bian, bian_yao): (&str, Value),
+                bian_rel: &str| {
+        json!({
+            "liushen": liushen,
+            "role": role,
+            "base_text": base,
+            "yao": yao,
+            "zheng_relations_text": zheng,
+            "bian_text": bian,
+            "bian_yao": bian_yao,
+            "bian_relations_text": bian_rel,
+            "is_changing": !bian_rel.is_empty(),
+        })
+    };
+    assert_eq!(
+        body,
+        json!({
+            "gua_lines": [
+                line("青龙", "Normal", ("子孙子水⚊ o", yao(1, "zi_sun", "jia", "zi", "shui", "⚊ o")), " 月冲", ("父母丑土⚋", yao(1, "fu_mu", "xin", "chou", "tu", "⚋")), " 月生 日合 回头克回头合"),
+                line("朱雀", "Normal", ("妻财寅木⚊", yao(2, "qi_cai", "jia", "yin", "mu", "⚊")), " 日生", ("子孙亥水⚊", yao(2, "zi_sun", "xin", "hai", "shui", "⚊")), ""),
+                line("勾陈", "Ying", ("父母辰土⚊", yao(3, "fu_mu", "jia", "chen", "tu", "⚊")), " 月生", ("兄弟酉金⚊", yao(3, "xiong_di", "xin", "you", "jin", "⚊")), ""),
+                line("螣蛇", "Normal", ("官鬼午火⚊", yao(4, "guan_gui", "ren", "wu", "huo", "⚊")), " 日冲 日克", ("子孙亥水⚊", yao(4, "zi_sun", "ding", "hai", "shui", "⚊")), ""),
+                line("白虎", "Normal", ("兄弟申金⚊", yao(5, "xiong_di", "ren", "shen", "jin", "⚊")), " 月克", ("兄弟酉金⚊", yao(5, "xiong_di", "ding", "you", "jin", "⚊")), ""),
+                line("玄武", "Shi", ("父母戌土⚊ o", yao(6, "fu_mu", "ren", "xu", "tu", "⚊ o")), " 旬空 月生", ("父母未土⚋", yao(6, "fu_mu", "ding", "wei", "tu", "⚋")), " 月合 月生"),
+                {
+                    "liushen": null,
+                    "role": "Normal",
+                    "base_text": "乾为天䷀(六冲)",
+                    "yao": null,
+                    "zheng_relations_text": "",
+                    "bian_text": "泽风大过䷛",
+                    "bian_yao": null,
+                    "bian_relations_text": "",
+                    "is_changing": false,
+                },
+            ],
+            "hexagram": {
+                "name": "乾为天",
+                "symbol": "䷀",
+                "index": "111111",
+                "king_wen": 1,
+                "fu_xi": 1,
+                "upper": {"name": "乾", "symbol": "☰", "image": "天", "wuxing": "金", "family": "父"},
+                "lower": {"name": "乾", "symbol": "☰", "image": "天", "wuxing": "金", "family": "父"},
+                "palace": "乾",
+                "generation": "本宫",
+            },
+            "bian_hexagram": {
+                "name": "泽风大过",
+                "symbol": "䷛",
+                "index": "211112",
+                "king_wen": 28,
+                "fu_xi": 34,
+                "upper": {"name": "兑", "symbol": "☱", "image": "泽", "wuxing": "金", "family": "少女"},
+                "lower": {"name": "巽", "symbol": "☴", "image": "风", "wuxing": "木", "family": "长女"},
+                "palace": "震",
+                "generation": "游魂",
+            },
+            "year_ganzhi": "甲辰",
+            "month_ganzhi": "庚午",
+            "day_ganzhi": "甲子",
+            "hour_ganzhi": "己巳",
+            "xun_kong": "戌亥",
+            "hour_xun_kong": "戌亥",
+            "solar_time": null,
+            "bazi": null,
+            "locale": "zh-Hans",
+        })
+    );
+}
+
+// 2024-06-19 至 06-28 依次为甲寅到癸亥日，覆盖十个日干
+#[actix_web::test]
+async fn liushen_order_follows_day_stem() {
+    let expected = [
+        ('甲', ["青龙", "朱雀", "勾陈", "螣蛇", "白虎", "玄武"]),
+        ('乙', ["青龙", "朱雀", "勾陈", "螣蛇", "白虎", "玄武"]),
+        ('丙', ["朱雀", "勾陈", "螣蛇", "白虎", "玄武", "青龙"]),
+        ('丁', ["朱雀", "勾陈", "螣蛇", "白虎", "玄武", "青龙"]),
+        ('戊', ["勾陈", "螣蛇", "白虎", "玄武", "青龙", "朱雀"]),
+        ('己', ["螣蛇", "白虎", "玄武", "青龙", "朱雀", "勾陈"]),
+        ('庚', ["白虎", "玄武", "青龙", "朱雀", "勾陈", "螣蛇"]),
+        ('辛', ["白虎", "玄武", "青龙", "朱雀", "勾陈", "螣蛇"]),
+        ('壬', ["玄武", "青龙", "朱雀", "勾陈", "螣蛇", "白虎"]),
+        ('癸', ["玄武", "青龙", "朱雀", "勾陈", "螣蛇", "白虎"]),
+    ];
+    for (day, (gan, liushen)) in (19..).zip(expected) {
+        let (_, body) = generate(
+            &format!("2024-06-{}T10:30", day),
+            json!({"numbers": "012312"}),
+        )
+        .await;
+        assert_eq!(
+            body["day_ganzhi"].as_str().unwrap().chars().next(),
+            Some(gan)
+        );
+        assert_eq!(line_field(&body, "liushen"), liushen, "{}日", gan);
+        assert!(body["gua_lines"][6]["liushen"].is_null());
+    }
+}
+
+// 乙卯日属甲寅旬，子丑空；坤为地只有四爻丑土落空。月建子、日建卯对各爻的冲合生克逐一核对
+#[actix_web::test]
+async fn xun_kong_and_month_day_relations() {
+    let (_, body) = generate("2024-12-17T10:30", json!({"numbers": "222222"})).await;
+    assert_eq!(body["month_ganzhi"], "丙子");
+    assert_eq!(body["day_ganzhi"], "乙卯");
+    assert_eq!(body["xun_kong"], "子丑");
+    assert_eq!(
+        line_field(&body, "zheng_relations_text"),
+        [
+            " 日克",
+            " 月克 日生",
+            " 月生",
+            " 旬空 月合 日克",
+            "",
+            " 日冲"
+        ]
+    );
+    // 静卦没有变爻关系
+    assert_eq!(line_field(&body, "bian_relations_text"), [""; 6]);
+}
+
+// 动爻化出的变爻落空也要标出
+#[actix_web::test]
+async fn xun_kong_applies_to_changed_lines() {
+    let (_, body) = generate("2024-06-19T10:30", json!({"numbers": "311111"})).await;
+    assert_eq!(body["xun_kong"], "子丑");
+    let zheng = line_field(&body, "zheng_relations_text");
+    assert!(zheng[0].starts_with(" 旬空"));
+    assert!(zheng[1..].iter().all(|text| !text.contains("旬空")));
+    assert!(line_field(&body, "bian_relations_text")[0].starts_with(" 旬空"));
+}
+
+#[actix_web::test]
+async fn invalid_numbers_are_rejected() {
+    let (status, body) = generate("2024-06-29T10:30", json!({"numbers": "3111"})).await;
+    assert_eq!(status, 400);
+    assert!(body.as_str().unwrap().contains("3111"));
+}
+
+// 同一卦可以用文王卦序、伏羲卦序、卦符或字符串索引查询
+#[actix_web::test]
+async fn hexagram_lookup_accepts_every_numbering() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(Config::default()))
+            .configure(crate::routes),
+    )
+    .await;
+    for uri in [
+        "/hexagram/44",
+        "/hexagram/%E4%B7%AB",
+        "/hexagram/211111",
+        "/hexagram/33?order=fu_xi",
+    ] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["name"], "天风姤", "{}", uri);
+        assert_eq!(body["symbol"], "䷫");
+        assert_eq!(body["king_wen"], 44);
+        assert_eq!(body["fu_xi"], 33);
+        assert_eq!(body["palace"], "乾");
+        assert_eq!(body["generation"], "一世");
+    }
+    for uri in [
+        "/hexagram/65",
+        "/hexagram/0?order=fu_xi",
+        "/hexagram/211131",
+        "/hexagram/%E4%B7%80x",
+    ] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 400, "{}", uri);
+    }
+}
+
+// 换语言只改文本字段，role、yao 等枚举字段和编号保持不变
+#[actix_web::test]
+async fn locale_translates_text_but_not_enums() {
+    let (_, zh) = generate("2024-06-29T10:30", json!({"numbers": "311113"})).await;
+    let (status, en) = generate(
+        "2024-06-29T10:30",
+        json!({"numbers": "311113", "locale": "en"}),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(en["locale"], "en");
+    assert_eq!(en["day_ganzhi"], "Jia Zi");
+    assert_eq!(en["xun_kong"], "Xu Hai");
+    assert_eq!(en["gua_lines"][0]["liushen"], "Azure Dragon");
+    assert_eq!(en["gua_lines"][0]["base_text"], "Offspring Zi Water ⚊ o");
+    assert_eq!(
+        en["gua_lines"][6]["base_text"],
+        "The Creative ䷀ (Six Clashes)"
+    );
+    assert_eq!(en["hexagram"]["name"], "The Creative");
+    assert_eq!(en["bian_hexagram"]["generation"], "Wandering Soul");
+    for field in ["role", "yao", "bian_yao", "is_changing"] {
+        assert_eq!(line_field_values(&en, field), line_field_values(&zh, field));
+    }
+    assert_eq!(en["hexagram"]["king_wen"], zh["hexagram"]["king_wen"]);
+
+    let (_, hant) = generate(
+        "2024-06-29T10:30",
+        json!({"numbers": "311113", "locale": "zh-Hant"}),
+    )
+    .await;
+    assert_eq!(hant["gua_lines"][0]["base_text"], "子孫子水⚊ o");
+    assert_eq!(
+        hant["gua_lines"][0]["bian_relations_text"],
+        " 月生 日合 回頭剋回頭合"
+    );
+    assert_eq!(hant["gua_lines"][6]["bian_text"], "澤風大過䷛");
+}
+
+fn line_field_values(body: &Value, field: &str) -> Vec<Value> {
+    body["gua_lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| line[field].clone())
+        .collect()
+}
+
+#[actix_web::test]
+async fn hexagram_and_locale_table_follow_locale() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(Config::default()))
+            .configure(crate::routes),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri("/hexagram/44?locale=pinyin")
+        .to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["name"], "tiān fēng gòu");
+    assert_eq!(body["upper"]["image"], "tiān");
+    assert_eq!(body["symbol"], "䷫");
+
+    let req = test::TestRequest::get().uri("/locales/en").to_request();
+    let body: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["推演卦象"], "Cast");
+    assert_eq!(body["子"], "Zi");
+}
+
+async fn call_v1(req: test::TestRequest) -> (u16, Value) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config_at("2024-06-29T10:30")))
+            .app_data(web::Data::new(
+                Storage::open(Path::new(":memory:")).unwrap(),
+            ))
+            .configure(crate::routes),
+    )
+    .await;
+    let resp = test::call_service(&app, req.to_request()).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+// 旧路由与 /api/v1/cast 返回相同的排盘，只是带上弃用的响应头
+#[actix_web::test]
+async fn legacy_route_is_deprecated_alias_of_v1_cast() {
+    let (status, v1) = call_v1(
+        test::TestRequest::post()
+            .uri("/api/v1/cast")
+            .set_json(json!({"numbers": "311113"})),
+    )
+    .await;
+    assert_eq!(status, 200);
+    let (_, legacy) = generate("2024-06-29T10:30", json!({"numbers": "311113"})).await;
+    assert_eq!(v1, legacy);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(Config::default()))
+            .configure(crate::routes),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/generate_gua_xian")
+        .set_json(json!({"numbers": "311113"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let header = |name| resp.headers().get(name).unwrap().to_str().unwrap();
+    assert_eq!(header("deprecation"), "true");
+    assert!(header("link").contains("</api/v1/cast>"));
+}
+
+// 处理函数、提取器和路由的错误在 /api/v1 下都是 {status, message}
+#[actix_web::test]
+async fn v1_errors_share_one_json_shape() {
+    let cases = [
+        (
+            test::TestRequest::post()
+                .uri("/api/v1/cast")
+                .set_json(json!({"numbers": "3111"})),
+            400,
+        ),
+        (
+            test::TestRequest::post()
+                .uri("/api/v1/cast")
+                .insert_header(("content-type", "application/json"))
+                .set_payload("{"),
+            400,
+        ),
+        (test::TestRequest::get().uri("/api/v1/calendar"), 400),
+        (
+            test::TestRequest::get().uri("/api/v1/calendar?from=2025-02-07&to=2025-02-01"),
+            400,
+        ),
+        // 超出历法库支持范围的年份
+        (
+            test::TestRequest::post()
+                .uri("/api/v1/cast")
+                .set_json(json!({
+                    "numbers": "311113",
+                    "lunar": {"year": 99999, "month": 1, "day": 1, "shichen": "午"},
+                })),
+            400,
+        ),
+        (
+            test::TestRequest::post()
+                .uri("/api/v1/cast")
+                .set_json(json!({
+                    "numbers": "311113",
+                    "birth": "+99999-01-01T10:00:00",
+                })),
+            400,
+        ),
+        (
+            test::TestRequest::get().uri("/api/v1/calendar?from=9999-12-31&to=10000-01-01"),
+            400,
+        ),
+        (test::TestRequest::get().uri("/api/v1/readings/999"), 404),
+        (test::TestRequest::get().uri("/api/v1/no-such-thing"), 404),
+    ];
+    for (req, expected) in cases {
+        let (status, body) = call_v1(req).await;
+        assert_eq!(status, expected, "{}", body);
+        assert_eq!(body["status"], expected, "{}", body);
+        assert!(!body["message"].as_str().unwrap().is_empty(), "{}", body);
+    }
+
+    let (_, body) = call_v1(
+        test::TestRequest::post()
+            .uri("/api/v1/cast")
+            .set_json(json!({"numbers": "3111"})),
+    )
+    .await;
+    assert!(body["message"].as_str().unwrap().contains("3111"));
+}
+
+#[actix_web::test]
+async fn openapi_document_describes_v1() {
+    let (status, doc) = call_v1(test::TestRequest::get().uri("/api/v1/openapi.json")).await;
+    assert_eq!(status, 200);
+    assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+    for path in [
+        "/api/v1/cast",
+        "/api/v1/chart.svg",
+        "/api/v1/calendar",
+        "/api/v1/readings",
+        "/api/v1/readings/{id}/outcome",
+        "/api/v1/export",
+        "/api/v1/import",
+        "/api/v1/yingqi",
+    ] {
+        assert!(doc["paths"][path].is_object(), "缺少 {}", path);
+    }
+    let schemas = &doc["components"]["schemas"];
+    for schema in [
+        "GuaRequest",
+        "GuaResponse",
+        "Reading",
+        "CalendarDay",
+        "ApiError",
+        "Document",
+        "ImportReport",
+    ] {
+        assert!(schemas[schema].is_object(), "缺少 {}", schema);
+    }
+    // 枚举按序列化后的取值描述
+    assert_eq!(schemas["ZiShi"]["enum"], json!(["split", "next_day"]));
+}
+
+// 应期推算在 /api/v1 下，错误也是统一的格式
+#[actix_web::test]
+async fn yingqi_is_served_under_v1() {
+    let (status, body) = call_v1(
+        test::TestRequest::post()
+            .uri("/api/v1/yingqi")
+            .set_json(json!({"numbers": "013212", "at": "2025-06-14T10:00:00", "line": 1})),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["states"], json!(["旬空", "动"]));
+
+    let (status, body) = call_v1(
+        test::TestRequest::post()
+            .uri("/api/v1/yingqi")
+            .set_json(json!({"numbers": "013212", "line": 1, "days": 1000})),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert_eq!(body["status"], 400);
+
+    // 甲寅日前一晚的子时：子初换日时已是甲寅日，子丑旬空；分早晚子时仍是癸丑日，寅卯旬空
+    for (zi_shi, xun_kong) in [("next_day", true), ("split", false)] {
+        let (status, body) = call_v1(test::TestRequest::post().uri("/api/v1/yingqi").set_json(
+            json!({"numbers": "013212", "at": "2025-06-13T23:30:00", "line": 1, "zi_shi": zi_shi}),
+        ))
+        .await;
+        assert_eq!(status, 200);
+        let states = body["states"].as_array().unwrap();
+        assert_eq!(states.contains(&json!("旬空")), xun_kong, "{}", zi_shi);
+    }
+}
+
+// 每个带文本的接口都按 locale 输出，没有漏译的汉字
+fn assert_no_han(value: &Value) {
+    let text = value.to_string();
+    assert!(
+        !text.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c)),
+        "{}",
+        text
+    );
+}
+
+#[actix_web::test]
+async fn cast_translates_bazi_in_en() {
+    let (status, body) = call_v1(test::TestRequest::post().uri("/api/v1/cast").set_json(
+        json!({"numbers": "311113", "birth": "1990-05-17T08:30", "yong_shen_line": 1, "locale": "en"}),
+    ))
+    .await;
+    assert_eq!(status, 200, "{}", body);
+    let bazi = &body["bazi"];
+    assert_eq!(bazi["pillars"][0]["label"], "Year Pillar");
+    assert_eq!(bazi["pillars"][2]["gan"]["shishen"], "Day Master");
+    assert_no_han(bazi);
+}
+
+#[actix_web::test]
+async fn yingqi_translates_text_in_en() {
+    let (status, body) = call_v1(test::TestRequest::post().uri("/api/v1/yingqi").set_json(
+        json!({"numbers": "013212", "at": "2025-06-14T10:00:00", "line": 1, "locale": "en"}),
+    ))
+    .await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["states"], json!(["void", "moving"]));
+    assert_eq!(body["candidates"][0]["rule"], "Clashing the void");
+    assert_no_han(&body);
+}
+
+#[actix_web::test]
+async fn calendar_translates_text_in_en() {
+    let (status, body) = call_v1(
+        test::TestRequest::get().uri("/api/v1/calendar?from=2025-02-03&to=2025-02-03&locale=en"),
+    )
+    .await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body[0]["jieqi"]["name"], "Start of Spring");
+    assert_eq!(body[0]["lunar"]["text"], "Month 1, Day 6");
+    assert_eq!(body[0]["shichen"][0]["zhi"], "Zi");
+    assert_no_han(&body);
+}
+
+// 经文只有简体原文，要求其他语言时明确拒绝
+#[actix_web::test]
+async fn texts_reject_en() {
+    let (status, body) =
+        call_v1(test::TestRequest::get().uri("/texts?numbers=311113&locale=en")).await;
+    assert_eq!(status, 400);
+    assert!(body.as_str().unwrap().contains("locale=en"));
+    let (status, _) =
+        call_v1(test::TestRequest::get().uri("/texts?numbers=311113&locale=zh-Hans")).await;
+    assert_eq!(status, 200);
+}
+
+#[actix_web::test]
+async fn chart_svg_is_drawn_in_en() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config_at("2024-06-29T10:30")))
+            .configure(crate::routes),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri("/api/v1/chart.svg?numbers=311113&locale=en")
+        .to_request();
+    let svg = String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap();
+    assert!(svg.contains("Offspring Zi Water"), "{}", svg);
+    assert!(svg.contains(">Self<"), "{}", svg);
+    assert!(
+        !svg.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c)),
+        "{}",
+        svg
+    );
+}
+
+async fn batch(content_type: &str, body: &str) -> Vec<Value> {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(Config::default()))
+            .configure(crate::routes),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/api/v1/charts:batch")
+        .insert_header(("content-type", content_type))
+        .set_payload(body.to_string())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/x-ndjson"
+    );
+    let body = test::read_body(resp).await;
+    std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+// 每条记录一行结果，出错的记录不影响其余记录
+#[actix_web::test]
+async fn batch_streams_one_result_per_json_line() {
+    let results = batch(
+        "application/x-ndjson",
+        concat!(
+            r#"{"numbers": "311113", "datetime": "2024-06-29T10:30", "question": "求财"}"#,
+            "\n\n",
+            r#"{"numbers": "3111", "datetime": "2024-06-29T10:30"}"#,
+            "\n",
+            r#"{"numbers": "000000", "datetime": "2024-06-29 10:30"}"#,
+        ),
+    )
+    .await;
+    assert_eq!(results.len(), 3);
+    let lines: Vec<_> = results.iter().map(|r| r["line"].clone()).collect();
+    assert_eq!(lines, [json!(1), json!(3), json!(4)]);
+
+    let (_, single) = generate("2024-06-29T10:30", json!({"numbers": "311113"})).await;
+    assert_eq!(results[0]["chart"], single);
+    assert_eq!(results[0]["question"], "求财");
+    assert!(results[1]["chart"].is_null());
+    assert!(results[1]["error"].as_str().unwrap().contains("3111"));
+    assert_eq!(results[2]["chart"]["hexagram"]["name"], "坤为地");
+    assert_eq!(results[2]["question"], "");
+}
+
+#[actix_web::test]
+async fn batch_reads_csv_by_header() {
+    let results = batch(
+        "text/csv; charset=utf-8",
+        "question,datetime,numbers\r\n\"问事业, 可成否\",2024-06-29T10:30,311113\r\n\"\"\"某\"\"之事\",,311113\r\n",
+    )
+    .await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["line"], 2);
+    assert_eq!(results[0]["question"], "问事业, 可成否");
+    assert_eq!(results[0]["chart"]["hexagram"]["name"], "乾为天");
+    // 历史记录必须给出时间，不取当前时间
+    assert!(results[1]["error"]
+        .as_str()
+        .unwrap()
+        .contains("无法解析时间"));
+
+    let results = batch("text/csv", "numbers,question\n311113,求财\n").await;
+    assert_eq!(results[0]["error"], "CSV 表头缺少 datetime 列");
+}
+
+// datetime 带时区时按该时区的当地时间排盘，给出经度时按该时区定标准经线，与服务器时区无关
+#[actix_web::test]
+async fn batch_keeps_each_record_offset() {
+    let results = batch(
+        "application/x-ndjson",
+        concat!(
+            r#"{"numbers": "311113", "datetime": "2024-06-29T10:30:00-05:00", "longitude": -75, "equation_of_time": false}"#,
+            "\n",
+            r#"{"numbers": "311113", "datetime": "2024-06-29T10:30:00+08:00"}"#,
+        ),
+    )
+    .await;
+    let west = &results[0]["chart"];
+    assert_eq!(west["solar_time"]["standard_meridian"], -75.0);
+    assert_eq!(west["solar_time"]["offset_seconds"], 0);
+    assert_eq!(west["solar_time"]["clock_time"], "2024-06-29T10:30:00");
+    assert_eq!(west["day_ganzhi"], "甲子");
+    assert_eq!(west["hour_ganzhi"], "己巳");
+    assert!(results[1]["chart"]["solar_time"].is_null());
+    assert_eq!(results[1]["chart"]["hour_ganzhi"], "己巳");
+
+    let results = batch(
+        "text/csv",
+        "numbers,datetime,longitude\n311113,2024-06-29T10:30+08:00,116.4\n311113,2024-06-29T10:30+08:00,\n311113,2024-06-29T10:30+08:00,东经\n",
+    )
+    .await;
+    assert_eq!(
+        results[0]["chart"]["solar_time"]["standard_meridian"],
+        120.0
+    );
+    assert!(results[1]["chart"]["solar_time"].is_null());
+    assert!(results[2]["error"].as_str().unwrap().contains("longitude"));
+}
+
+// 按块在线程池中排盘，结果仍与输入逐行对应，空行不输出也不打乱行号
+#[actix_web::test]
+async fn batch_spanning_several_chunks_keeps_order() {
+    let body: String = (1..=150)
+        .map(|i| match i % 50 {
+            0 => "\n".to_string(),
+            _ => format!(
+                "{{\"numbers\": \"311113\", \"datetime\": \"2024-06-29T10:30\", \"question\": \"{}\"}}\n",
+                i
+            ),
+        })
+        .collect();
+    let results = batch("application/x-ndjson", &body).await;
+    assert_eq!(results.len(), 147);
+    for result in &results {
+        assert_eq!(result["question"], result["line"].to_string());
+        assert_eq!(result["chart"]["day_ganzhi"], "甲子");
+    }
+}
@@ -0,0 +1,223 @@
+//! 交换格式的测试：导出保存的排盘设置，导入时重新排盘核对并列出所有错误。
+
+use crate::config::Config;
+use crate::storage::{NewReading, ReadingFilter, Storage};
+use crate::{ZiShi, CHART_VERSION};
+use actix_web::{test, web, App};
+use chrono::NaiveDate;
+use chrono_tz::Asia::Shanghai;
+use serde_json::{json, Value};
+use std::path::Path;
+
+async fn call(storage: &web::Data<Storage>, zi_shi: ZiShi, req: test::TestRequest) -> (u16, Value) {
+    let config = Config {
+        timezone: Some(Shanghai),
+        zi_shi,
+        ..Default::default()
+    };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(storage.clone())
+            .configure(crate::routes),
+    )
+    .await;
+    let resp = test::call_service(&app, req.to_request()).await;
+    let status = resp.status().as_u16();
+    (status, test::read_body_json(resp).await)
+}
+
+fn empty_storage() -> web::Data<Storage> {
+    web::Data::new(Storage::open(Path::new(":memory:")).unwrap())
+}
+
+// 导出的是起卦时保存的设置，换一个配置导入到新数据库后排盘结果一致
+#[actix_web::test]
+async fn export_and_import_keep_stored_settings() {
+    let source = empty_storage();
+    let (status, _) = call(
+        &source,
+        ZiShi::NextDay,
+        test::TestRequest::post()
+            .uri("/api/v1/readings")
+            .set_json(json!({
+                "numbers": "013212",
+                "at": "2025-06-01T23:30:00",
+                "location": {"name": "乌鲁木齐", "longitude": 87.6},
+                "true_solar_time": true,
+                "tags": ["面试"],
+            })),
+    )
+    .await;
+    assert_eq!(status, 201);
+
+    let (status, document) = call(
+        &source,
+        ZiShi::Split,
+        test::TestRequest::get().uri("/api/v1/export"),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(document["version"], 3);
+    assert!(document.get("chart_version").is_none());
+    assert!(document.get("engine_version").is_none());
+    let record = &document["readings"][0];
+    assert_eq!(record["chart_version"], CHART_VERSION);
+    assert_eq!(record["zi_shi"], "next_day");
+    assert_eq!(record["solar_time_utc_offset"], 8 * 3600);
+    assert_eq!(record["tags"], json!(["面试"]));
+
+    let target = empty_storage();
+    let (status, report) = call(
+        &target,
+        ZiShi::Split,
+        test::TestRequest::post()
+            .uri("/api/v1/import?strict=true")
+            .set_json(&document),
+    )
+    .await;
+    assert_eq!(status, 200, "{}", report);
+    assert_eq!(report["imported"], 1);
+    assert_eq!(report["chart_version_differs"], false);
+    assert_eq!(report["results"][0]["chart_matches"], true);
+    assert_eq!(report["results"][0]["chart_version_differs"], false);
+
+    let imported = target.list(&ReadingFilter::default()).unwrap();
+    assert_eq!(imported[0].chart_version, Some(CHART_VERSION));
+    assert_eq!(imported[0].zi_shi, Some(ZiShi::NextDay));
+    assert_eq!(imported[0].solar_time_utc_offset, Some(8 * 3600));
+    assert_eq!(imported[0].chart, record["chart"]);
+}
+
+// 版本 1 的文件带 engine_version，没有 chart_version，仍可导入
+#[actix_web::test]
+async fn version_1_file_is_accepted() {
+    let storage = empty_storage();
+    let (status, report) = call(
+        &storage,
+        ZiShi::Split,
+        test::TestRequest::post()
+            .uri("/api/v1/import")
+            .set_json(json!({
+                "format": "sixsixsix.readings",
+                "version": 1,
+                "engine_version": "0.1.0",
+                "readings": [{
+                    "numbers": "013212",
+                    "cast_at": "2025-06-01T10:00:00",
+                    "chart": {"day_ganzhi": "甲子"},
+                }],
+            })),
+    )
+    .await;
+    assert_eq!(status, 200, "{}", report);
+    assert_eq!(report["chart_version_differs"], true);
+    assert_eq!(report["results"][0]["chart_version_differs"], true);
+    assert_eq!(report["results"][0]["differences"][0], "gua_lines");
+}
+
+// 导出的是每条记录保存时的结构版本，导入时逐条比较
+#[actix_web::test]
+async fn chart_version_is_compared_per_record() {
+    let source = empty_storage();
+    let at = NaiveDate::from_ymd_opt(2025, 6, 1)
+        .unwrap()
+        .and_hms_opt(10, 0, 0)
+        .unwrap();
+    source
+        .insert(&NewReading {
+            numbers: "013212".to_string(),
+            cast_at: at,
+            question: String::new(),
+            category: String::new(),
+            location: None,
+            zi_shi: ZiShi::Split,
+            solar_time_utc_offset: None,
+            chart: json!({}),
+            chart_version: CHART_VERSION - 1,
+            interpretation: None,
+            tags: Vec::new(),
+            created_at: at,
+        })
+        .unwrap();
+    let (status, _) = call(
+        &source,
+        ZiShi::Split,
+        test::TestRequest::post()
+            .uri("/api/v1/readings")
+            .set_json(json!({"numbers": "013212", "at": "2025-06-01T10:00:00"})),
+    )
+    .await;
+    assert_eq!(status, 201);
+
+    let (_, document) = call(
+        &source,
+        ZiShi::Split,
+        test::TestRequest::get().uri("/api/v1/export"),
+    )
+    .await;
+    let versions: Vec<&Value> = document["readings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| &r["chart_version"])
+        .collect();
+    assert!(versions.contains(&&json!(CHART_VERSION - 1)));
+    assert!(versions.contains(&&json!(CHART_VERSION)));
+
+    let (status, report) = call(
+        &empty_storage(),
+        ZiShi::Split,
+        test::TestRequest::post()
+            .uri("/api/v1/import")
+            .set_json(&document),
+    )
+    .await;
+    assert_eq!(status, 200, "{}", report);
+    assert_eq!(report["chart_version_differs"], true);
+    for (record, result) in document["readings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .zip(report["results"].as_array().unwrap())
+    {
+        let old = record["chart_version"] != CHART_VERSION;
+        assert_eq!(result["chart_version_differs"], old);
+        assert_eq!(result["chart_matches"], !old);
+    }
+}
+
+#[actix_web::test]
+async fn import_lists_every_error() {
+    let storage = empty_storage();
+    let (status, body) = call(
+        &storage,
+        ZiShi::Split,
+        test::TestRequest::post()
+            .uri("/api/v1/import")
+            .set_json(json!({
+                "format": "sixsixsix.readings",
+                "version": 4,
+                "readings": [{
+                    "numbers": "0142",
+                    "cast_at": "2025-06-01T10:00:00",
+                    "solar_time_utc_offset": 28800,
+                    "chart": {},
+                }],
+            })),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert_eq!(body["status"], 400);
+    let errors: Vec<&str> = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e.as_str().unwrap())
+        .collect();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors[0].starts_with("version:"));
+    assert!(errors[1].starts_with("readings[0].numbers:"));
+    assert!(errors[2].starts_with("readings[0].solar_time_utc_offset:"));
+    assert!(storage.list(&ReadingFilter::default()).unwrap().is_empty());
+}
@@ -0,0 +1,168 @@
+//! 译名表覆盖了排盘输出中的所有词，翻译结果可以逐字断言。
+
+use crate::gua_table::{Generation, GUA_TABLE};
+use crate::hexagram::{Hexagram, Trigram};
+use crate::locale::Locale;
+use crate::{ChongHe, DiZhi, LiuQin, LiuShen, ShengKe, WuXing, YaoRole, TIAN_GAN};
+
+const TRANSLATED: [Locale; 3] = [Locale::ZhHant, Locale::En, Locale::Pinyin];
+
+// 排盘输出中会出现的所有词，都应有英文和拼音译名
+fn chart_terms() -> Vec<String> {
+    let mut terms: Vec<String> = [
+        WuXing::Jin,
+        WuXing::Mu,
+        WuXing::Shui,
+        WuXing::Huo,
+        WuXing::Tu,
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+    terms.extend(DiZhi::ALL.iter().map(ToString::to_string));
+    terms.extend(TIAN_GAN.iter().map(ToString::to_string));
+    terms.extend(
+        [
+            LiuQin::XiongDi,
+            LiuQin::ZiSun,
+            LiuQin::QiCai,
+            LiuQin::GuanGui,
+            LiuQin::FuMu,
+        ]
+        .iter()
+        .map(ToString::to_string),
+    );
+    terms.extend(
+        [
+            LiuShen::QingLong,
+            LiuShen::ZhuQue,
+            LiuShen::GouChen,
+            LiuShen::TengShe,
+            LiuShen::BaiHu,
+            LiuShen::XuanWu,
+        ]
+        .iter()
+        .map(ToString::to_string),
+    );
+    terms.extend(
+        [ChongHe::Chong, ChongHe::He]
+            .iter()
+            .map(ToString::to_string),
+    );
+    terms.extend(
+        [ShengKe::Sheng, ShengKe::Ke]
+            .iter()
+            .map(ToString::to_string),
+    );
+    terms.extend(
+        [YaoRole::Shi, YaoRole::Ying]
+            .iter()
+            .map(ToString::to_string),
+    );
+    terms.extend(
+        [
+            Generation::BenGong,
+            Generation::YiShi,
+            Generation::ErShi,
+            Generation::SanShi,
+            Generation::SiShi,
+            Generation::WuShi,
+            Generation::YouHun,
+            Generation::GuiHun,
+        ]
+        .iter()
+        .map(ToString::to_string),
+    );
+    for trigram in Trigram::ALL {
+        terms.extend([trigram.name(), trigram.image(), trigram.family()].map(String::from));
+    }
+    terms.extend((0..64).map(|code| Hexagram::from_code(code).name().to_string()));
+    terms.extend(["月", "日", "回头", "旬空", "(六冲)", "(六合)"].map(String::from));
+    terms
+}
+
+#[test]
+fn every_chart_term_has_a_translation() {
+    for term in chart_terms() {
+        for locale in [Locale::En, Locale::Pinyin] {
+            let translated = locale.term(&term);
+            assert!(
+                translated.is_ascii() || locale == Locale::Pinyin,
+                "{} 的 {} 译名仍含汉字：{}",
+                locale,
+                term,
+                translated
+            );
+            assert_ne!(translated, term, "{} 缺少 {} 的译名", locale, term);
+        }
+        assert!(!Locale::ZhHant.term(&term).is_empty());
+        assert_eq!(Locale::ZhHans.term(&term), term);
+    }
+}
+
+#[test]
+fn hexagram_names_are_distinct_in_every_locale() {
+    for locale in TRANSLATED {
+        let mut names: Vec<&str> = (0..64)
+            .map(|code| locale.term(Hexagram::from_code(code).name()))
+            .collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), 64, "{}", locale);
+    }
+}
+
+#[test]
+fn text_uses_longest_match_and_spaces_words() {
+    let base = "妻财子水⚊ o";
+    assert_eq!(Locale::ZhHans.text(base), base);
+    assert_eq!(Locale::ZhHant.text(base), "妻財子水⚊ o");
+    assert_eq!(Locale::En.text(base), "Wealth Zi Water ⚊ o");
+    assert_eq!(Locale::Pinyin.text(base), "qīcái zǐ shuǐ ⚊ o");
+
+    // "子孙" 整体匹配，不拆成地支 "子"
+    assert_eq!(Locale::En.text("子孙子水"), "Offspring Zi Water");
+    assert_eq!(
+        Locale::En.text(" 旬空 月冲 回头克回头合"),
+        " void month clash returning control returning combine"
+    );
+    assert_eq!(Locale::ZhHant.text(" 月冲 回头克"), " 月沖 回頭剋");
+}
+
+#[test]
+fn chart_names_translate_with_marks() {
+    for info in GUA_TABLE {
+        let en = Locale::En.text(info.name);
+        assert!(
+            !en.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c)),
+            "{} 译为 {}",
+            info.name,
+            en
+        );
+    }
+    assert_eq!(
+        Locale::En.text("天地否䷋(六合)"),
+        "Standstill ䷋ (Six Harmonies)"
+    );
+    assert_eq!(Locale::ZhHant.text("天地否䷋(六合)"), "天地否䷋(六合)");
+}
+
+// 应期理由、用神对照和农历日期由整句的片段拼成，逐段查表翻译
+#[test]
+fn reasons_and_lunar_dates_translate() {
+    assert_eq!(
+        Locale::En.text("申金化入丑墓，逢未日冲开墓库"),
+        "Shen Metal changes into Chou tomb, on Wei day, clashing the tomb open"
+    );
+    assert_eq!(
+        Locale::Pinyin.text("用神午火值月，事缓则应在午月"),
+        "yòngshén wǔ huǒ zhí yuè, shì huǎn zé yìng zài wǔ yuè"
+    );
+    assert_eq!(
+        Locale::En.text("用神午火为日主之财星，是命主忌神，所测之事命局不助"),
+        "Use God Wu Fire is, for the Day Master, Wealth Star, unfavourable to the native, \
+         the birth chart does not support the matter"
+    );
+    assert_eq!(Locale::ZhHant.text("腊月廿九"), "臘月廿九");
+    assert_eq!(Locale::Pinyin.text("闰六月初三"), "rùn liù yuè chū sān");
+}
@@ -0,0 +1,58 @@
+//! 农历起卦时间的测试：换算公历，以及超出历法库支持范围的年份。
+
+use crate::cli::{run_paipan, OutputFormat, PaipanArgs};
+use crate::config::Config;
+use crate::lunar_date::{LunarDateError, LunarDateInput};
+use crate::raster;
+use chrono::NaiveDateTime;
+
+fn args(at: Option<&str>, lunar: Option<&str>, birth: Option<&str>) -> PaipanArgs {
+    PaipanArgs {
+        numbers: "013212".to_string(),
+        at: at.map(str::to_string),
+        lunar: lunar.map(|s| s.parse().unwrap()),
+        longitude: None,
+        no_equation_of_time: false,
+        birth: birth.map(str::to_string),
+        yong_shen_line: None,
+        format: OutputFormat::Json,
+        scale: raster::DEFAULT_SCALE,
+    }
+}
+
+#[test]
+fn lunar_date_converts_to_solar() {
+    let input: LunarDateInput = "2025-闰06-03 午".parse().unwrap();
+    assert_eq!(
+        input.to_solar().unwrap(),
+        NaiveDateTime::parse_from_str("2025-07-27T11:00", "%Y-%m-%dT%H:%M").unwrap()
+    );
+}
+
+// 历法库对超出范围的年份会 panic，应在换算前报错
+#[test]
+fn years_out_of_range_are_rejected() {
+    for year in ["99999", "0"] {
+        let input: LunarDateInput = format!("{}-01-01 午", year).parse().unwrap();
+        assert!(matches!(
+            input.to_solar(),
+            Err(LunarDateError::InvalidYear(_))
+        ));
+    }
+
+    let config = Config::default();
+    for args in [
+        args(None, Some("99999-01-01 午"), None),
+        args(Some("+99999-01-01T10:00:00"), None, None),
+        args(Some("+10000-01-01T10:00:00"), None, None),
+        args(
+            Some("2025-06-01T10:00:00"),
+            None,
+            Some("+99999-01-01T10:00:00"),
+        ),
+    ] {
+        let result = run_paipan(&args, &config);
+        assert!(result.is_err(), "{:?}", args);
+    }
+    assert!(run_paipan(&args(Some("9999-06-01T10:00:00"), None, None), &config).is_ok());
+}
@@ -0,0 +1,17 @@
+mod assets;
+mod bazi;
+mod calendar;
+mod config;
+mod enumeration;
+mod golden;
+mod http;
+mod interchange;
+mod locale;
+mod lunar_date;
+mod raster;
+mod solar_time;
+mod stats;
+mod storage;
+mod tables;
+mod yingqi;
+mod zhouyi;
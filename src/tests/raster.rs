@@ -0,0 +1,53 @@
+//! 图片输出的测试：SVG 注明或内嵌所用字体，渲染的 PNG 能解码，尺寸随缩放倍数变化。
+
+use crate::raster::{self, RasterError, DEFAULT_SCALE};
+use crate::{paipan, render, ZiShi};
+use chrono::NaiveDateTime;
+use resvg::tiny_skia::Pixmap;
+
+fn chart_svg() -> String {
+    let at = NaiveDateTime::parse_from_str("2025-06-01T10:00", "%Y-%m-%dT%H:%M").unwrap();
+    render::svg(&paipan("013212", at, ZiShi::Split).unwrap())
+}
+
+fn decode(png: &[u8]) -> Pixmap {
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    let image = Pixmap::decode_png(png).unwrap();
+    assert!(image.width() > 0 && image.height() > 0);
+    image
+}
+
+// 有内置字体时以 data URI 内嵌并排在 font-family 最前，否则在 desc 中注明依赖系统字体
+#[test]
+fn svg_embeds_or_names_its_fonts() {
+    let svg = chart_svg();
+    let faces = raster::font_faces();
+    if faces.is_empty() {
+        assert!(svg.contains("<desc>本图未内嵌字体"), "{}", svg);
+    } else {
+        assert!(svg.contains("@font-face { font-family: '"));
+        assert!(svg.contains(&format!("font-family=\"'{}', ", faces[0].family)));
+        assert!(!svg.contains("<desc>"));
+    }
+}
+
+#[test]
+fn chart_renders_as_png() {
+    let svg = chart_svg();
+    let image = decode(&raster::png(&svg, DEFAULT_SCALE).unwrap());
+    assert!(image.pixels().iter().any(|p| p.alpha() > 0));
+
+    let small = decode(&raster::png(&svg, 1.0).unwrap());
+    assert_eq!(image.width(), small.width() * 2);
+    assert_eq!(image.height(), small.height() * 2);
+}
+
+#[test]
+fn scale_out_of_range_is_rejected() {
+    for scale in [0.1, 10.0, f32::NAN] {
+        assert!(matches!(
+            raster::png(&chart_svg(), scale),
+            Err(RasterError::InvalidScale(_))
+        ));
+    }
+}
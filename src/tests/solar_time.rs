@@ -0,0 +1,120 @@
+//! 真太阳时校正的测试：经度差、均时差，以及标准经线取自输入的时区。
+
+use crate::cli::{run_paipan, OutputFormat, PaipanArgs};
+use crate::config::Config;
+use crate::raster;
+use crate::solar_time::{correct, SolarTimeOptions};
+use chrono::{NaiveDate, NaiveDateTime};
+use chrono_tz::{America::New_York, Asia::Shanghai};
+use serde_json::Value;
+
+const EAST_8: i32 = 8 * 3600;
+
+fn at(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").unwrap()
+}
+
+#[test]
+fn longitude_offset_is_four_minutes_per_degree() {
+    let options = SolarTimeOptions {
+        longitude: 116.4,
+        equation_of_time: false,
+    };
+    let result = correct(at("2025-06-01T12:00"), EAST_8, options).unwrap();
+    assert_eq!(result.standard_meridian, 120.0);
+    assert_eq!(result.longitude_offset_seconds, -864);
+    assert_eq!(result.equation_of_time_seconds, 0);
+    assert_eq!(
+        result.solar_time,
+        at("2025-06-01T11:45") + chrono::Duration::seconds(36)
+    );
+
+    // 西五区的标准经线为西经75度
+    let options = SolarTimeOptions {
+        longitude: -75.0,
+        equation_of_time: false,
+    };
+    let result = correct(at("2025-06-01T12:00"), -5 * 3600, options).unwrap();
+    assert_eq!(result.standard_meridian, -75.0);
+    assert_eq!(result.offset_seconds, 0);
+
+    for longitude in [180.5, -181.0] {
+        let options = SolarTimeOptions {
+            longitude,
+            equation_of_time: true,
+        };
+        assert!(correct(at("2025-06-01T12:00"), EAST_8, options).is_err());
+    }
+}
+
+// 均时差的参考值（分钟），近似公式的误差应在一分钟以内
+#[test]
+fn equation_of_time_at_known_dates() {
+    for ((month, day), minutes) in [
+        ((2, 11), -14.2),
+        ((4, 15), 0.0),
+        ((5, 14), 3.7),
+        ((6, 13), 0.0),
+        ((7, 26), -6.5),
+        ((11, 3), 16.4),
+    ] {
+        let noon = NaiveDate::from_ymd_opt(2025, month, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let options = SolarTimeOptions {
+            longitude: 120.0,
+            equation_of_time: true,
+        };
+        let result = correct(noon, EAST_8, options).unwrap();
+        assert_eq!(result.longitude_offset_seconds, 0);
+        let error = result.equation_of_time_seconds as f64 - minutes * 60.0;
+        assert!(error.abs() <= 60.0, "{}-{}: {}s", month, day, error);
+    }
+}
+
+fn solar_time(at: &str, longitude: f64) -> Value {
+    let args = PaipanArgs {
+        numbers: "013212".to_string(),
+        at: Some(at.to_string()),
+        lunar: None,
+        longitude: Some(longitude),
+        no_equation_of_time: true,
+        birth: None,
+        yong_shen_line: None,
+        format: OutputFormat::Json,
+        scale: raster::DEFAULT_SCALE,
+    };
+    let config = Config {
+        timezone: Some(Shanghai),
+        ..Default::default()
+    };
+    let output = run_paipan(&args, &config).unwrap();
+    serde_json::from_slice::<Value>(&output).unwrap()["solar_time"].clone()
+}
+
+// 输入带时区时按该时区定标准经线，不受服务器配置的时区影响
+#[test]
+fn offset_in_input_sets_standard_meridian() {
+    let west = solar_time("2025-06-01T10:00:00-05:00", -75.0);
+    assert_eq!(west["standard_meridian"], -75.0);
+    assert_eq!(west["offset_seconds"], 0);
+    assert_eq!(west["clock_time"], "2025-06-01T10:00:00");
+
+    let local = solar_time("2025-06-01T10:00:00", 116.4);
+    assert_eq!(local["standard_meridian"], 120.0);
+    assert_eq!(local["offset_seconds"], -864);
+}
+
+// 夏令时跳过的时刻仍按配置的时区取偏移
+#[test]
+fn utc_offset_uses_configured_timezone() {
+    let config = Config {
+        timezone: Some(New_York),
+        ..Default::default()
+    };
+    assert_eq!(config.utc_offset_seconds(at("2025-07-01T12:00")), -4 * 3600);
+    assert_eq!(config.utc_offset_seconds(at("2025-01-01T12:00")), -5 * 3600);
+    let gap = config.utc_offset_seconds(at("2025-03-09T02:30"));
+    assert!([-5 * 3600, -4 * 3600].contains(&gap), "{}", gap);
+}
@@ -0,0 +1,98 @@
+//! 应验统计的测试：断卦规则取自排盘的结构化数据，与输出的语言无关。
+
+use crate::config::Config;
+use crate::stats::{fired_rules, report};
+use crate::storage::{Outcome, OutcomeResult, Reading};
+use crate::{paipan, ZiShi};
+use chrono::NaiveDateTime;
+use serde_json::json;
+
+fn at(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").unwrap()
+}
+
+// 水风井之水泽节，巳月丑日：应爻亥水月冲日克，世爻戌土月生，两个动爻都回头生
+#[test]
+fn rules_of_a_known_chart() {
+    let chart = paipan("013212", at("2025-06-01T10:00"), ZiShi::Split).unwrap();
+    let mut expected = [
+        "应爻月冲",
+        "应爻日克",
+        "世爻月生",
+        "动爻回头生",
+        "变卦六合",
+        "动卦",
+    ];
+    expected.sort();
+    assert_eq!(fired_rules(&chart), expected);
+}
+
+// 六冲、六合由三对爻的地支推出，与卦名表中的标注一致
+#[test]
+fn liu_chong_he_matches_hexagram_names() {
+    let mut counts = (0, 0);
+    for code in 0..64u32 {
+        let numbers: String = (0..6)
+            .map(|i| if code >> i & 1 == 1 { '1' } else { '2' })
+            .collect();
+        let chart = paipan(&numbers, at("2025-06-01T10:00"), ZiShi::Split).unwrap();
+        let name = &chart.gua_lines[6].base_text;
+        let rules = fired_rules(&chart);
+        let chong = rules.iter().any(|r| r == "本卦六冲");
+        let he = rules.iter().any(|r| r == "本卦六合");
+        assert_eq!(chong, name.contains("六冲"), "{}", name);
+        assert_eq!(he, name.contains("六合"), "{}", name);
+        counts.0 += chong as u32;
+        counts.1 += he as u32;
+        // 静卦不统计变卦
+        assert!(!rules.iter().any(|r| r.starts_with("变卦")), "{}", name);
+    }
+    assert_eq!(counts, (10, 8));
+}
+
+fn reading(chart: serde_json::Value, result: OutcomeResult) -> Reading {
+    Reading {
+        id: 1,
+        numbers: "013212".to_string(),
+        cast_at: at("2025-06-01T10:00"),
+        question: String::new(),
+        category: String::new(),
+        location: None,
+        zi_shi: Some(ZiShi::Split),
+        solar_time_utc_offset: None,
+        chart,
+        chart_version: None,
+        interpretation: None,
+        notes: String::new(),
+        tags: Vec::new(),
+        created_at: at("2025-06-01T10:00"),
+        outcome: Some(Outcome {
+            result,
+            date: None,
+            notes: String::new(),
+        }),
+        yingqi: None,
+    }
+}
+
+// 保存的排盘结果是什么语言、是否完整都不影响规则统计
+#[test]
+fn report_does_not_read_chart_text() {
+    let mut localized = paipan("013212", at("2025-06-01T10:00"), ZiShi::Split).unwrap();
+    localized.localize(crate::Locale::En);
+    let readings = [
+        reading(
+            serde_json::to_value(&localized).unwrap(),
+            OutcomeResult::Fulfilled,
+        ),
+        reading(json!({}), OutcomeResult::Unfulfilled),
+    ];
+    let report = report(&Config::default(), &readings);
+    let keys: Vec<&str> = report.by_rule.iter().map(|g| g.key.as_str()).collect();
+    assert_eq!(keys.len(), 6, "{:?}", keys);
+    for group in &report.by_rule {
+        assert_eq!(group.tally.total, 2, "{}", group.key);
+        assert_eq!(group.tally.fulfilled, 1, "{}", group.key);
+    }
+    assert!(keys.contains(&"应爻月冲"));
+}
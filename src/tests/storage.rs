@@ -0,0 +1,237 @@
+//! 占卜记录存储的测试：迁移、记录与标签的保存和按设置重新排盘。
+
+use crate::config::Config;
+use crate::storage::{Location, NewReading, ReadingFilter, Storage, MIGRATIONS};
+use crate::ZiShi;
+// 同步测试要用 #[test]，所以不导入 actix_web::test
+use actix_web::{test as actix_test, web, App};
+use chrono::NaiveDateTime;
+use chrono_tz::Asia::Shanghai;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use std::path::Path;
+
+fn at(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").unwrap()
+}
+
+fn new_reading(tags: &[&str]) -> NewReading {
+    NewReading {
+        numbers: "311113".to_string(),
+        cast_at: at("2024-06-29T10:30"),
+        question: "问事业".to_string(),
+        category: "事业".to_string(),
+        location: Some(Location {
+            name: Some("北京".to_string()),
+            longitude: Some(116.4),
+        }),
+        zi_shi: ZiShi::NextDay,
+        solar_time_utc_offset: Some(8 * 3600),
+        chart: json!({"gua_lines": []}),
+        chart_version: 3,
+        interpretation: None,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        created_at: at("2024-06-29T10:31"),
+    }
+}
+
+// 只执行过第一条迁移的旧数据库，打开时补齐其余迁移，旧记录照常读出
+#[test]
+fn migrates_old_database_to_latest() {
+    let path = std::env::temp_dir().join(format!("sixsixsix-migrate-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    {
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute(
+            "INSERT INTO readings (numbers, cast_at, question, category, chart_json, created_at)
+             VALUES ('311113', '2024-06-29T10:30:00', '', '', '{}', '2024-06-29T10:30:00')",
+            [],
+        )
+        .unwrap();
+    }
+
+    let storage = Storage::open(&path).unwrap();
+    let reading = storage.get(1).unwrap();
+    assert_eq!(reading.numbers, "311113");
+    assert!(reading.location.is_none());
+    assert_eq!(reading.zi_shi, None);
+    assert_eq!(reading.solar_time_utc_offset, None);
+    storage.insert(&new_reading(&[])).unwrap();
+    drop(storage);
+
+    let conn = Connection::open(&path).unwrap();
+    let version: usize = conn
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .unwrap();
+    assert_eq!(version, MIGRATIONS.len());
+    drop(conn);
+    // 已是最新版本时再次打开不会重复执行迁移
+    assert_eq!(
+        Storage::open(&path).unwrap().get(2).unwrap().numbers,
+        "311113"
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn insert_and_list_round_trip() {
+    let storage = Storage::open(Path::new(":memory:")).unwrap();
+    let first = storage
+        .insert(&new_reading(&["工作", " 工作 ", ""]))
+        .unwrap();
+    assert_eq!(first.tags, ["工作"]);
+    assert_eq!(first.zi_shi, Some(ZiShi::NextDay));
+    assert_eq!(first.solar_time_utc_offset, Some(8 * 3600));
+    assert_eq!(first.location.as_ref().unwrap().longitude, Some(116.4));
+    storage.insert(&new_reading(&["感情"])).unwrap();
+
+    let all = storage.list(&ReadingFilter::default()).unwrap();
+    assert_eq!(all.len(), 2);
+    let tagged = storage
+        .list(&ReadingFilter {
+            tag: Some("工作".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(tagged.len(), 1);
+    assert_eq!(tagged[0].id, first.id);
+    assert_eq!(tagged[0].question, "问事业");
+    assert_eq!(tagged[0].chart, json!({"gua_lines": []}));
+}
+
+async fn call(storage: &web::Data<Storage>, zi_shi: ZiShi, req: actix_test::TestRequest) -> Value {
+    let config = Config {
+        timezone: Some(Shanghai),
+        zi_shi,
+        ..Default::default()
+    };
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(storage.clone())
+            .configure(crate::routes),
+    )
+    .await;
+    actix_test::call_and_read_body_json(&app, req.to_request()).await
+}
+
+// 重新排盘沿用保存时的子时约定和真太阳时设置，不受之后配置变化的影响
+#[actix_web::test]
+async fn chart_uses_stored_settings() {
+    let storage = web::Data::new(Storage::open(Path::new(":memory:")).unwrap());
+    let created = call(
+        &storage,
+        ZiShi::Split,
+        actix_test::TestRequest::post()
+            .uri("/api/v1/readings")
+            .set_json(json!({
+                "numbers": "311113",
+                "at": "2024-06-29T23:50:00+08:00",
+                "location": {"name": "乌鲁木齐", "longitude": 87.6},
+                "true_solar_time": true,
+                "zi_shi": "next_day",
+                "tags": ["工作"],
+            })),
+    )
+    .await;
+    assert_eq!(created["zi_shi"], "next_day");
+    assert_eq!(created["solar_time_utc_offset"], 8 * 3600);
+    assert_eq!(created["tags"], json!(["工作"]));
+    // 乌鲁木齐的真太阳时约早两个多小时，已不在子时
+    let solar_time = &created["chart"]["solar_time"];
+    assert_eq!(solar_time["standard_meridian"], 120.0);
+    assert!(solar_time["solar_time"]
+        .as_str()
+        .unwrap()
+        .starts_with("2024-06-29T21:"));
+
+    let uri = format!("/api/v1/readings/{}/chart", created["id"]);
+    let chart = call(
+        &storage,
+        ZiShi::Split,
+        actix_test::TestRequest::get().uri(&uri),
+    )
+    .await;
+    assert_eq!(chart, created["chart"]);
+
+    // 不按真太阳时的 23 点后起卦，日柱按保存的子时约定
+    let created = call(
+        &storage,
+        ZiShi::NextDay,
+        actix_test::TestRequest::post()
+            .uri("/api/v1/readings")
+            .set_json(json!({
+                "numbers": "311113",
+                "at": "2024-06-29T23:30:00",
+            })),
+    )
+    .await;
+    assert_eq!(created["zi_shi"], "next_day");
+    let uri = format!("/api/v1/readings/{}/chart", created["id"]);
+    let chart = call(
+        &storage,
+        ZiShi::Split,
+        actix_test::TestRequest::get().uri(&uri),
+    )
+    .await;
+    assert_eq!(chart["day_ganzhi"], created["chart"]["day_ganzhi"]);
+    assert_eq!(chart, created["chart"]);
+}
+
+#[actix_web::test]
+async fn true_solar_time_needs_longitude() {
+    let storage = web::Data::new(Storage::open(Path::new(":memory:")).unwrap());
+    let config = Config::default();
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(storage.clone())
+            .configure(crate::routes),
+    )
+    .await;
+    let req = actix_test::TestRequest::post()
+        .uri("/api/v1/readings")
+        .set_json(json!({"numbers": "311113", "true_solar_time": true}))
+        .to_request();
+    let resp = actix_test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 400);
+    assert!(storage.list(&ReadingFilter::default()).unwrap().is_empty());
+}
+
+// 保存时刻取自配置的时钟；解读给出 null 时清除，未给出时不变
+#[actix_web::test]
+async fn created_at_uses_clock_and_interpretation_can_be_cleared() {
+    let storage = web::Data::new(Storage::open(Path::new(":memory:")).unwrap());
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::Data::new(super::http::config_at("2025-06-01T10:00")))
+            .app_data(storage.clone())
+            .configure(crate::routes),
+    )
+    .await;
+    let req = actix_test::TestRequest::post()
+        .uri("/api/v1/readings")
+        .set_json(json!({"numbers": "311113", "interpretation": "吉"}))
+        .to_request();
+    let created: Value = actix_test::call_and_read_body_json(&app, req).await;
+    assert_eq!(created["created_at"], "2025-06-01T10:00:00");
+    assert_eq!(created["interpretation"], "吉");
+
+    let uri = format!("/api/v1/readings/{}", created["id"]);
+    let patch = |body: Value| {
+        actix_test::TestRequest::patch()
+            .uri(&uri)
+            .set_json(body)
+            .to_request()
+    };
+    let kept: Value =
+        actix_test::call_and_read_body_json(&app, patch(json!({"notes": "已应"}))).await;
+    assert_eq!(kept["interpretation"], "吉");
+    assert_eq!(kept["notes"], "已应");
+    let cleared: Value =
+        actix_test::call_and_read_body_json(&app, patch(json!({"interpretation": null}))).await;
+    assert_eq!(cleared["interpretation"], Value::Null);
+    assert_eq!(cleared["notes"], "已应");
+}
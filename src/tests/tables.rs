@@ -0,0 +1,120 @@
+//! 编码查表与原先按字符串索引查八宫表的结果逐卦比对。
+
+use crate::{
+    gua_table::{lookup, Generation, GUA_TABLE},
+    ChongHe, DiZhi, WuXing, SIXTYFOURGUA_DATA,
+};
+
+// 原先的字符串索引，'1' 阳 '2' 阴，初爻在前
+fn index_str(code: u8) -> String {
+    (0..6)
+        .map(|i| if code >> i & 1 == 1 { '1' } else { '2' })
+        .collect()
+}
+
+// 原先的纳甲查法：内外卦字符串各自在八宫表中查找
+fn najia_by_search(index: &str) -> [DiZhi; 6] {
+    let (nei, wai) = index.split_at(3);
+    let nei = SIXTYFOURGUA_DATA.iter().find(|h| h.index == nei).unwrap();
+    let wai = SIXTYFOURGUA_DATA.iter().find(|h| h.index == wai).unwrap();
+    let mut dizhi = [DiZhi::Zi; 6];
+    dizhi[0..3].copy_from_slice(&nei.nei_dizhi);
+    dizhi[3..6].copy_from_slice(&wai.wai_dizhi);
+    dizhi
+}
+
+fn palace_by_search(index: &str) -> (WuXing, &'static str) {
+    SIXTYFOURGUA_DATA
+        .iter()
+        .find_map(|gua| {
+            gua.gua_index
+                .iter()
+                .position(|&idx| idx == index)
+                .map(|pos| (gua.palace_element, gua.gua_name[pos]))
+        })
+        .unwrap()
+}
+
+// 原先的寻世诀：天同二世天变五，地同四世地变初，本宫六世三世异，人同游魂人变归
+fn shi_ying_by_rule(index: &str) -> (usize, usize) {
+    let b = index.as_bytes();
+    let same = [b[0] == b[3], b[1] == b[4], b[2] == b[5]];
+    match same {
+        [false, false, true] => (1, 4),
+        [true, true, false] => (4, 1),
+        [true, false, false] => (3, 0),
+        [false, true, true] => (0, 3),
+        [false, true, false] => (3, 0),
+        [true, false, true] => (2, 5),
+        [true, true, true] => (5, 2),
+        [false, false, false] => (2, 5),
+    }
+}
+
+#[test]
+fn table_matches_string_lookups() {
+    for code in 0..64u8 {
+        let index = index_str(code);
+        let info = lookup(code);
+        let (palace_element, name) = palace_by_search(&index);
+        assert_eq!(info.najia, najia_by_search(&index), "{}", index);
+        assert_eq!(info.palace_element(), palace_element, "{}", index);
+        assert_eq!(info.name, name, "{}", index);
+        assert_eq!(
+            (info.shi(), info.ying()),
+            shi_ying_by_rule(&index),
+            "{}",
+            name
+        );
+        let chong_he = match info.chong_he {
+            Some(ChongHe::Chong) => "(六冲)",
+            Some(ChongHe::He) => "(六合)",
+            None => "",
+        };
+        assert_eq!(
+            !chong_he.is_empty() && name.ends_with(chong_he),
+            info.chong_he.is_some(),
+            "{}",
+            name
+        );
+        assert!(SIXTYFOURGUA_DATA[info.palace]
+            .gua_index
+            .contains(&index.as_str()));
+    }
+}
+
+// 八宫中六冲卦十个、六合卦八个
+#[test]
+fn chong_he_counts() {
+    let count = |relation| {
+        GUA_TABLE
+            .iter()
+            .filter(|g| g.chong_he == Some(relation))
+            .count()
+    };
+    assert_eq!(count(ChongHe::Chong), 10);
+    assert_eq!(count(ChongHe::He), 8);
+}
+
+#[test]
+fn generation_follows_palace_order() {
+    for palace in SIXTYFOURGUA_DATA.iter() {
+        let generations: Vec<Generation> = palace
+            .gua_index
+            .iter()
+            .map(|index| {
+                let code = (0..6)
+                    .filter(|&i| index.as_bytes()[i] == b'1')
+                    .fold(0u8, |code, i| code | 1 << i);
+                GUA_TABLE[code as usize].generation
+            })
+            .collect();
+        assert_eq!(
+            generations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["本宫", "一世", "二世", "三世", "四世", "五世", "游魂", "归魂"]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! 应期推算的测试：旬空的冲空、出空之日，月破的出月，按月的值月，以及化墓。
+
+use crate::storage::YingQiUnit;
+use crate::yingqi::{estimate, Candidate};
+use crate::{get_ganzhi_info, ZiShi};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+fn at(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").unwrap()
+}
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn by_rule<'a>(candidates: &'a [Candidate], rule: &str) -> Vec<&'a Candidate> {
+    candidates.iter().filter(|c| c.rule == rule).collect()
+}
+
+// 每个候选日期的干支都与历法逐日计算的一致，按月的候选是该月的第一天
+fn assert_consistent(candidates: &[Candidate]) {
+    for candidate in candidates {
+        let noon = |date: NaiveDate| date.and_hms_opt(12, 0, 0).unwrap();
+        let (_, month, day, _) = get_ganzhi_info(noon(candidate.date), ZiShi::default());
+        match candidate.unit {
+            YingQiUnit::Day => assert_eq!(candidate.ganzhi, day, "{:?}", candidate),
+            YingQiUnit::Month => {
+                assert_eq!(candidate.ganzhi, month, "{:?}", candidate);
+                let (_, previous, _, _) =
+                    get_ganzhi_info(noon(candidate.date - Duration::days(1)), ZiShi::default());
+                assert_ne!(previous, month, "{:?}", candidate);
+            }
+        }
+    }
+}
+
+// 甲寅日起卦，子丑旬空：初爻丑土发动，逢未日冲空、逢丑日出空
+#[test]
+fn xun_kong_is_filled_by_chong_and_chu_kong() {
+    let response = estimate("013212", at("2025-06-14T10:00"), ZiShi::Split, 1, 60, 12).unwrap();
+    assert_eq!(response.yao, "丑土");
+    assert_eq!(response.states, ["旬空", "动"]);
+    assert_consistent(&response.candidates);
+
+    let chong = by_rule(&response.candidates, "冲空");
+    assert_eq!(chong.len(), 3);
+    assert_eq!(chong[0].date, date("2025-06-19"));
+    assert_eq!(chong[0].ganzhi, "己未");
+    assert_eq!(chong[1].date - chong[0].date, Duration::days(12));
+
+    let chu = by_rule(&response.candidates, "出空");
+    assert_eq!(chu[0].date, date("2025-06-25"));
+    assert_eq!(chu[0].ganzhi, "乙丑");
+    // 权重最高的排在最前
+    assert_eq!(response.candidates[0].rule, "冲空");
+}
+
+// 午月子水月破：出月应在小暑后的未月，事缓则值月应在子月
+#[test]
+fn month_unit_predictions() {
+    let response = estimate("013212", at("2025-06-14T10:00"), ZiShi::Split, 6, 60, 12).unwrap();
+    assert_eq!(response.yao, "子水");
+    assert!(response.states.contains(&"月破"));
+    assert_consistent(&response.candidates);
+
+    let chu_yue = by_rule(&response.candidates, "出月");
+    assert_eq!(chu_yue.len(), 1);
+    assert_eq!(chu_yue[0].unit, YingQiUnit::Month);
+    assert_eq!(chu_yue[0].ganzhi, "癸未");
+    assert_eq!(chu_yue[0].date, date("2025-07-07"));
+
+    let zhi_yue = by_rule(&response.candidates, "值月");
+    assert_eq!(zhi_yue.len(), 1);
+    assert_eq!(zhi_yue[0].ganzhi, "戊子");
+    assert_eq!(zhi_yue[0].date.format("%Y-%m").to_string(), "2025-12");
+}
+
+// 寅月申金月破，惊蛰在 2025-03-05 16:07，出月应在交节当天
+#[test]
+fn month_change_after_noon_is_on_jie_day() {
+    let response = estimate("111111", at("2025-02-20T10:00"), ZiShi::Split, 5, 60, 12).unwrap();
+    assert_eq!(response.yao, "申金");
+    assert!(response.states.contains(&"月破"));
+    let chu_yue = by_rule(&response.candidates, "出月");
+    assert_eq!(chu_yue[0].ganzhi, "己卯");
+    assert_eq!(chu_yue[0].date, date("2025-03-05"));
+}
+
+// 三爻申金发动化丑，是化入墓中而不是日辰入墓
+#[test]
+fn hua_mu_reason() {
+    let response = estimate("003000", at("2025-06-14T10:00"), ZiShi::Split, 3, 60, 12).unwrap();
+    assert_eq!(response.yao, "申金");
+    assert!(response.states.contains(&"化墓"));
+    let chong_mu = by_rule(&response.candidates, "冲墓");
+    assert_eq!(chong_mu[0].reason, "申金化入丑墓，逢未日冲开墓库");
+}
+
+#[test]
+fn range_is_capped() {
+    assert!(estimate("013212", at("2025-06-14T10:00"), ZiShi::Split, 1, 367, 12).is_err());
+    assert!(estimate("013212", at("2025-06-14T10:00"), ZiShi::Split, 1, 60, 25).is_err());
+    assert!(estimate("013212", at("2025-06-14T10:00"), ZiShi::Split, 7, 60, 12).is_err());
+}
@@ -0,0 +1,131 @@
+//! 周易经传文本的完整性和朱熹占法规则。
+
+use crate::hexagram::Hexagram;
+use crate::zhouyi::{self, Emphasis};
+
+const LINE_NAMES: [&str; 6] = ["初", "二", "三", "四", "五", "上"];
+
+#[test]
+fn texts_cover_all_64_hexagrams_in_king_wen_order() {
+    let texts = zhouyi::all();
+    assert_eq!(texts.len(), 64);
+    for (i, text) in texts.iter().enumerate() {
+        assert_eq!(text.number as usize, i + 1);
+        for field in [&text.guaci, &text.tuan, &text.xiang] {
+            assert!(!field.is_empty(), "{} 缺少经文", text.name);
+        }
+        // 卦名与排盘用的卦名一致，例如 "姤" 对 "天风姤"，"乾" 对 "乾为天"
+        let name = Hexagram::from_king_wen(text.number).unwrap().name();
+        assert!(
+            name.ends_with(text.name.as_str()) || name.starts_with(&format!("{}为", text.name)),
+            "{} 与 {} 不符",
+            text.name,
+            name
+        );
+    }
+}
+
+#[test]
+fn line_titles_match_line_position_and_polarity() {
+    for text in zhouyi::all() {
+        let hexagram = Hexagram::from_king_wen(text.number).unwrap();
+        for (i, yao) in text.yao.iter().enumerate() {
+            let number = if hexagram.code() >> i & 1 == 1 {
+                "九"
+            } else {
+                "六"
+            };
+            let title = match i {
+                0 | 5 => format!("{}{}：", LINE_NAMES[i], number),
+                _ => format!("{}{}：", number, LINE_NAMES[i]),
+            };
+            assert!(
+                yao.starts_with(&title),
+                "{} 的 {} 应以 {} 开头",
+                text.name,
+                yao,
+                title
+            );
+        }
+    }
+}
+
+#[test]
+fn only_qian_and_kun_have_yong() {
+    for text in zhouyi::all() {
+        match text.number {
+            1 => assert!(text.yong.as_deref().unwrap().starts_with("用九：")),
+            2 => assert!(text.yong.as_deref().unwrap().starts_with("用六：")),
+            _ => assert!(text.yong.is_none(), "{} 不应有用九用六", text.name),
+        }
+    }
+}
+
+fn emphasis(numbers: &str) -> Emphasis {
+    zhouyi::lookup(numbers).unwrap().emphasis
+}
+
+fn quote(emphasis: &Emphasis) -> (&str, Option<usize>) {
+    (emphasis.main.gua, emphasis.main.line)
+}
+
+#[test]
+fn zhu_xi_rules_pick_main_text_by_changing_count() {
+    // 乾为天静卦，占本卦卦辞
+    let e = emphasis("111111");
+    assert_eq!((e.changing, quote(&e)), (0, ("乾", None)));
+    assert_eq!(e.main.text, "元亨利贞。");
+
+    // 乾初爻动，占乾初九
+    let e = emphasis("311111");
+    assert_eq!((e.changing, quote(&e)), (1, ("乾", Some(1))));
+    assert!(e.main.text.starts_with("初九：潜龙勿用"));
+
+    // 乾初、上爻动，以上九为主
+    let e = emphasis("311113");
+    assert_eq!(quote(&e), ("乾", Some(6)));
+    assert_eq!(e.others[0].line, Some(1));
+
+    // 乾下三爻动之否，占乾、否卦辞，以乾为主
+    let e = emphasis("333111");
+    assert_eq!(quote(&e), ("乾", None));
+    assert_eq!((e.others[0].gua, e.others[0].line), ("否", None));
+
+    // 乾下四爻动之观，占观五、上两不变爻，以九五为主
+    let e = emphasis("333311");
+    assert_eq!(quote(&e), ("观", Some(5)));
+    assert!(e.main.text.starts_with("九五："));
+    assert_eq!(e.others[0].line, Some(6));
+
+    // 乾五爻动之剥，占剥上九
+    let e = emphasis("333331");
+    assert_eq!(quote(&e), ("剥", Some(6)));
+    assert!(e.main.text.starts_with("上九：硕果不食"));
+}
+
+#[test]
+fn all_changing_uses_yong_for_qian_kun_and_zhi_gua_otherwise() {
+    let e = emphasis("333333");
+    assert_eq!(quote(&e), ("乾", None));
+    assert_eq!(e.main.text, "用九：见群龙无首，吉。");
+
+    let e = emphasis("000000");
+    assert_eq!(e.main.text, "用六：利永贞。");
+
+    // 既济六爻皆动之未济，占未济卦辞
+    let e = emphasis("303030");
+    assert_eq!(quote(&e), ("未济", None));
+    assert_eq!(e.others[0].gua, "既济");
+}
+
+#[test]
+fn lookup_returns_changing_lines_and_zhi_gua() {
+    let texts = zhouyi::lookup("311113").unwrap();
+    assert_eq!(texts.hexagram.name, "乾");
+    assert_eq!(texts.bian_hexagram.unwrap().name, "大过");
+    let lines: Vec<_> = texts.changing_lines.iter().map(|q| q.line).collect();
+    assert_eq!(lines, [Some(1), Some(6)]);
+
+    assert!(zhouyi::lookup("111111").unwrap().bian_hexagram.is_none());
+    assert!(zhouyi::lookup("11111").is_err());
+}
@@ -0,0 +1,365 @@
+use crate::{
+    build_guas, calculate_xun_kong, cli::parse_datetime, config::Config, get_ganzhi_info,
+    locale::Locale, parse_xun_kong, storage::YingQiUnit, DiZhi, PaipanError, Yao, ZiShi,
+    JIAZI_CYCLE, SUPPORTED_YEARS,
+};
+use actix_web::{web, HttpResponse, Responder};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+/// 同一条规则最多列出的候选日期数，避免同一地支每十二天重复出现刷屏。
+const MAX_PER_RULE: usize = 3;
+/// 查询范围上限。
+const MAX_DAYS: u32 = 366;
+const MAX_MONTHS: u32 = 24;
+/// 相邻两个节至少相隔二十九天，换月后这么多天内月柱不会再变，不必逐日计算。
+const MIN_MONTH_DAYS: u32 = 29;
+
+fn default_days() -> u32 {
+    60
+}
+
+fn default_months() -> u32 {
+    12
+}
+
+/// 应期推算请求：起卦数字、起卦时间、用神所在爻位（初爻为1）和查询范围。
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct YingQiRequest {
+    numbers: String,
+    at: Option<String>,
+    /// 用神所在爻位，初爻为1
+    line: usize,
+    /// 按日推算的天数，默认 60，最多 366
+    #[serde(default = "default_days")]
+    days: u32,
+    /// 按月推算的月数，默认 12，最多 24
+    #[serde(default = "default_months")]
+    months: u32,
+    /// 23点子时的日柱归属，缺省时用配置的约定
+    zi_shi: Option<ZiShi>,
+    /// 文本字段的语言，缺省时使用配置
+    locale: Option<Locale>,
+}
+
+/// 一个候选应期。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct Candidate {
+    pub date: NaiveDate,
+    /// 当日的日干支或当月的月干支
+    pub ganzhi: String,
+    pub unit: YingQiUnit,
+    pub rule: &'static str,
+    pub reason: String,
+    /// 规则的权重，越大越优先
+    pub weight: u8,
+}
+
+/// 用神的状态及按权重排序的候选应期。
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct YingQiResponse {
+    pub line: usize,
+    /// 用神的地支五行，例如 "午火"
+    pub yao: String,
+    /// 用神的状态，例如 ["旬空", "静"]
+    pub states: Vec<&'static str>,
+    pub candidates: Vec<Candidate>,
+}
+
+impl YingQiResponse {
+    /// 把用神、状态、规则和理由译成指定语言；推算始终用简体，只在输出前翻译。
+    pub fn localize(&mut self, locale: Locale) {
+        self.yao = locale.text(&self.yao);
+        for state in &mut self.states {
+            *state = locale.term(state);
+        }
+        for candidate in &mut self.candidates {
+            candidate.ganzhi = locale.text(&candidate.ganzhi);
+            candidate.rule = locale.term(candidate.rule);
+            candidate.reason = locale.text(&candidate.reason);
+        }
+    }
+}
+
+// 一条应期规则：在哪个地支的日/月应验，None 表示不论地支（如出月）
+struct Target {
+    zhi: Option<DiZhi>,
+    unit: YingQiUnit,
+    rule: &'static str,
+    reason: String,
+    weight: u8,
+}
+
+/// 推算应期时可能出现的错误。
+#[derive(Debug)]
+pub enum YingQiError {
+    Paipan(PaipanError),
+    InvalidLine(usize),
+    InvalidRange,
+}
+
+impl std::fmt::Display for YingQiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YingQiError::Paipan(e) => write!(f, "{}", e),
+            YingQiError::InvalidLine(line) => write!(f, "用神爻位必须在1-6之间，收到 {}", line),
+            YingQiError::InvalidRange => {
+                write!(f, "查询范围过大，最多 {} 天、{} 个月", MAX_DAYS, MAX_MONTHS)
+            }
+        }
+    }
+}
+
+impl std::error::Error for YingQiError {}
+
+/// 根据用神爻的状态（旬空、月破、入墓、动静）列出未来的候选应期，按权重和先后排序。
+pub fn estimate(
+    numbers: &str,
+    at: NaiveDateTime,
+    zi_shi: ZiShi,
+    line: usize,
+    days: u32,
+    months: u32,
+) -> Result<YingQiResponse, YingQiError> {
+    if !(1..=6).contains(&line) {
+        return Err(YingQiError::InvalidLine(line));
+    }
+    if days > MAX_DAYS || months > MAX_MONTHS {
+        return Err(YingQiError::InvalidRange);
+    }
+    let (zheng_gua, bian_gua) = build_guas(numbers).map_err(YingQiError::Paipan)?;
+    if !SUPPORTED_YEARS.contains(&(at.year() as i64)) {
+        return Err(YingQiError::Paipan(PaipanError::DateOutOfRange(at)));
+    }
+    let i = line - 1;
+    let zhi = zheng_gua.dizhi[i];
+    let wuxing = zheng_gua.wuxing[i];
+    let changing = matches!(zheng_gua.yao_xiang[i], Yao::YinChanging | Yao::YangChanging);
+
+    let (_, month_ganzhi, day_ganzhi, _) = get_ganzhi_info(at, zi_shi);
+    let month_zhi = branch_of(&month_ganzhi);
+    let day_zhi = branch_of(&day_ganzhi);
+    let xun_kong = calculate_xun_kong(&day_ganzhi).and_then(parse_xun_kong);
+
+    let yao = format!("{}{}", zhi, wuxing);
+    let mut states = Vec::new();
+    let mut targets = Vec::new();
+
+    if xun_kong.is_some_and(|(a, b)| zhi == a || zhi == b) {
+        states.push("旬空");
+        targets.push(Target {
+            zhi: Some(zhi.chong()),
+            unit: YingQiUnit::Day,
+            rule: "冲空",
+            reason: format!("{}旬空，逢{}日冲空则实", yao, zhi.chong()),
+            weight: 5,
+        });
+        targets.push(Target {
+            zhi: Some(zhi),
+            unit: YingQiUnit::Day,
+            rule: "出空",
+            reason: format!("{}旬空，出旬逢{}日填实", yao, zhi),
+            weight: 5,
+        });
+    }
+    if month_zhi.is_some_and(|m| m.chong() == zhi) {
+        states.push("月破");
+        targets.push(Target {
+            zhi: Some(zhi),
+            unit: YingQiUnit::Day,
+            rule: "填实",
+            reason: format!("{}月破，逢{}日填实", yao, zhi),
+            weight: 4,
+        });
+        targets.push(Target {
+            zhi: None,
+            unit: YingQiUnit::Month,
+            rule: "出月",
+            reason: format!("{}月破，出了本月则破而不破", yao),
+            weight: 3,
+        });
+    }
+    let mu = wuxing.mu_ku();
+    let ru_mu = day_zhi == Some(mu);
+    let hua_mu = changing && bian_gua.dizhi[i] == mu;
+    if ru_mu || hua_mu {
+        states.push(if ru_mu { "入墓" } else { "化墓" });
+        // 日辰为墓是入墓，动爻变出墓库是化入墓中
+        let ru = if ru_mu { "入" } else { "化入" };
+        targets.push(Target {
+            zhi: Some(mu.chong()),
+            unit: YingQiUnit::Day,
+            rule: "冲墓",
+            reason: format!("{}{}{}墓，逢{}日冲开墓库", yao, ru, mu, mu.chong()),
+            weight: 4,
+        });
+    }
+    if changing {
+        states.push("动");
+        targets.push(Target {
+            zhi: Some(zhi.he()),
+            unit: YingQiUnit::Day,
+            rule: "逢合",
+            reason: format!("{}发动，动而逢合，应在{}日", yao, zhi.he()),
+            weight: 3,
+        });
+    } else {
+        states.push("静");
+        targets.push(Target {
+            zhi: Some(zhi.chong()),
+            unit: YingQiUnit::Day,
+            rule: "逢冲",
+            reason: format!("{}安静，静而逢冲，应在{}日", yao, zhi.chong()),
+            weight: 3,
+        });
+    }
+    targets.push(Target {
+        zhi: Some(zhi),
+        unit: YingQiUnit::Day,
+        rule: "值日",
+        reason: format!("用神{}值日", yao),
+        weight: 2,
+    });
+    targets.push(Target {
+        zhi: Some(zhi),
+        unit: YingQiUnit::Month,
+        rule: "值月",
+        reason: format!("用神{}值月，事缓则应在{}月", yao, zhi),
+        weight: 1,
+    });
+
+    let mut candidates = enumerate(&targets, at.date(), &month_ganzhi, days, months);
+    candidates.sort_by(|a, b| b.weight.cmp(&a.weight).then(a.date.cmp(&b.date)));
+
+    Ok(YingQiResponse {
+        line,
+        yao,
+        states,
+        candidates,
+    })
+}
+
+// 逐日向后推，找出日支/月支符合规则的日期；月以节气换月，取新月的第一天。
+// 日柱六十天一轮，由起始日递推；月柱只在可能交节的日子才查历法
+fn enumerate(
+    targets: &[Target],
+    start: NaiveDate,
+    start_month_ganzhi: &str,
+    days: u32,
+    months: u32,
+) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut found = vec![0usize; targets.len()];
+    let mut current_month = start_month_ganzhi.to_string();
+    let mut months_seen = 0;
+    let mut next_month_check = 0;
+    let noon = |date: NaiveDate| date.and_hms_opt(12, 0, 0).unwrap_or_default();
+    // 当天之内交节的，当天就算新月，所以取当天最后一刻的月柱
+    let end_of_day = |date: NaiveDate| date.and_hms_opt(23, 59, 59).unwrap_or_default();
+    let (_, _, start_day_ganzhi, _) = get_ganzhi_info(noon(start), ZiShi::default());
+    let Some(start_index) = JIAZI_CYCLE.iter().position(|gz| *gz == start_day_ganzhi) else {
+        return candidates;
+    };
+
+    // 一个节气月最长约31天，多扫一天保证能看到最后一个月的月初
+    for offset in 0..=days.max(months * 31 + 1) {
+        let pending = |unit: YingQiUnit| {
+            targets
+                .iter()
+                .zip(&found)
+                .any(|(target, count)| target.unit == unit && *count < MAX_PER_RULE)
+        };
+        let days_left = offset <= days && pending(YingQiUnit::Day);
+        let months_left = months_seen < months && pending(YingQiUnit::Month);
+        if !days_left && !months_left {
+            break;
+        }
+        let date = start + Duration::days(offset as i64);
+        // 推到支持范围之外就停，不再向后查历法
+        if !SUPPORTED_YEARS.contains(&(date.year() as i64)) {
+            break;
+        }
+        let day_ganzhi = JIAZI_CYCLE[(start_index + offset as usize) % 60].to_string();
+        let mut new_month = false;
+        if months_left && offset >= next_month_check {
+            let (_, month_ganzhi, _, _) = get_ganzhi_info(end_of_day(date), ZiShi::default());
+            if month_ganzhi != current_month {
+                new_month = true;
+                months_seen += 1;
+                current_month = month_ganzhi;
+                next_month_check = offset + MIN_MONTH_DAYS;
+            }
+        }
+
+        for (target, count) in targets.iter().zip(found.iter_mut()) {
+            if *count >= MAX_PER_RULE {
+                continue;
+            }
+            let ganzhi = match target.unit {
+                YingQiUnit::Day if offset <= days => &day_ganzhi,
+                YingQiUnit::Month if new_month && months_seen <= months => &current_month,
+                _ => continue,
+            };
+            let matches = match target.zhi {
+                Some(zhi) => branch_of(ganzhi) == Some(zhi),
+                // 出月只取下一个月的月初
+                None => months_seen == 1,
+            };
+            if matches {
+                *count += 1;
+                candidates.push(Candidate {
+                    date,
+                    ganzhi: ganzhi.clone(),
+                    unit: target.unit,
+                    rule: target.rule,
+                    reason: target.reason.clone(),
+                    weight: target.weight,
+                });
+            }
+        }
+    }
+    candidates
+}
+
+// 取干支中的地支，例如 "壬午" -> 午
+fn branch_of(ganzhi: &str) -> Option<DiZhi> {
+    ganzhi
+        .chars()
+        .nth(1)
+        .and_then(|c| DiZhi::try_from(c.to_string().as_str()).ok())
+}
+
+/// 根据用神爻的旬空、月破、入墓和动静推算候选应期，按权重和先后排序。
+#[utoipa::path(
+    post,
+    path = "/api/v1/yingqi",
+    tag = "readings",
+    request_body = YingQiRequest,
+    responses(
+        (status = 200, description = "用神状态和候选应期", body = YingQiResponse),
+        (status = 400, description = "卦数、爻位、时间或查询范围无效", body = crate::api::ApiError),
+    )
+)]
+pub async fn yingqi(config: web::Data<Config>, req: web::Json<YingQiRequest>) -> impl Responder {
+    let at = match &req.at {
+        Some(at) => match parse_datetime(at) {
+            Ok(at) => at,
+            Err(e) => return HttpResponse::BadRequest().json(e),
+        },
+        None => config.now(),
+    };
+    match estimate(
+        &req.numbers,
+        at,
+        req.zi_shi.unwrap_or(config.zi_shi),
+        req.line,
+        req.days,
+        req.months,
+    ) {
+        Ok(mut response) => {
+            response.localize(req.locale.unwrap_or(config.locale));
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => HttpResponse::BadRequest().json(e.to_string()),
+    }
+}
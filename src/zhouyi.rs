@@ -0,0 +1,201 @@
+use crate::{build_guas, hexagram::Hexagram, locale::Locale, PaipanError, Yao};
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// 一卦的经传文本，来自 data/zhouyi.toml。
+#[derive(Debug, Deserialize)]
+pub struct GuaText {
+    /// 文王卦序
+    pub number: u32,
+    /// 卦名，例如 "乾"、"同人"
+    pub name: String,
+    pub guaci: String,
+    pub tuan: String,
+    /// 大象传
+    pub xiang: String,
+    /// 初爻到上爻的爻辞，带爻题，例如 "初九：潜龙勿用。"
+    pub yao: [String; 6],
+    /// 乾坤两卦的用九、用六
+    pub yong: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ZhouYi {
+    gua: Vec<GuaText>,
+}
+
+/// 按文王卦序排列的六十四卦文本，首次使用时解析内置的 TOML。
+pub fn all() -> &'static [GuaText] {
+    static TEXTS: OnceLock<Vec<GuaText>> = OnceLock::new();
+    TEXTS.get_or_init(|| {
+        toml::from_str::<ZhouYi>(include_str!("../data/zhouyi.toml"))
+            .expect("内置的周易文本格式有误")
+            .gua
+    })
+}
+
+/// 取一卦的经传文本。
+pub fn text(hexagram: Hexagram) -> &'static GuaText {
+    &all()[hexagram.king_wen() as usize - 1]
+}
+
+/// 一段引用的经文。
+#[derive(Debug, Serialize)]
+pub struct Quote {
+    /// 卦名，例如 "乾"
+    pub gua: &'static str,
+    /// 爻位（初爻为1），卦辞、用九用六为 None
+    pub line: Option<usize>,
+    pub text: &'static str,
+}
+
+/// 按朱熹《易学启蒙·考变占》的规则选出的占辞。
+#[derive(Debug, Serialize)]
+pub struct Emphasis {
+    /// 动爻个数
+    pub changing: usize,
+    /// 所依据的规则
+    pub rule: &'static str,
+    /// 为主的占辞
+    pub main: Quote,
+    /// 参看的占辞
+    pub others: Vec<Quote>,
+}
+
+/// 一卦在接口中的文本。
+#[derive(Debug, Serialize)]
+pub struct GuaTextResponse {
+    pub name: &'static str,
+    pub symbol: char,
+    pub number: u32,
+    pub guaci: &'static str,
+    pub tuan: &'static str,
+    pub xiang: &'static str,
+    pub yong: Option<&'static str>,
+}
+
+impl From<Hexagram> for GuaTextResponse {
+    fn from(hexagram: Hexagram) -> Self {
+        let text = text(hexagram);
+        GuaTextResponse {
+            name: &text.name,
+            symbol: hexagram.symbol(),
+            number: text.number,
+            guaci: &text.guaci,
+            tuan: &text.tuan,
+            xiang: &text.xiang,
+            yong: text.yong.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TextsResponse {
+    pub hexagram: GuaTextResponse,
+    /// 没有动爻时为 None
+    pub bian_hexagram: Option<GuaTextResponse>,
+    /// 本卦各动爻的爻辞
+    pub changing_lines: Vec<Quote>,
+    pub emphasis: Emphasis,
+}
+
+fn guaci(hexagram: Hexagram) -> Quote {
+    let text = text(hexagram);
+    Quote {
+        gua: &text.name,
+        line: None,
+        text: &text.guaci,
+    }
+}
+
+fn yaoci(hexagram: Hexagram, line: usize) -> Quote {
+    let text = text(hexagram);
+    Quote {
+        gua: &text.name,
+        line: Some(line + 1),
+        text: &text.yao[line],
+    }
+}
+
+/// 按动爻个数决定以哪段经文为占：
+/// 无动爻占本卦卦辞；一爻动占本卦变爻；二爻动占本卦二变爻，以上爻为主；
+/// 三爻动占本卦及之卦卦辞，以本卦为主；四爻动占之卦二不变爻，以下爻为主；
+/// 五爻动占之卦不变爻；六爻皆动，乾坤占用九用六，余卦占之卦卦辞。
+pub fn emphasis(ben: Hexagram, zhi: Hexagram, changing: &[usize]) -> Emphasis {
+    let unchanged: Vec<usize> = (0..6).filter(|i| !changing.contains(i)).collect();
+    let (rule, main, others) = match changing {
+        [] => ("六爻皆不变，占本卦卦辞", guaci(ben), vec![]),
+        [line] => ("一爻变，以本卦变爻辞占", yaoci(ben, *line), vec![]),
+        [lower, upper] => (
+            "二爻变，以本卦二变爻辞占，以上爻为主",
+            yaoci(ben, *upper),
+            vec![yaoci(ben, *lower)],
+        ),
+        [_, _, _] => (
+            "三爻变，占本卦及之卦卦辞，以本卦为主",
+            guaci(ben),
+            vec![guaci(zhi)],
+        ),
+        [_, _, _, _] => (
+            "四爻变，以之卦二不变爻占，以下爻为主",
+            yaoci(zhi, unchanged[0]),
+            vec![yaoci(zhi, unchanged[1])],
+        ),
+        [_, _, _, _, _] => ("五爻变，以之卦不变爻占", yaoci(zhi, unchanged[0]), vec![]),
+        _ => match &text(ben).yong {
+            Some(yong) => (
+                "六爻皆变，乾坤占用九用六",
+                Quote {
+                    gua: &text(ben).name,
+                    line: None,
+                    text: yong,
+                },
+                vec![guaci(zhi)],
+            ),
+            None => ("六爻皆变，占之卦卦辞", guaci(zhi), vec![guaci(ben)]),
+        },
+    };
+    Emphasis {
+        changing: changing.len(),
+        rule,
+        main,
+        others,
+    }
+}
+
+/// 根据六次阳面个数取本卦、之卦和动爻的经文。
+pub fn lookup(numbers: &str) -> Result<TextsResponse, PaipanError> {
+    let (zheng_gua, bian_gua) = build_guas(numbers)?;
+    let ben = Hexagram::from_code(zheng_gua.code);
+    let zhi = Hexagram::from_code(bian_gua.code);
+    let changing: Vec<usize> = (0..6)
+        .filter(|&i| matches!(zheng_gua.yao_xiang[i], Yao::YinChanging | Yao::YangChanging))
+        .collect();
+
+    Ok(TextsResponse {
+        hexagram: ben.into(),
+        bian_hexagram: (!changing.is_empty()).then(|| zhi.into()),
+        changing_lines: changing.iter().map(|&i| yaoci(ben, i)).collect(),
+        emphasis: emphasis(ben, zhi, &changing),
+    })
+}
+
+#[derive(Deserialize)]
+pub struct TextsQuery {
+    numbers: String,
+    /// 经文只有简体中文原文，给出其他语言时拒绝，不使用配置的语言
+    locale: Option<Locale>,
+}
+
+/// 查询本卦、之卦的卦辞、彖传、大象传和动爻爻辞，例如 /texts?numbers=311113。
+pub async fn texts(query: web::Query<TextsQuery>) -> impl Responder {
+    if let Some(locale) = query.locale.filter(|&l| l != Locale::ZhHans) {
+        return HttpResponse::BadRequest()
+            .json(format!("经文只有简体中文原文，不支持 locale={}", locale));
+    }
+    match lookup(&query.numbers) {
+        Ok(texts) => HttpResponse::Ok().json(texts),
+        Err(e) => HttpResponse::BadRequest().json(e.to_string()),
+    }
+}
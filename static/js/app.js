@@ -1,54 +1,111 @@
-// 页面加载完成后，立即生成地支掌诀图
+// 页面加载完成后，立即生成地支掌诀图，并按上次选择的语言翻译界面
 document.addEventListener("DOMContentLoaded", () => {
 	createDizhiGrid();
+	const select = document.getElementById("localeSelect");
+	select.value = locale;
+	select.addEventListener("change", () => setLocale(select.value));
+	setLocale(locale);
 });
 
-function addWuxingColorClass(element, branchChar) {
+// 当前语言及其译名表（键为简体原文），译名表由 /locales/{locale} 提供
+let locale = localStorage.getItem("locale") || "zh-Hans";
+let terms = {};
+
+function t(text) {
+	return terms[text] || text;
+}
+
+// 英文和拼音按词书写，标签与干支之间要空格
+function spaced() {
+	return !locale.startsWith("zh");
+}
+
+async function setLocale(next) {
+	try {
+		const response = await fetch(`/locales/${next}`);
+		if (!response.ok) return;
+		terms = await response.json();
+	} catch (error) {
+		console.error("Error:", error);
+		return;
+	}
+	locale = next;
+	localStorage.setItem("locale", locale);
+	document.documentElement.lang = locale;
+	document.querySelectorAll("[data-i18n]").forEach((element) => {
+		element.textContent = t(element.dataset.i18n);
+	});
+	document.querySelectorAll("[data-i18n-placeholder]").forEach((element) => {
+		element.placeholder = t(element.dataset.i18nPlaceholder);
+	});
+	document.querySelectorAll("[data-i18n-alt]").forEach((element) => {
+		element.alt = t(element.dataset.i18nAlt);
+	});
+}
+
+// 地支的五行，与接口中 wuxing 字段的取值一致
+const BRANCH_WUXING = {
+	子: "shui",
+	亥: "shui",
+	寅: "mu",
+	卯: "mu",
+	巳: "huo",
+	午: "huo",
+	申: "jin",
+	酉: "jin",
+	辰: "tu",
+	戌: "tu",
+	丑: "tu",
+	未: "tu",
+};
+
+const WUXING_CLASS = {
+	jin: "metal",
+	mu: "wood",
+	shui: "water",
+	huo: "fire",
+	tu: "earth",
+};
+
+function addElementColorClass(element, wuxing) {
 	// 移除所有可能的五行类，确保清洁
 	element.classList.remove("earth", "wood", "fire", "metal", "water");
-
-	switch (branchChar) {
-		case "子":
-		case "亥":
-			element.classList.add("water");
-			break;
-		case "寅":
-		case "卯":
-			element.classList.add("wood");
-			break;
-		case "巳":
-		case "午":
-			element.classList.add("fire");
-			break;
-		case "申":
-		case "酉":
-			element.classList.add("metal");
-			break;
-		case "辰":
-		case "戌":
-		case "丑":
-		case "未":
-			element.classList.add("earth");
-			break;
+	if (WUXING_CLASS[wuxing]) {
+		element.classList.add(WUXING_CLASS[wuxing]);
 	}
 }
 
+function addWuxingColorClass(element, branchChar) {
+	addElementColorClass(element, BRANCH_WUXING[branchChar]);
+}
+
+// 取干支中的地支原文：中文直接取第二个字，英文和拼音取最后一个词反查译名表
+function branchOf(ganzhi) {
+	if (!spaced()) return ganzhi.charAt(1);
+	const name = ganzhi.split(" ").pop();
+	return Object.keys(BRANCH_WUXING).find((zhi) => t(zhi) === name);
+}
+
+function ganzhiLabel(ganzhi, unit) {
+	return spaced() ? `${ganzhi} ${t(unit)}` : ganzhi + t(unit);
+}
+
 async function generateGuaXiang() {
 	const input = document.getElementById("yaoInput").value;
 	if (input.length !== 6) {
-		alert("请输入6位数字（0-3）");
+		alert(t("请输入6位数字（0-3）"));
 		return;
 	}
 
 	const button = document.querySelector("button");
-	button.textContent = "推演中...";
+	button.textContent = t("推演中...");
 	button.disabled = true;
 
 	try {
-		const response = await fetch("/generate_gua_xian", {
+		const response = await fetch("/api/v1/cast", {
 			method: "POST",
 			headers: { "Content-Type": "application/json" },
-			body: JSON.stringify({ numbers: input }),
+			body: JSON.stringify({ numbers: input, locale }),
 		});
 
 		if (response.ok) {
@@ -61,17 +118,20 @@ async function generateGuaXiang() {
 			ganzhiInfoDiv.className = "ganzhi-info";
 			const ganzhiParts = [
 				{
-					text: data.year_ganzhi + "年",
-					branchChar: data.year_ganzhi.charAt(1),
+					text: ganzhiLabel(data.year_ganzhi, "年"),
+					branchChar: branchOf(data.year_ganzhi),
+				},
+				{
+					text: ganzhiLabel(data.month_ganzhi, "月"),
+					branchChar: branchOf(data.month_ganzhi),
 				},
 				{
-					text: data.month_ganzhi + "月",
-					branchChar: data.month_ganzhi.charAt(1),
+					text: ganzhiLabel(data.day_ganzhi, "日"),
+					branchChar: branchOf(data.day_ganzhi),
 				},
-				{ text: data.day_ganzhi + "日", branchChar: data.day_ganzhi.charAt(1) },
 				{
-					text: data.hour_ganzhi + "时",
-					branchChar: data.hour_ganzhi.charAt(0),
+					text: ganzhiLabel(data.hour_ganzhi, "时"),
+					branchChar: branchOf(data.hour_ganzhi),
 				},
 			];
 			ganzhiParts.forEach((part) => {
@@ -81,7 +141,7 @@ async function generateGuaXiang() {
 				ganzhiInfoDiv.appendChild(span);
 			});
 			const xunKongSpan = document.createElement('span');
-			xunKongSpan.textContent = `旬空:${data.xun_kong}`;
+			xunKongSpan.textContent = `${t("旬空")}:${data.xun_kong}`;
 			xunKongSpan.className = 'xun-kong-info';
 			ganzhiInfoDiv.appendChild(xunKongSpan);
 			resultContainer.appendChild(ganzhiInfoDiv);
@@ -118,14 +178,14 @@ async function generateGuaXiang() {
 					// 添加基础文本 (六亲、地支、五行、爻象)
 					const baseSpan = document.createElement("span");
 					baseSpan.textContent = lineData.base_text;
-					addWuxingColorClass(baseSpan, lineData.base_text.charAt(2));
+					addElementColorClass(baseSpan, lineData.yao.wuxing);
 					zhengDiv.appendChild(baseSpan);
 
 					// 如果是世或应，创建独立的、带样式的 <span>
 					if (lineData.role === "Shi" || lineData.role === "Ying") {
 						const roleSpan = document.createElement("span");
 						roleSpan.className = `role-tag ${lineData.role.toLowerCase()}-tag`;
-						roleSpan.textContent = lineData.role === "Shi" ? "世" : "应";
+						roleSpan.textContent = t(lineData.role === "Shi" ? "世" : "应");
 						zhengDiv.appendChild(roleSpan);
 					}
 
@@ -138,8 +198,8 @@ async function generateGuaXiang() {
 					// 变卦内容
 					const bianBaseSpan = document.createElement("span");
 					bianBaseSpan.textContent = lineData.bian_text;
-					addWuxingColorClass(bianBaseSpan, lineData.bian_text.charAt(2));
-					if (!lineData.is_changing) {
+					addElementColorClass(bianBaseSpan, lineData.bian_yao.wuxing);
+					if (!lineData.yao.changing) {
 						bianDiv.classList.add("not-changed");
 					}
 					bianDiv.appendChild(bianBaseSpan);
@@ -161,13 +221,13 @@ async function generateGuaXiang() {
 			resultContainer.appendChild(guaDisplayGrid);
 			resultContainer.classList.add("show");
 		} else {
-			alert("生成卦象失败，请重试");
+			alert(t("生成卦象失败，请重试"));
 		}
 	} catch (error) {
 		console.error("Error:", error);
-		alert("网络请求失败，请检查后端服务是否运行。");
+		alert(t("网络请求失败，请检查后端服务是否运行。"));
 	} finally {
-		button.textContent = "推演卦象";
+		button.textContent = t("推演卦象");
 		button.disabled = false;
 	}
 }